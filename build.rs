@@ -0,0 +1,21 @@
+fn main() {
+    // The header is generated into OUT_DIR, so that building doesn't
+    // touch the tree; with RATE_UPDATE_HEADER=1 it also replaces the
+    // committed include/rate.h. test_header checks that the two agree.
+    #[cfg(feature = "cdylib")]
+    {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out = std::env::var("OUT_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-env-changed=RATE_UPDATE_HEADER");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir))
+            .expect("cannot read cbindgen.toml");
+        let bindings =
+            cbindgen::generate_with_config(&dir, config).expect("cannot generate the C header");
+        bindings.write_to_file(format!("{}/rate.h", out));
+        if std::env::var_os("RATE_UPDATE_HEADER").is_some_and(|v| v == "1") {
+            bindings.write_to_file(format!("{}/include/rate.h", dir));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Feeds arbitrary input to every parser: none of them may panic, and
+//! whatever they accept must be a sensible amount.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The parsers take &str; lossy decoding still lets the fuzzer reach
+    // them with any byte, including the replacement character.
+    let s = String::from_utf8_lossy(data);
+    if let Ok(rate) = rate::parse(&s) {
+        assert!(rate.bytes_per_second() >= 0.0);
+        let _ = rate::format_table(rate);
+    }
+    if let Ok(bytes) = rate::parse_size(&s) {
+        assert!(bytes >= 0.0);
+        let _ = rate::format_size(bytes);
+    }
+    if let Ok(seconds) = rate::parse_duration(&s) {
+        assert!(seconds >= 0.0);
+        let _ = rate::format_duration(seconds);
+    }
+    let _ = rate::parse_quantity(&s);
+    let _ = rate::parse_frequency(&s);
+    let _ = rate::parse_percent(&s);
+});
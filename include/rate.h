@@ -0,0 +1,52 @@
+#ifndef RATE_H
+#define RATE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * The expression was parsed.
+ */
+#define RATE_OK 0
+
+/**
+ * A pointer argument was null, or the expression was not UTF-8.
+ */
+#define RATE_INVALID_ARGUMENT -1
+
+#define RATE_INVALID_NUMBER 1
+
+#define RATE_INVALID_UNIT 2
+
+#define RATE_INVALID_PERIOD 3
+
+#define RATE_UNEXPECTED_CHARACTER 4
+
+#define RATE_TRAILING_INPUT 5
+
+/**
+ * Parses the NUL-terminated rate expression `s` (e.g. "12 MB/s") and
+ * stores it in bytes per second into `bytes_per_second`. Returns
+ * `RATE_OK`, or the error code of why the expression is invalid.
+ *
+ * # Safety
+ *
+ * `s` must be null or a NUL-terminated string, and `bytes_per_second`
+ * null or a valid pointer to a double. Null pointers are rejected with
+ * `RATE_INVALID_ARGUMENT`.
+ */
+int32_t rate_parse(const char *s, double *bytes_per_second);
+
+/**
+ * Writes `bytes_per_second` as e.g. "50.000 MB / sec" into `buf`,
+ * truncated to `len` bytes including the terminating NUL, like
+ * snprintf. Returns the length of the full string, without the NUL.
+ *
+ * # Safety
+ *
+ * `buf` must point to at least `len` writable bytes, or be null with a
+ * `len` of 0.
+ */
+size_t rate_format(double bytes_per_second, char *buf, size_t len);
+
+#endif  /* RATE_H */
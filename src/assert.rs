@@ -0,0 +1,62 @@
+//! `rate assert`: exit with an error when a rate falls outside of the
+//! given bounds, so that scripts and CI jobs can gate on throughput.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_rate, parse, parse_input, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " assert <rate> [--at-least <rate>] [--at-most <rate>]\n",
+    "       Exits with status 1 if <rate> is outside of the given bounds."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let at_least: Option<f64> = pargs.opt_value_from_fn("--at-least", parse)?;
+    let at_most: Option<f64> = pargs.opt_value_from_fn("--at-most", parse)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let measured = parse_input(&args, &args, parse)?;
+    if at_least.is_none() && at_most.is_none() {
+        return Err(Error::Usage(messages::text(Message::AssertBounds, &[])));
+    }
+    check(measured, at_least, at_most)?;
+    out.line(
+        &format!("ok: {}", format_rate(measured)),
+        &[("ok", Value::Rate(Rate::from_decimal(measured)))],
+    )?;
+    return Ok(());
+}
+
+fn check(measured: f64, at_least: Option<f64>, at_most: Option<f64>) -> Result<(), Error> {
+    if let Some(min) = at_least {
+        if measured < min {
+            return Err(Error::Assertion(messages::text(
+                Message::LessThan,
+                &[&format_rate(measured), &format_rate(min)],
+            )));
+        }
+    }
+    if let Some(max) = at_most {
+        if measured > max {
+            return Err(Error::Assertion(messages::text(
+                Message::MoreThan,
+                &[&format_rate(measured), &format_rate(max)],
+            )));
+        }
+    }
+    return Ok(());
+}
+
+#[test]
+fn test_check() {
+    let mbps = parse("50 MB/s").unwrap();
+    assert!(check(mbps, Some(mbps), None).is_ok());
+    assert!(check(mbps, None, Some(mbps)).is_ok());
+    assert!(check(mbps, Some(mbps + 1.0), None).is_err());
+    assert!(check(mbps, None, Some(mbps - 1.0)).is_err());
+    assert!(check(mbps, Some(1.0), Some(2.0 * mbps)).is_ok());
+}
@@ -0,0 +1,154 @@
+//! `rate bench-disk`: a quick sequential write and read test.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_duration, format_rate, format_size, optional_arg, parse_size, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " bench-disk [<dir>] [--size <size>] [--block <size>]\n",
+    "       Writes then reads back a temporary file in <dir> (the temporary\n",
+    "       directory by default) and reports both rates. Direct I/O is used\n",
+    "       where available so the page cache does not inflate the results.\n",
+    "       Defaults: 256MB file, 1MiB blocks."
+);
+
+/// Direct I/O needs buffers, offsets and lengths aligned to the logical
+/// block size of the device, which is at most this on common hardware.
+const ALIGN: usize = 4096;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let size: f64 = pargs
+        .opt_value_from_fn("--size", parse_size)?
+        .unwrap_or(256e6);
+    let block: f64 = pargs
+        .opt_value_from_fn("--block", parse_size)?
+        .unwrap_or(1048576.0);
+    let mut out = output(&mut pargs)?;
+    let dir = optional_arg(pargs)?;
+    if block < 1.0 {
+        return Err(Error::Usage(messages::text(
+            Message::BlockSizePositive,
+            &[],
+        )));
+    }
+    let dir = dir.map_or_else(std::env::temp_dir, PathBuf::from);
+    let block = (block as usize).div_ceil(ALIGN) * ALIGN;
+    let blocks = (size as usize).div_ceil(block);
+    if blocks == 0 {
+        return Err(Error::Usage(messages::text(Message::SizePositive, &[])));
+    }
+
+    let path = dir.join(format!(".rate-bench-{}", std::process::id()));
+    let result = bench(&path, block, blocks);
+    let _ = std::fs::remove_file(&path);
+    let (direct, write, read) = result?;
+    let total = (block * blocks) as f64;
+    let text = [
+        format!(
+            "file  : {} in blocks of {}{}",
+            format_size(total),
+            format_size(block as f64),
+            if direct { "" } else { " (buffered)" }
+        ),
+        format!(
+            "write : {} in {}",
+            format_rate(total / write),
+            format_duration(write)
+        ),
+        format!(
+            "read  : {} in {}",
+            format_rate(total / read),
+            format_duration(read)
+        ),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("file", Value::Size(total)),
+            ("block", Value::Size(block as f64)),
+            (
+                "io",
+                Value::Text(if direct { "direct" } else { "buffered" }),
+            ),
+            ("write", Value::Rate(Rate::from_decimal(total / write))),
+            ("read", Value::Rate(Rate::from_decimal(total / read))),
+        ],
+    )?;
+    out.table(Rate::from_decimal(total / read.max(write)))?;
+    return Ok(());
+}
+
+/// Writes and reads `blocks` blocks of `block` bytes at `path`, and
+/// returns whether direct I/O was used and the seconds each pass took.
+fn bench(path: &Path, block: usize, blocks: usize) -> Result<(bool, f64, f64), Error> {
+    let mut storage = vec![0u8; block + ALIGN];
+    let offset = storage.as_ptr().align_offset(ALIGN);
+    let buf = &mut storage[offset..offset + block];
+    // Incompressible data, so that compressing filesystems can't cheat.
+    let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+    for b in buf.iter_mut() {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        *b = x as u8;
+    }
+
+    let (mut file, direct) = open(path, true)?;
+    let start = Instant::now();
+    for _ in 0..blocks {
+        file.write_all(buf)?;
+    }
+    file.sync_all()?;
+    let write = start.elapsed().as_secs_f64();
+    drop(file);
+
+    let (mut file, _) = open(path, false)?;
+    if !direct {
+        drop_cache(&file);
+    }
+    let start = Instant::now();
+    while file.read(buf)? > 0 {}
+    let read = start.elapsed().as_secs_f64();
+    return Ok((direct, write, read));
+}
+
+/// Opens `path` with direct I/O, falling back to buffered I/O on
+/// filesystems that refuse it (e.g., tmpfs).
+fn open(path: &Path, write: bool) -> Result<(File, bool), Error> {
+    let mut options = OpenOptions::new();
+    if write {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.read(true);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut direct = options.clone();
+        if let Ok(file) = direct.custom_flags(libc::O_DIRECT).open(path) {
+            return Ok((file, true));
+        }
+    }
+    return Ok((options.open(path)?, false));
+}
+
+/// Evicts the file from the page cache, so that reads hit the disk.
+#[cfg(target_os = "linux")]
+fn drop_cache(file: &File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_cache(_file: &File) {}
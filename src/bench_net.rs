@@ -0,0 +1,152 @@
+//! `rate bench-net`: measure TCP throughput between two hosts.
+
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, format_size, parse_duration, remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " bench-net --server [--port <n>]\n",
+    "       ",
+    env!("CARGO_BIN_NAME"),
+    " bench-net --client <host> [--port <n>] [--duration <duration>]\n",
+    "       The client sends data to the server for 10s by default, then\n",
+    "       reports the amount the server acknowledged receiving. The server\n",
+    "       handles one client at a time until interrupted. Port 5210 by\n",
+    "       default."
+);
+
+const PORT: u16 = 5210;
+const BLOCK: usize = 128 * 1024;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let server = pargs.contains("--server");
+    let client: Option<String> = pargs.opt_value_from_str("--client")?;
+    let port: u16 = pargs.opt_value_from_str("--port")?.unwrap_or(PORT);
+    let duration: f64 = pargs
+        .opt_value_from_fn("--duration", parse_duration)?
+        .unwrap_or(10.0);
+    let duration = Duration::try_from_secs_f64(duration)
+        .map_err(|_| Error::Usage(messages::text(Message::DurationTooLong, &[])))?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    match (server, client) {
+        (true, None) => {
+            let listener = TcpListener::bind(("::", port))
+                .or_else(|_| TcpListener::bind(("0.0.0.0", port)))?;
+            eprintln!("{}", messages::text(Message::ListeningPort, &[&port]));
+            loop {
+                let (stream, peer) = listener.accept()?;
+                match receive(stream) {
+                    Ok((bytes, seconds)) => {
+                        let text = format!(
+                            "{}: {} in {} = {}",
+                            peer,
+                            format_size(bytes as f64),
+                            format_duration(seconds),
+                            format_rate(bytes as f64 / seconds)
+                        );
+                        let peer = peer.to_string();
+                        out.line(
+                            &text,
+                            &[
+                                ("peer", Value::Text(&peer)),
+                                ("bytes", Value::Size(bytes as f64)),
+                                ("seconds", Value::Duration(seconds)),
+                                (
+                                    "rate",
+                                    Value::Rate(Rate::from_decimal(bytes as f64 / seconds)),
+                                ),
+                            ],
+                        )?;
+                    }
+                    Err(e) => eprintln!("{}: {}", peer, e),
+                }
+            }
+        }
+        (false, Some(host)) => {
+            let stream = TcpStream::connect((host.as_str(), port))?;
+            let (bytes, seconds) = send(stream, duration)?;
+            let text = format!(
+                "sent : {} in {}",
+                format_size(bytes as f64),
+                format_duration(seconds)
+            );
+            out.line(
+                &text,
+                &[
+                    ("sent", Value::Size(bytes as f64)),
+                    ("seconds", Value::Duration(seconds)),
+                ],
+            )?;
+            out.table(Rate::from_decimal(bytes as f64 / seconds))?;
+            return Ok(());
+        }
+        _ => {
+            return Err(Error::Usage(messages::text(
+                Message::ExpectedEither,
+                &[&"--server", &"--client <host>"],
+            )))
+        }
+    }
+}
+
+/// Reads from `stream` until the client is done, acknowledges the number
+/// of bytes received, and returns it with the seconds it took.
+fn receive(mut stream: TcpStream) -> Result<(u64, f64), Error> {
+    let mut buf = vec![0u8; BLOCK];
+    let mut bytes: u64 = 0;
+    let start = Instant::now();
+    loop {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        bytes += n as u64;
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    stream.write_all(&bytes.to_be_bytes())?;
+    return Ok((bytes, seconds));
+}
+
+/// Writes to `stream` for `duration`, then waits for the server to
+/// acknowledge, and returns the bytes it received and the seconds until
+/// the acknowledgement.
+fn send(mut stream: TcpStream, duration: Duration) -> Result<(u64, f64), Error> {
+    let buf = vec![0u8; BLOCK];
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        stream.write_all(&buf)?;
+    }
+    stream.shutdown(Shutdown::Write)?;
+    let mut ack = [0u8; 8];
+    stream.read_exact(&mut ack)?;
+    return Ok((u64::from_be_bytes(ack), start.elapsed().as_secs_f64()));
+}
+
+#[test]
+fn test_loopback() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || receive(listener.accept().unwrap().0).unwrap());
+    let stream = TcpStream::connect(addr).unwrap();
+    let (sent, _) = send(stream, Duration::from_millis(50)).unwrap();
+    let (received, _) = server.join().unwrap();
+    assert!(sent > 0);
+    assert_eq!(sent, received);
+}
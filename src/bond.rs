@@ -0,0 +1,115 @@
+//! `rate bond`: the combined throughput of bonded or ECMP links.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_duration, parse, parse_input, parse_size, remaining_args, Error,
+    ParseError, ParseErrorKind, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " bond <link> [+ <link>...] [--efficiency <0-1>] [--size <size>]\n",
+    "       Each link is a rate, optionally repeated: \"2x1Gb/s + 500Mb/s\".\n",
+    "       --efficiency (default 1) scales what each link delivers in the\n",
+    "       bond; --size compares transfer times with the fastest link alone."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let efficiency: f64 = pargs.opt_value_from_str("--efficiency")?.unwrap_or(1.0);
+    let size: Option<f64> = pargs.opt_value_from_fn("--size", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let links = parse_input(&args, &args, parse_links)?;
+    if !(efficiency > 0.0 && efficiency <= 1.0) {
+        return Err(Error::Usage(messages::text(
+            Message::Between,
+            &[&"--efficiency", &0, &1],
+        )));
+    }
+
+    let raw: f64 = links.iter().sum();
+    let combined = raw * efficiency;
+    let fastest = links.iter().copied().fold(0.0, f64::max);
+    let mut text = vec![
+        format!("links     : {}", links.len()),
+        format!("raw       : {}", format_bitrate(raw)),
+        format!(
+            "effective : {} ({:.0}% efficiency)",
+            format_bitrate(combined),
+            efficiency * 100.0
+        ),
+        format!(
+            "speedup   : {:.2}x over the fastest link",
+            combined / fastest
+        ),
+        format!("per flow  : {} at most", format_bitrate(fastest)),
+    ];
+    let mut fields = vec![
+        ("links", Value::Number(links.len() as f64)),
+        ("raw", Value::Bitrate(Rate::from_decimal(raw))),
+        ("effective", Value::Bitrate(Rate::from_decimal(combined))),
+        ("efficiency", Value::Number(efficiency)),
+        ("speedup", Value::Number(combined / fastest)),
+        ("per_flow", Value::Bitrate(Rate::from_decimal(fastest))),
+    ];
+    if let Some(size) = size {
+        text.push(format!("single    : {}", format_duration(size / fastest)));
+        text.push(format!("bonded    : {}", format_duration(size / combined)));
+        fields.push(("single", Value::Duration(size / fastest)));
+        fields.push(("bonded", Value::Duration(size / combined)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Parses "<link> + <link> + ..." where a link is "[<n>x]<rate>". There
+/// must be at least one link.
+fn parse_links(s: &str) -> Result<Vec<f64>, ParseError> {
+    let mut links = Vec::new();
+    let offset = |part: &str| part.as_ptr() as usize - s.as_ptr() as usize;
+    for term in s.split('+') {
+        let term = term.trim();
+        let repeated = term
+            .split_once(['x', 'X', '*'])
+            .and_then(|(n, rate)| Some((n.trim().parse::<usize>().ok()?, rate)));
+        let (count, rate) = repeated.unwrap_or((1, term));
+        if count == 0 {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidNumber,
+                offset(term)..offset(rate),
+            ));
+        }
+        let rate = parse(rate).map_err(|e| e.shift(offset(rate)))?;
+        for _ in 0..count {
+            links.push(rate);
+        }
+    }
+    if links.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::InvalidNumber, 0..s.len()));
+    }
+    return Ok(links);
+}
+
+#[test]
+fn test_parse_links() {
+    assert_eq!(parse_links("1Gb/s"), Ok(vec![125e6]));
+    assert_eq!(
+        parse_links("2x1Gb/s + 500Mb/s"),
+        Ok(vec![125e6, 125e6, 62.5e6])
+    );
+    assert_eq!(parse_links("3 x 10 MB/s"), Ok(vec![10e6; 3]));
+    assert!(parse_links("2x1Gb/s +").is_err());
+    assert_eq!(
+        parse_links("0x1Gb/s"),
+        Err(ParseError::new(ParseErrorKind::InvalidNumber, 0..2))
+    );
+    assert!(parse_links("").is_err());
+    assert_eq!(
+        parse_links("1Gb/s + 2x10 Zq/s").map_err(|e| e.span),
+        Err(Some(13..15))
+    );
+}
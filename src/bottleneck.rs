@@ -0,0 +1,100 @@
+//! `rate bottleneck`: the end-to-end rate of a chain of stages.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_rate, parse, parse_input, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " bottleneck [<name>] <rate> -> [<name>] <rate> ...\n",
+    "       Prints the end-to-end rate of a pipeline of stages, e.g.\n",
+    "       'disk 550MB/s -> net 1Gb/s -> nas 200MB/s' (quoted for the\n",
+    "       shell), the stage that limits it, and how much speeding it up\n",
+    "       would help before another stage limits it: the others do not."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let mut stages: Vec<(String, f64)> = Vec::new();
+    for (i, stage) in args.split("->").enumerate() {
+        let stage = stage.trim();
+        let (name, rate) = match stage.split_once(' ') {
+            Some((name, rate)) if name.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                (String::from(name), rate.trim())
+            }
+            _ => (format!("stage {}", i + 1), stage),
+        };
+        let rate = parse_input(&args, rate, parse)?;
+        if rate <= 0.0 {
+            return Err(Error::Usage(messages::text(
+                Message::StageRatePositive,
+                &[&name],
+            )));
+        }
+        stages.push((name, rate));
+    }
+    if stages.len() < 2 {
+        return Err(Error::Usage(messages::text(Message::TwoStages, &[])));
+    }
+
+    let rates: Vec<f64> = stages.iter().map(|(_, rate)| *rate).collect();
+    let (limit, end_to_end) = slowest(&rates, None);
+    // Only the slowest stage gains anything, up to the next slowest.
+    let (next, next_rate) = slowest(&rates, Some(limit));
+    let width = stages
+        .iter()
+        .map(|(name, _)| name.len())
+        .fold("end to end".len(), usize::max);
+    for (i, (name, rate)) in stages.iter().enumerate() {
+        let fields = [
+            ("stage", Value::Text(name)),
+            ("rate", Value::Rate(Rate::from_decimal(*rate))),
+        ];
+        if i != limit {
+            out.line(
+                &format!("{:<width$} : {}", name, format_rate(*rate)),
+                &fields,
+            )?;
+            continue;
+        }
+        let text = format!(
+            "{:<width$} : {} (bottleneck, up to {:.0}% faster until {} limits)",
+            name,
+            format_rate(*rate),
+            (next_rate / end_to_end - 1.0) * 100.0,
+            stages[next].0
+        );
+        out.line(&text, &fields)?;
+    }
+    let text = format!("{:<width$} : {}", "end to end", format_rate(end_to_end));
+    out.line(
+        &text,
+        &[
+            ("stage", Value::Text("end to end")),
+            ("rate", Value::Rate(Rate::from_decimal(end_to_end))),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// The index and the rate of the slowest stage, leaving out `skip`.
+fn slowest(rates: &[f64], skip: Option<usize>) -> (usize, f64) {
+    let mut slowest = (usize::MAX, f64::INFINITY);
+    for (i, rate) in rates.iter().enumerate() {
+        if Some(i) != skip && *rate < slowest.1 {
+            slowest = (i, *rate);
+        }
+    }
+    return slowest;
+}
+
+#[test]
+fn test_slowest() {
+    let rates = [550.0, 125.0, 200.0];
+    assert_eq!(slowest(&rates, None), (1, 125.0));
+    assert_eq!(slowest(&rates, Some(1)), (2, 200.0));
+}
@@ -0,0 +1,132 @@
+//! `rate bucket`: token-bucket sizing, with ready-to-use configurations.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_duration, format_size, parse, parse_duration, parse_size,
+    remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " bucket --sustained <rate> --burst <rate> --for <duration>\n",
+    "       [--request-size <size>] [--format all|tc|nginx|haproxy]\n",
+    "       Sizes a token bucket that refills at the sustained rate and is\n",
+    "       deep enough to allow the burst rate for the given duration.\n",
+    "       --request-size converts bytes to requests for nginx limit_req."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let sustained: f64 = pargs.value_from_fn("--sustained", parse)?;
+    let burst: f64 = pargs.value_from_fn("--burst", parse)?;
+    let duration: f64 = pargs.value_from_fn("--for", parse_duration)?;
+    let request_size: Option<f64> = pargs.opt_value_from_fn("--request-size", parse_size)?;
+    let format: String = pargs
+        .opt_value_from_str("--format")?
+        .unwrap_or_else(|| String::from("all"));
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if !["all", "tc", "nginx", "haproxy"].contains(&format.as_str()) {
+        return Err(Error::Usage(messages::text(
+            Message::UnknownFormat,
+            &[&format!("{:?}", format)],
+        )));
+    }
+    if sustained <= 0.0 || burst < sustained {
+        return Err(Error::Usage(messages::text(Message::BurstRate, &[])));
+    }
+
+    let depth = depth(sustained, burst, duration);
+    let text = [
+        format!("sustained : {}", format_bitrate(sustained)),
+        format!(
+            "burst     : {} for {}",
+            format_bitrate(burst),
+            format_duration(duration)
+        ),
+        format!("depth     : {}", format_size(depth)),
+        format!("refill    : {}", format_duration(depth / sustained)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("sustained", Value::Bitrate(Rate::from_decimal(sustained))),
+            ("burst", Value::Bitrate(Rate::from_decimal(burst))),
+            ("duration", Value::Duration(duration)),
+            ("depth", Value::Size(depth)),
+            ("refill", Value::Duration(depth / sustained)),
+        ],
+    )?;
+
+    let show = |name: &str| format == "all" || format == name;
+    if show("tc") {
+        out.line("", &[])?;
+        out.line("# tc (tbf)", &[])?;
+        let tc = format!(
+            "tc qdisc add dev eth0 root tbf rate {:.0}bit burst {:.0} peakrate {:.0}bit mtu 1500 latency 50ms",
+            sustained * 8.0,
+            depth,
+            burst * 8.0
+        );
+        out.line(&tc, &[])?;
+    }
+    if show("nginx") {
+        out.line("", &[])?;
+        out.line("# nginx", &[])?;
+        out.line(&format!("limit_rate {:.0};", sustained), &[])?;
+        out.line(&format!("limit_rate_after {:.0};", depth), &[])?;
+        if let Some(size) = request_size {
+            let per_second = sustained / size;
+            let zone_rate = if per_second >= 1.0 {
+                format!("{:.0}r/s", per_second)
+            } else {
+                format!("{:.0}r/m", (per_second * 60.0).max(1.0))
+            };
+            out.line(
+                &format!(
+                    "limit_req_zone $binary_remote_addr zone=rate:10m rate={};",
+                    zone_rate
+                ),
+                &[],
+            )?;
+            out.line(
+                &format!("limit_req zone=rate burst={:.0};", (depth / size).ceil()),
+                &[],
+            )?;
+        }
+    }
+    if show("haproxy") {
+        out.line("", &[])?;
+        out.line("# haproxy", &[])?;
+        out.line(
+            &format!(
+                "filter bwlim-out limit default-limit {:.0} default-period 1s",
+                sustained
+            ),
+            &[],
+        )?;
+    }
+    return Ok(());
+}
+
+/// The bucket must hold everything sent above the refill rate during
+/// the burst.
+fn depth(sustained: f64, burst: f64, duration: f64) -> f64 {
+    return (burst - sustained) * duration;
+}
+
+#[test]
+fn test_depth() {
+    // 100 Mb/s sustained, 400 Mb/s for 5 seconds: 150 Mb/s * 5 s above.
+    assert_eq!(depth(12.5e6, 50e6, 5.0), 187.5e6);
+    assert_eq!(depth(12.5e6, 12.5e6, 5.0), 0.0);
+}
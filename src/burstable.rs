@@ -0,0 +1,111 @@
+//! `rate burstable`: 95th percentile ("burstable") bandwidth billing.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::stats::percentile;
+use crate::{
+    format_bitrate, format_duration, input_lines, parse, parse_duration, Error, ParseError,
+    ParseErrorKind, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " burstable [<sample>...] [--file <path>] [--price <per Mb/s>] [--commit <rate>]\n",
+    "       [--percentile <p>] [--interval <duration>]\n",
+    "       Samples are rates, or plain numbers in Mb/s, one per line on\n",
+    "       stdin or in --file. The billable rate is the 95th percentile\n",
+    "       (or --percentile), but no less than --commit. --price is the\n",
+    "       monthly price per Mb/s. Samples are 5 minutes apart by default."
+);
+
+const MEGABIT: f64 = 125e3;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let file: Option<String> = pargs.opt_value_from_str("--file")?;
+    let price: Option<f64> = pargs.opt_value_from_str("--price")?;
+    let commit: f64 = pargs.opt_value_from_fn("--commit", parse)?.unwrap_or(0.0);
+    let pct: f64 = pargs.opt_value_from_str("--percentile")?.unwrap_or(95.0);
+    let interval: f64 = pargs
+        .opt_value_from_fn("--interval", parse_duration)?
+        .unwrap_or(300.0);
+    let mut out = output(&mut pargs)?;
+    if !(0.0..=100.0).contains(&pct) {
+        return Err(Error::Usage(messages::text(
+            Message::Between,
+            &[&"--percentile", &0, &100],
+        )));
+    }
+    if price.is_some_and(|p| !(p.is_finite() && p >= 0.0)) {
+        return Err(Error::Usage(messages::text(Message::PriceFinite, &[])));
+    }
+    let lines = match file {
+        Some(path) => std::fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .map(|(i, l)| (i + 1, l.trim().to_string()))
+            .collect(),
+        None => input_lines(pargs)?,
+    };
+    let mut samples: Vec<f64> = Vec::new();
+    for (n, line) in lines {
+        samples.push(parse_sample(&line).map_err(|e| Error::Line(n, line.clone(), e))?);
+    }
+    if samples.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NoSamplesToBill, &[])));
+    }
+    samples.sort_by(f64::total_cmp);
+
+    let billed = percentile(&samples, pct);
+    let billable = billed.max(commit);
+    let peak = samples[samples.len() - 1];
+    let mut text = vec![
+        format!(
+            "samples   : {} ({})",
+            samples.len(),
+            format_duration(samples.len() as f64 * interval)
+        ),
+        format!("peak      : {}", format_bitrate(peak)),
+        format!("p{:<8} : {}", pct, format_bitrate(billed)),
+    ];
+    if billable > billed {
+        text.push(format!("billable  : {} (commit)", format_bitrate(billable)));
+    } else {
+        text.push(format!("billable  : {}", format_bitrate(billable)));
+    }
+    let mut fields = vec![
+        ("samples", Value::Number(samples.len() as f64)),
+        ("peak", Value::Bitrate(Rate::from_decimal(peak))),
+        ("percentile", Value::Number(pct)),
+        ("billed", Value::Bitrate(Rate::from_decimal(billed))),
+        ("billable", Value::Bitrate(Rate::from_decimal(billable))),
+    ];
+    if let Some(price) = price {
+        let cost = billable / MEGABIT * price;
+        text.push(format!("cost      : {:.2} / month", cost));
+        fields.push(("cost", Value::Number(cost)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+fn parse_sample(s: &str) -> Result<f64, ParseError> {
+    match s.parse::<f64>() {
+        Ok(mbps) if mbps.is_finite() && mbps >= 0.0 => return Ok(mbps * MEGABIT),
+        Ok(_) => return Err(ParseErrorKind::InvalidNumber.into()),
+        Err(_) => return parse(s),
+    }
+}
+
+#[test]
+fn test_parse_sample() {
+    assert_eq!(parse_sample("8"), Ok(1e6));
+    assert_eq!(parse_sample("8 Mb/s"), Ok(1e6));
+    assert!(parse_sample("eight").is_err());
+    assert!(parse_sample("nan").is_err());
+    assert!(parse_sample("inf").is_err());
+    assert!(parse_sample("-8").is_err());
+}
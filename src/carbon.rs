@@ -0,0 +1,95 @@
+//! `rate carbon`: emissions attributed to a transfer.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_size, month, parse, parse_input, parse_size, remaining_args, Error, DAY, HOUR, YEAR,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " carbon <rate> | <size> [--intensity <gCO2e per GB> | --preset <name>]\n",
+    "       Prints the emissions of a sustained rate per period, or of a\n",
+    "       single transfer of <size>. Presets: swd (Sustainable Web Design,\n",
+    "       the default) and aslan (Aslan et al., networks only), both at a\n",
+    "       world average grid intensity of 442 gCO2e per kWh."
+);
+
+/// Emissions in gCO2e per GB: kWh per GB times 442 gCO2e per kWh.
+const PRESETS: &[(&str, f64)] = &[("swd", 0.81 * 442.0), ("aslan", 0.06 * 442.0)];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let intensity: Option<f64> = pargs.opt_value_from_str("--intensity")?;
+    let preset: Option<String> = pargs.opt_value_from_str("--preset")?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let intensity = match (intensity, preset) {
+        (Some(_), Some(_)) => {
+            return Err(Error::Usage(messages::text(
+                Message::Exclusive,
+                &[&"--intensity", &"--preset"],
+            )))
+        }
+        (Some(intensity), None) => intensity,
+        (None, preset) => {
+            let name = preset.unwrap_or_else(|| String::from("swd"));
+            let Some((_, intensity)) = PRESETS.iter().find(|(n, _)| *n == name) else {
+                let names: Vec<&str> = PRESETS.iter().map(|(n, _)| *n).collect();
+                return Err(Error::Usage(messages::text(
+                    Message::UnknownPreset,
+                    &[&format!("{:?}", name), &names.join(" ")],
+                )));
+            };
+            *intensity
+        }
+    };
+
+    let mut text = vec![format!("intensity : {:.1} gCO2e / GB", intensity)];
+    let mut fields = vec![("intensity", Value::Number(intensity))];
+    if let Ok(size) = parse_size(&args) {
+        let grams = size / 1e9 * intensity;
+        text.push(format!(
+            "transfer  : {} emits {}",
+            format_size(size),
+            format_mass(grams)
+        ));
+        fields.push(("transfer", Value::Size(size)));
+        fields.push(("grams", Value::Number(grams)));
+        out.line(&text.join("\n"), &fields)?;
+        return Ok(());
+    }
+    let rate = parse_input(&args, &args, parse)?;
+    for (name, seconds) in [
+        ("grams_per_hour", HOUR),
+        ("grams_per_day", DAY),
+        ("grams_per_month", month()),
+        ("grams_per_year", YEAR),
+    ] {
+        let grams = rate * seconds as f64 / 1e9 * intensity;
+        text.push(format!("per {:<5} : {}", &name[10..], format_mass(grams)));
+        fields.push((name, Value::Number(grams)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Formats a mass given in grams as g, kg or t.
+fn format_mass(grams: f64) -> String {
+    if grams < 1e3 {
+        return format!("{:.3} g CO2e", grams);
+    } else if grams < 1e6 {
+        return format!("{:.3} kg CO2e", grams / 1e3);
+    } else {
+        return format!("{:.3} t CO2e", grams / 1e6);
+    }
+}
+
+#[test]
+fn test_format_mass() {
+    assert_eq!(format_mass(12.0), "12.000 g CO2e");
+    assert_eq!(format_mass(1500.0), "1.500 kg CO2e");
+    assert_eq!(format_mass(2.5e6), "2.500 t CO2e");
+}
@@ -0,0 +1,101 @@
+//! `rate catchup`: when a lagging replica catches up, if ever.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::time::{format_timestamp, now};
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_duration, parse_size, remaining_args,
+    Error, ParseError, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " catchup --lag <size|duration> --write <rate> --apply <rate>\n",
+    "       --lag is how far behind the replica is, in data or in time;\n",
+    "       --write is the rate new data comes in on the primary, and\n",
+    "       --apply the rate the replica can apply it at."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let lag: Lag = pargs.value_from_fn("--lag", parse_lag)?;
+    let write: f64 = pargs.value_from_fn("--write", parse)?;
+    let apply: f64 = pargs.value_from_fn("--apply", parse)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+
+    let behind = match lag {
+        Lag::Bytes(b) => b,
+        Lag::Seconds(s) => s * write,
+    };
+    let mut text = vec![format!("behind    : {}", format_size(behind))];
+    match catchup_time(behind, write, apply) {
+        Some(seconds) => {
+            let at = format_timestamp(now() + seconds);
+            text.push(format!("closing   : {}", format_rate(apply - write)));
+            text.push(format!("caught up : in {}", format_duration(seconds)));
+            text.push(format!("          : at {} UTC", at));
+            out.line(
+                &text.join("\n"),
+                &[
+                    ("behind", Value::Size(behind)),
+                    ("closing", Value::Rate(Rate::from_decimal(apply - write))),
+                    ("caught_up", Value::Duration(seconds)),
+                    ("at", Value::Text(&at)),
+                ],
+            )?;
+        }
+        None => {
+            if write > apply {
+                text.push(format!("growing   : {}", format_rate(write - apply)));
+                text.push(String::from("caught up : never"));
+            } else {
+                text.push(String::from("caught up : never, the lag stays constant"));
+            }
+            out.line(
+                &text.join("\n"),
+                &[
+                    ("behind", Value::Size(behind)),
+                    ("growing", Value::Rate(Rate::from_decimal(write - apply))),
+                ],
+            )?;
+        }
+    }
+    return Ok(());
+}
+
+enum Lag {
+    Bytes(f64),
+    Seconds(f64),
+}
+
+fn parse_lag(s: &str) -> Result<Lag, ParseError> {
+    match parse_size(s) {
+        Ok(bytes) => return Ok(Lag::Bytes(bytes)),
+        Err(_) => return parse_duration(s).map(Lag::Seconds),
+    }
+}
+
+/// The replica closes the gap at the difference between the two rates,
+/// as long as it applies faster than the primary writes.
+fn catchup_time(behind: f64, write: f64, apply: f64) -> Option<f64> {
+    if apply <= write {
+        return None;
+    }
+    return Some(behind / (apply - write));
+}
+
+#[test]
+fn test_catchup_time() {
+    assert_eq!(catchup_time(100.0, 10.0, 20.0), Some(10.0));
+    assert_eq!(catchup_time(100.0, 10.0, 10.0), None);
+    assert_eq!(catchup_time(0.0, 10.0, 20.0), Some(0.0));
+}
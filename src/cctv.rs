@@ -0,0 +1,60 @@
+//! `rate cctv`: storage needed to keep surveillance footage.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_size, parse, parse_duration, parse_input, parse_percent, parse_quantity,
+    remaining_args, split_keyword, Error, Rate, DAY,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " cctv <n> [cameras] @ <rate> --retention <duration> [--duty <ratio>]\n",
+    "       Computes the daily ingest of the cameras and the storage needed\n",
+    "       to keep the footage for the retention period. --duty is the share\n",
+    "       of time motion-activated cameras actually record (e.g., 40%)."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let retention: f64 = pargs.value_from_fn("--retention", parse_duration)?;
+    let duty: f64 = pargs
+        .opt_value_from_fn("--duty", parse_percent)?
+        .unwrap_or(1.0);
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((cameras, rate)) = split_keyword(&args, "@").or(split_keyword(&args, "at")) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<n> cameras @ <rate>"],
+        )));
+    };
+    let cameras = cameras
+        .trim_end_matches("cameras")
+        .trim_end_matches("camera");
+    let cameras = parse_input(&args, cameras, parse_quantity)?;
+    let rate = parse_input(&args, rate, parse)?;
+    if duty > 1.0 {
+        return Err(Error::Usage(messages::text(Message::DutyCycle, &[])));
+    }
+
+    let ingest = cameras * rate * duty;
+    let mut text = vec![format!("ingest    : {}", format_bitrate(ingest))];
+    if duty < 1.0 {
+        text.push(format!("duty      : {:.1}% of the time", duty * 100.0));
+    }
+    text.push(format!("per day   : {}", format_size(ingest * DAY as f64)));
+    text.push(format!("retention : {}", format_size(ingest * retention)));
+    out.line(
+        &text.join("\n"),
+        &[
+            ("ingest", Value::Bitrate(Rate::from_decimal(ingest))),
+            ("duty", Value::Number(duty)),
+            ("per_day", Value::Size(ingest * DAY as f64)),
+            ("retention", Value::Size(ingest * retention)),
+        ],
+    )?;
+    return Ok(());
+}
@@ -0,0 +1,64 @@
+//! `rate cdn`: egress for a number of concurrent viewers of a stream.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, parse, parse_input, parse_percent, parse_quantity, remaining_args,
+    split_keyword, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " cdn <viewers> [viewers] @ <rate> [--cache-hit <ratio>]\n",
+    "       Computes the aggregate egress of concurrent viewers each pulling\n",
+    "       <rate>. With --cache-hit (e.g., 95%), also shows the part that\n",
+    "       still reaches the origin."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let cache_hit: Option<f64> = pargs.opt_value_from_fn("--cache-hit", parse_percent)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((viewers, rate)) = split_keyword(&args, "@").or(split_keyword(&args, "at")) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<viewers> @ <rate>"],
+        )));
+    };
+    let viewers = viewers
+        .trim_end_matches("viewers")
+        .trim_end_matches("viewer");
+    let viewers = parse_input(&args, viewers, parse_quantity)?;
+    let rate = parse_input(&args, rate, parse)?;
+    if cache_hit.is_some_and(|h| h > 1.0) {
+        return Err(Error::Usage(messages::text(Message::HitRatio, &[])));
+    }
+
+    let egress = viewers * rate;
+    out.line(
+        &format!("egress : {}", format_bitrate(egress)),
+        &[("egress", Value::Bitrate(Rate::from_decimal(egress)))],
+    )?;
+    out.table(Rate::from_decimal(egress))?;
+    if let Some(hit) = cache_hit {
+        let origin = egress * (1.0 - hit);
+        out.line("", &[])?;
+        let text = format!(
+            "origin : {} ({:.1}% cache hits)",
+            format_bitrate(origin),
+            hit * 100.0
+        );
+        out.line(
+            &text,
+            &[
+                ("origin", Value::Bitrate(Rate::from_decimal(origin))),
+                ("cache_hit", Value::Number(hit)),
+            ],
+        )?;
+        out.table(Rate::from_decimal(origin))?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,57 @@
+//! `rate cellular`: monthly mobile data used by an app.
+
+use rate::sink::Value;
+
+use crate::report::output;
+use crate::{
+    format_size, month, parse, parse_frequency, parse_input, parse_size, remaining_args,
+    split_keyword, Error,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " cellular <rate> | <size> x <sessions>/<period>\n",
+    "       e.g. cellular 2KB/min, cellular 15MB x 6/day\n",
+    "       Projects the monthly cellular data used by a background rate\n",
+    "       or by sessions of a given size, and compares it to typical\n",
+    "       plan tiers."
+);
+
+/// Monthly allowances of common mobile plans.
+const TIERS: &[f64] = &[1e9, 2e9, 5e9, 10e9, 20e9, 50e9, 100e9];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let monthly = match split_keyword(&args, "x") {
+        Some((size, sessions)) => {
+            let size = parse_input(&args, size, parse_size)?;
+            size * parse_input(&args, sessions, parse_frequency)? * month() as f64
+        }
+        None => parse_input(&args, &args, parse)? * month() as f64,
+    };
+
+    out.line(
+        &format!("{:>15} : {} / month", "usage", format_size(monthly)),
+        &[("usage", Value::Size(monthly))],
+    )?;
+    for tier in TIERS {
+        let share = 100.0 * monthly / tier;
+        let verdict = if monthly > *tier { "exceeds" } else { "fits" };
+        out.line(
+            &format!(
+                "{:>10} plan : {:7} ({:.1}%)",
+                format_size(*tier),
+                verdict,
+                share
+            ),
+            &[
+                ("plan", Value::Size(*tier)),
+                ("verdict", Value::Text(verdict)),
+                ("percent", Value::Number(share)),
+            ],
+        )?;
+    }
+    return Ok(());
+}
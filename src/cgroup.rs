@@ -0,0 +1,200 @@
+//! `rate cgroup`: the I/O and network rates of a cgroup or a container.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use rate::sink::Value;
+
+use crate::diff::parse_snapshot;
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::sampler::Sampler;
+use crate::{format_rate, format_size, parse_duration, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " cgroup <cgroup or container> [--interval <duration>]\n",
+    "       Samples the io.stat of a cgroup v2 (a path, absolute or under\n",
+    "       /sys/fs/cgroup) or of a docker or podman container (a name or\n",
+    "       an id) twice, --interval (1s by default) apart, and prints its\n",
+    "       read and write rates, and the network rates of its processes\n",
+    "       when they have a network namespace of their own."
+);
+
+const ROOT: &str = "/sys/fs/cgroup";
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let interval: f64 = pargs
+        .opt_value_from_fn("--interval", parse_duration)?
+        .unwrap_or(1.0);
+    let mut out = output(&mut pargs)?;
+    let target: String = pargs.free_from_str()?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if interval <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+    let interval = Duration::try_from_secs_f64(interval)
+        .map_err(|_| Error::Usage(messages::text(Message::IntervalTooLong, &[])))?;
+
+    let mut cgroup = Cgroup::resolve(&target)?;
+    let start = Instant::now();
+    let before = cgroup.sample()?;
+    std::thread::sleep(interval);
+    let after = cgroup.sample()?;
+    let seconds = start.elapsed().as_secs_f64();
+
+    let path = cgroup.path.display().to_string();
+    let memory = std::fs::read_to_string(cgroup.path.join("memory.current"))
+        .ok()
+        .and_then(|memory| memory.trim().parse::<f64>().ok());
+    let mut text = vec![format!("cgroup   : {}", path)];
+    let mut fields = vec![("cgroup", Value::Text(&path))];
+    if let Some(memory) = memory {
+        text.push(format!("memory   : {}", format_size(memory)));
+        fields.push(("memory", Value::Size(memory)));
+    }
+    for (key, new) in &after {
+        if let Some((_, old)) = before.iter().find(|(k, _)| k == key) {
+            let rate = (new - old).max(0.0) / seconds;
+            text.push(format!("{:<8} : {}", key, format_rate(rate)));
+            fields.push((key, Value::Rate(Rate::from_decimal(rate))));
+        }
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// A cgroup, and a process in it whose network namespace is not that of
+/// the host (that of a container) if there is one.
+struct Cgroup {
+    path: PathBuf,
+    pid: Option<u32>,
+}
+
+impl Cgroup {
+    /// Finds the cgroup of a path or a container.
+    fn resolve(target: &str) -> Result<Cgroup, Error> {
+        let candidates = [
+            PathBuf::from(target),
+            Path::new(ROOT).join(target.trim_start_matches('/')),
+        ];
+        if let Some(path) = candidates.into_iter().find(|p| p.join("io.stat").exists()) {
+            let pid = std::fs::read_to_string(path.join("cgroup.procs"))
+                .ok()
+                .and_then(|procs| procs.lines().next()?.trim().parse().ok());
+            return Ok(Cgroup {
+                pid: pid.filter(|pid| own_network(*pid)),
+                path,
+            });
+        }
+        let Some(pid) = container_pid(target) else {
+            return Err(Error::Usage(messages::text(
+                Message::NotACgroup,
+                &[&format!("{:?}", target)],
+            )));
+        };
+        let cgroups = std::fs::read_to_string(format!("/proc/{}/cgroup", pid))?;
+        let Some(path) = cgroups.lines().find_map(|l| l.strip_prefix("0::")) else {
+            return Err(Error::Usage(messages::text(
+                Message::ContainerNotV2,
+                &[&format!("{:?}", target)],
+            )));
+        };
+        return Ok(Cgroup {
+            path: Path::new(ROOT).join(path.trim_start_matches('/')),
+            pid: Some(pid).filter(|pid| own_network(*pid)),
+        });
+    }
+}
+
+impl Sampler for Cgroup {
+    /// The bytes read and written by the cgroup, and those received and
+    /// sent in the network namespace of its container.
+    fn sample(&mut self) -> io::Result<Vec<(String, f64)>> {
+        let (read, write) = parse_io_stat(&std::fs::read_to_string(self.path.join("io.stat"))?);
+        let mut counters = vec![
+            (String::from("io read"), read),
+            (String::from("io write"), write),
+        ];
+        if let Some(pid) = self.pid {
+            let net = parse_snapshot(&std::fs::read_to_string(format!("/proc/{}/net/dev", pid))?);
+            for dir in ["rx", "tx"] {
+                let total = net
+                    .iter()
+                    .filter(|(k, _)| k.ends_with(dir) && !k.starts_with("lo "))
+                    .map(|(_, v)| v)
+                    .sum();
+                counters.push((format!("net {}", dir), total));
+            }
+        }
+        return Ok(counters);
+    }
+}
+
+/// The main process of a docker or podman container, by name or id.
+fn container_pid(name: &str) -> Option<u32> {
+    for tool in ["docker", "podman"] {
+        let output = Command::new(tool)
+            .args(["inspect", "--format", "{{.State.Pid}}", name])
+            .output();
+        let Ok(output) = output else {
+            continue;
+        };
+        if !output.status.success() {
+            continue;
+        }
+        let pid = String::from_utf8_lossy(&output.stdout).trim().parse().ok();
+        // A stopped container has a PID of 0.
+        if let Some(pid) = pid.filter(|&pid| pid > 0) {
+            return Some(pid);
+        }
+    }
+    return None;
+}
+
+/// Whether a process has a network namespace other than ours.
+fn own_network(pid: u32) -> bool {
+    let theirs = std::fs::read_link(format!("/proc/{}/ns/net", pid));
+    let ours = std::fs::read_link("/proc/self/ns/net");
+    return matches!((theirs, ours), (Ok(theirs), Ok(ours)) if theirs != ours);
+}
+
+/// Sums the bytes read and written on every device of an io.stat, whose
+/// lines look like "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 ...".
+fn parse_io_stat(text: &str) -> (f64, f64) {
+    let mut read = 0.0;
+    let mut write = 0.0;
+    for field in text.split_whitespace() {
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        match key {
+            "rbytes" => read += value,
+            "wbytes" => write += value,
+            _ => {}
+        }
+    }
+    return (read, write);
+}
+
+#[test]
+fn test_parse_io_stat() {
+    let text = "\
+259:0 rbytes=1048576 wbytes=4096 rios=10 wios=1 dbytes=0 dios=0
+8:0 rbytes=1024 wbytes=0 rios=1 wios=0 dbytes=512 dios=1
+";
+    assert_eq!(parse_io_stat(text), (1049600.0, 4096.0));
+    assert_eq!(parse_io_stat(""), (0.0, 0.0));
+}
@@ -0,0 +1,85 @@
+//! `rate chunked`: transfers that wait for a round trip after each chunk.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_duration, parse_size, remaining_args,
+    Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " chunked <size> --chunk <size> --rtt <duration> --rate <rate>\n",
+    "       Estimates a transfer sent one chunk at a time, each chunk\n",
+    "       waiting for a round trip (an acknowledgement, a request) on top\n",
+    "       of its transfer at --rate. Prints the effective throughput, the\n",
+    "       total time and the chunk size that would use 90% of the rate."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let chunk: f64 = pargs.value_from_fn("--chunk", parse_size)?;
+    let rtt: f64 = pargs.value_from_fn("--rtt", parse_duration)?;
+    let rate: f64 = pargs.value_from_fn("--rate", parse)?;
+    let mut out = output(&mut pargs)?;
+    let size = parse_size(&remaining_args(pargs)?)?;
+    if chunk <= 0.0 || rate <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::ChunkPositive, &[])));
+    }
+
+    let (chunks, seconds) = estimate(size, chunk, rtt, rate);
+    let chunk_90 = chunk_for(0.9, rtt, rate);
+    let text = [
+        format!("chunks     : {} of {}", chunks, format_size(chunk)),
+        format!("duration   : {}", format_duration(seconds)),
+        format!("throughput : {}", format_rate(size / seconds)),
+        format!("efficiency : {:.1}%", size / seconds / rate * 100.0),
+        format!(
+            "naive      : {} (size / rate)",
+            format_duration(size / rate)
+        ),
+        format!("90% chunk  : {}", format_size(chunk_90)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("chunks", Value::Number(chunks as f64)),
+            ("chunk", Value::Size(chunk)),
+            ("duration", Value::Duration(seconds)),
+            (
+                "throughput",
+                Value::Rate(Rate::from_decimal(size / seconds)),
+            ),
+            ("efficiency", Value::Number(size / seconds / rate)),
+            ("naive", Value::Duration(size / rate)),
+            ("chunk_90", Value::Size(chunk_90)),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// The number of chunks and the seconds they take: each one is sent at
+/// `rate` and then waits `rtt`.
+fn estimate(size: f64, chunk: f64, rtt: f64, rate: f64) -> (usize, f64) {
+    let chunks = ((size / chunk).ceil() as usize).max(1);
+    return (chunks, size / rate + chunks as f64 * rtt);
+}
+
+/// The chunk size at which the transfer uses `efficiency` of the rate:
+/// its time on the wire is that fraction of its time with the round trip.
+fn chunk_for(efficiency: f64, rtt: f64, rate: f64) -> f64 {
+    return efficiency / (1.0 - efficiency) * rtt * rate;
+}
+
+#[test]
+fn test_estimate() {
+    // 4 chunks of 10 bytes at 1 B/s, a second of round trip each.
+    assert_eq!(estimate(40.0, 10.0, 1.0, 1.0), (4, 44.0));
+    // A smaller last chunk still pays a round trip.
+    assert_eq!(estimate(35.0, 10.0, 1.0, 1.0), (4, 39.0));
+    assert_eq!(estimate(0.0, 10.0, 1.0, 1.0), (1, 1.0));
+    // 90% of 1 Gb/s over 80ms takes chunks of 90 MB.
+    assert_eq!(chunk_for(0.9, 0.08, 125e6).round(), 90e6);
+}
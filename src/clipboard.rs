@@ -0,0 +1,45 @@
+//! Copying results to the system clipboard, through the clipboard tool
+//! of the platform.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::messages::{self, Message};
+use crate::Error;
+
+/// The commands that set the clipboard from their stdin, in the order
+/// they are tried.
+#[cfg(target_os = "macos")]
+const TOOLS: &[&[&str]] = &[&["pbcopy"]];
+#[cfg(windows)]
+const TOOLS: &[&[&str]] = &[&["clip"]];
+#[cfg(not(any(target_os = "macos", windows)))]
+const TOOLS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Puts `text` on the clipboard.
+pub fn copy(text: &str) -> Result<(), Error> {
+    for tool in TOOLS {
+        let child = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            continue;
+        };
+        child.stdin.take().unwrap().write_all(text.as_bytes())?;
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    let names: Vec<&str> = TOOLS.iter().map(|tool| tool[0]).collect();
+    return Err(Error::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        messages::text(Message::Clipboard, &[&names.join(", ")]),
+    )));
+}
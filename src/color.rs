@@ -0,0 +1,108 @@
+//! Whether to color the output, decided once for all the commands from
+//! `--color`, the `NO_COLOR`, `CLICOLOR_FORCE` and `CLICOLOR` variables,
+//! and whether the output goes to a terminal.
+
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ColorChoice, String> {
+        match s {
+            "auto" => return Ok(ColorChoice::Auto),
+            "always" => return Ok(ColorChoice::Always),
+            "never" => return Ok(ColorChoice::Never),
+            _ => return Err(format!("unknown color choice {:?} (auto always never)", s)),
+        }
+    }
+}
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+/// Records the `--color` choice.
+pub fn init(choice: ColorChoice) {
+    _ = CHOICE.set(choice);
+}
+
+/// Whether what is written to stderr is colored.
+pub fn stderr() -> bool {
+    let choice = *CHOICE.get().unwrap_or(&ColorChoice::Auto);
+    let var = |name: &str| std::env::var(name).ok();
+    return enabled(choice, var, std::io::stderr().is_terminal());
+}
+
+/// Whether an output is colored. With `auto`, a non-empty `NO_COLOR`
+/// turns colors off, a `CLICOLOR_FORCE` other than "0" turns them on,
+/// and otherwise they are on for a terminal, unless `CLICOLOR` is "0" or
+/// the terminal is "dumb".
+fn enabled(choice: ColorChoice, var: impl Fn(&str) -> Option<String>, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => return true,
+        ColorChoice::Never => return false,
+        ColorChoice::Auto => {}
+    }
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if var("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    if var("CLICOLOR").as_deref() == Some("0") || var("TERM").as_deref() == Some("dumb") {
+        return false;
+    }
+    return terminal;
+}
+
+pub const RED: &str = "31";
+
+/// Wraps `s` in the SGR escape sequence `code` if `enabled`.
+pub fn paint(s: &str, code: &str, enabled: bool) -> String {
+    if !enabled {
+        return s.to_string();
+    }
+    return format!("\x1b[{}m{}\x1b[0m", code, s);
+}
+
+#[test]
+fn test_enabled() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+        }
+    };
+    assert!(enabled(ColorChoice::Auto, env(&[]), true));
+    assert!(!enabled(ColorChoice::Auto, env(&[]), false));
+    assert!(!enabled(ColorChoice::Auto, env(&[("NO_COLOR", "1")]), true));
+    assert!(enabled(ColorChoice::Auto, env(&[("NO_COLOR", "")]), true));
+    assert!(enabled(
+        ColorChoice::Auto,
+        env(&[("CLICOLOR_FORCE", "1")]),
+        false
+    ));
+    assert!(!enabled(
+        ColorChoice::Auto,
+        env(&[("CLICOLOR_FORCE", "0")]),
+        false
+    ));
+    assert!(!enabled(ColorChoice::Auto, env(&[("CLICOLOR", "0")]), true));
+    assert!(!enabled(ColorChoice::Auto, env(&[("TERM", "dumb")]), true));
+    assert!(enabled(
+        ColorChoice::Always,
+        env(&[("NO_COLOR", "1")]),
+        false
+    ));
+    assert!(!enabled(ColorChoice::Never, env(&[]), true));
+    assert_eq!(paint("^", RED, true), "\x1b[31m^\x1b[0m");
+    assert_eq!(paint("^", RED, false), "^");
+}
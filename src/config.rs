@@ -0,0 +1,970 @@
+//! Defaults for the output options, read from
+//! `$XDG_CONFIG_HOME/rate/config.toml` (by default
+//! `~/.config/rate/config.toml`), overridden by the `RATE_*` environment
+//! variables, a `--profile` of the file, and those by the command line.
+//! `rate config` reads and edits the file.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use rate::sink::{self, CsvSink, JsonSink, OutputSink, PrometheusSink, TextSink};
+use rate::{
+    period_to_seconds, Period, RateFormatter, Registry, UnitSystem, DAY, MONTH, PERIODS, WORKDAY,
+};
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{remaining_args, Error};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " config path | list | get <key> | set <key> <value>\n",
+    "       Shows where the configuration file is, lists its settings,\n",
+    "       prints one, or changes one. The keys of the sections are\n",
+    "       prefixed with the section: aliases.uplink, custom-periods.sprint,\n",
+    "       profile.networking.bits. Lists are set as sec,hour,day.\n",
+    "       A change is checked like the whole file before it is saved."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let action: String = pargs.free_from_str()?;
+    let Some(path) = path() else {
+        return Err(Error::Usage(messages::text(Message::NoConfigDir, &[])));
+    };
+    match action.as_str() {
+        "path" => {
+            finish(pargs)?;
+            let path = path.display().to_string();
+            out.line(&path, &[("path", sink::Value::Text(&path))])?;
+        }
+        "list" => {
+            finish(pargs)?;
+            for (key, value) in settings(&read(&path)?).map_err(Error::Usage)? {
+                let value = value.toml();
+                out.line(
+                    &format!("{} = {}", key, value),
+                    &[
+                        ("key", sink::Value::Text(&key)),
+                        ("value", sink::Value::Text(&value)),
+                    ],
+                )?;
+            }
+        }
+        "get" => {
+            let key: String = pargs.free_from_str()?;
+            finish(pargs)?;
+            let settings = settings(&read(&path)?).map_err(Error::Usage)?;
+            match settings.iter().find(|(k, _)| *k == key) {
+                Some((_, value)) => {
+                    let value = value.plain();
+                    out.line(&value, &[("value", sink::Value::Text(&value))])?;
+                }
+                None => {
+                    return Err(Error::Usage(messages::text(
+                        Message::NotSet,
+                        &[&format!("{:?}", key)],
+                    )))
+                }
+            }
+        }
+        "set" => {
+            let key: String = pargs.free_from_str()?;
+            let value: String = pargs.free_from_str()?;
+            finish(pargs)?;
+            let text = set(&read(&path)?, &key, &value).map_err(Error::Usage)?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, text)?;
+        }
+        _ => {
+            let action = format!("{:?}", action);
+            return Err(Error::Usage(messages::text(
+                Message::UnknownAction,
+                &[&action, &"path list get set"],
+            )));
+        }
+    }
+    return Ok(());
+}
+
+/// Fails if arguments are left after those of the action.
+fn finish(pargs: pico_args::Arguments) -> Result<(), Error> {
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    return Ok(());
+}
+
+/// The text of the configuration file, empty if there is none yet. It
+/// must parse, so that it is not edited blindly.
+fn read(path: &Path) -> Result<String, Error> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if let Err(e) = parse(&text) {
+        return Err(Error::Usage(format!("{}:{}", path.display(), e)));
+    }
+    return Ok(text);
+}
+
+/// The settings of the file in order, with their keys prefixed with
+/// their section.
+fn settings(text: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut settings = Vec::new();
+    let mut section = "";
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!(
+                "{}: expected <key> = <value>, got {:?}",
+                i + 1,
+                line
+            ));
+        };
+        let value = parse_value(value.trim()).map_err(|e| format!("{}: {}", i + 1, e))?;
+        match section {
+            "" => settings.push((String::from(key.trim()), value)),
+            _ => settings.push((format!("{}.{}", section, key.trim()), value)),
+        }
+    }
+    return Ok(settings);
+}
+
+/// Sets `key` to `value` in the text of a configuration file, replacing
+/// the setting if it is there, and adding it (and its section) if not.
+/// The rest of the file, comments included, is kept as it is.
+fn set(text: &str, key: &str, value: &str) -> Result<String, String> {
+    let (section, name) = key.rsplit_once('.').unwrap_or(("", key));
+    if value.contains('"') {
+        return Err(format!("{}: a value cannot contain '\"'", key));
+    }
+    let value = match (section, name) {
+        ("aliases" | "custom-periods", _) => Value::String(String::from(value)),
+        (_, "precision") => match value.trim().parse() {
+            Ok(n) => Value::Integer(n),
+            Err(_) => return Err(format!("{}: invalid number {:?}", key, value)),
+        },
+        (_, "bits" | "nice") => match value.trim().parse() {
+            Ok(bits) => Value::Boolean(bits),
+            Err(_) => return Err(format!("{}: expected true or false, got {:?}", key, value)),
+        },
+        (_, "periods") => Value::Array(value.split(',').map(|p| p.trim().to_string()).collect()),
+        _ => Value::String(String::from(value)),
+    };
+    let setting = format!("{} = {}", name, value.toml());
+
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let mut current = "";
+    // The line of the setting, and the line after the last one of its
+    // section (the header for a section that is still empty).
+    let mut found = None;
+    let mut end = None;
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = header.trim();
+            if current == section {
+                end = Some(i + 1);
+            }
+            continue;
+        }
+        if current != section || line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        end = Some(i + 1);
+        if line.split_once('=').is_some_and(|(k, _)| k.trim() == name) {
+            found = Some(i);
+        }
+    }
+    match (found, end) {
+        (Some(i), _) => lines[i] = setting,
+        (None, Some(i)) => lines.insert(i, setting),
+        // The settings outside of sections come before the first one.
+        (None, None) if section.is_empty() => lines.insert(0, setting),
+        (None, None) => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(setting);
+        }
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    if let Err(e) = parse(&text) {
+        let e = e.split_once(": ").map_or(e.as_str(), |(_, e)| e);
+        return Err(format!("{}: {}", key, e));
+    }
+    return Ok(text);
+}
+
+/// The output options. Those that are `None` keep the built-in default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub precision: Option<usize>,
+    pub output: Option<String>,
+    pub units: Option<UnitSystem>,
+    pub periods: Option<Vec<Period>>,
+    pub bits: Option<bool>,
+    pub nice: Option<bool>,
+    /// The periods of the `[custom-periods]` section, from the shortest
+    /// to the longest.
+    pub custom_periods: Vec<Period>,
+    /// The lengths of a work day and a work week, in seconds.
+    pub workday: Option<u64>,
+    pub workweek: Option<u64>,
+    /// The length of a month in seconds, from `month-days`.
+    pub month: Option<u64>,
+    /// The names of the `[aliases]` section and the rates they stand for.
+    pub aliases: Vec<(String, String)>,
+    /// The `[profile.<name>]` sections, which only set output options.
+    pub profiles: Vec<(String, Config)>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The configuration loaded by `load`, or the defaults before that.
+pub fn get() -> &'static Config {
+    return CONFIG.get_or_init(Config::default);
+}
+
+/// Reads the configuration file, if there is one, and the environment,
+/// for `get` to return, with the options of `profile` on top. A month
+/// lasts `month_days` days if given, e.g. "30.44" for an average one.
+pub fn load(profile: Option<&str>, month_days: Option<&str>) -> Result<(), Error> {
+    let mut config = Config::default();
+    if let Some(path) = path() {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                config =
+                    parse(&text).map_err(|e| Error::Usage(format!("{}:{}", path.display(), e)))?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(Error::Usage(format!("{}: {}", path.display(), e))),
+        }
+    }
+    let mut config = config
+        .environment(|name| std::env::var(name).ok())
+        .map_err(Error::Usage)?;
+    if let Some(name) = profile {
+        match config.profiles.iter().find(|(p, _)| p == name) {
+            Some((_, profile)) => config.overlay(&profile.clone()),
+            None => {
+                let names: Vec<&str> = config.profiles.iter().map(|(p, _)| p.as_str()).collect();
+                return Err(Error::Usage(messages::text(
+                    Message::UnknownProfile,
+                    &[&format!("{:?}", name), &names.join(" ")],
+                )));
+            }
+        }
+    }
+    if let Some(days) = month_days {
+        config.month = Some(month_seconds(days).map_err(Error::Usage)?);
+    }
+    if !config.custom_periods.is_empty()
+        || config.workday.is_some()
+        || config.workweek.is_some()
+        || config.month.is_some()
+    {
+        config.registry().install();
+    }
+    _ = CONFIG.set(config);
+    return Ok(());
+}
+
+/// Where the configuration file is, following the XDG base directories.
+pub fn path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    return Some(dir.join("rate").join("config.toml"));
+}
+
+impl Config {
+    /// Applies `RATE_FORMAT`, `RATE_PRECISION` and `RATE_UNITS`, as
+    /// returned by `var`. Empty variables are ignored.
+    fn environment(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Config, String> {
+        let var = |name: &str| var(name).filter(|v| !v.is_empty());
+        if let Some(precision) = var("RATE_PRECISION") {
+            match precision.trim().parse() {
+                Ok(precision) => self.precision = Some(precision),
+                Err(_) => return Err(format!("RATE_PRECISION: invalid number {:?}", precision)),
+            }
+        }
+        if let Some(units) = var("RATE_UNITS") {
+            self.units = Some(unit_system(&units).map_err(|e| format!("RATE_UNITS: {}", e))?);
+        }
+        if let Some(output) = var("RATE_FORMAT") {
+            self.output = Some(output_format(&output).map_err(|e| format!("RATE_FORMAT: {}", e))?);
+        }
+        return Ok(self);
+    }
+
+    /// Sets the output options that `other` sets.
+    fn overlay(&mut self, other: &Config) {
+        if other.precision.is_some() {
+            self.precision = other.precision;
+        }
+        if other.output.is_some() {
+            self.output.clone_from(&other.output);
+        }
+        if other.units.is_some() {
+            self.units = other.units;
+        }
+        if other.periods.is_some() {
+            self.periods.clone_from(&other.periods);
+        }
+        if other.bits.is_some() {
+            self.bits = other.bits;
+        }
+        if other.nice.is_some() {
+            self.nice = other.nice;
+        }
+    }
+
+    /// Reads the `--precision`, `--units`, `--periods`, `--bits`, `--nice`
+    /// and `--output` options, which take precedence over the
+    /// configuration.
+    pub fn options(mut self, pargs: &mut pico_args::Arguments) -> Result<Config, Error> {
+        if let Some(precision) = pargs.opt_value_from_str("--precision")? {
+            self.precision = Some(precision);
+        }
+        if let Some(units) = pargs.opt_value_from_str::<_, String>("--units")? {
+            self.units = Some(unit_system(&units).map_err(Error::Usage)?);
+        }
+        if let Some(periods) = pargs.opt_value_from_str::<_, String>("--periods")? {
+            let names: Vec<&str> = periods.split(',').collect();
+            let periods = period_list(&names, &self.custom_periods, &self.registry());
+            self.periods = Some(periods.map_err(Error::Usage)?);
+        }
+        if pargs.contains("--bits") {
+            self.bits = Some(true);
+        }
+        if pargs.contains("--nice") {
+            self.nice = Some(true);
+        }
+        if let Some(output) = pargs.opt_value_from_str::<_, String>("--output")? {
+            self.output = Some(output_format(&output).map_err(Error::Usage)?);
+        }
+        return Ok(self);
+    }
+
+    /// The registry of the built-in and custom periods, with the business
+    /// hours and the month of the configuration.
+    fn registry(&self) -> Registry {
+        let mut registry = Registry::new();
+        for period in &self.custom_periods {
+            registry.add_period(period.name, period.seconds);
+        }
+        let workday = self.workday.unwrap_or(WORKDAY);
+        registry.set_business_hours(workday, self.workweek.unwrap_or(5 * workday));
+        registry.set_month(self.month.unwrap_or(MONTH));
+        return registry;
+    }
+
+    /// The rate that `name` is an alias of, e.g. "35 Mb/s" for "uplink".
+    pub fn alias(&self, name: &str) -> Option<&str> {
+        let name = name.trim();
+        let alias = self
+            .aliases
+            .iter()
+            .find(|(a, _)| a.eq_ignore_ascii_case(name));
+        return alias.map(|(_, rate)| rate.as_str());
+    }
+
+    /// The formatter of the text tables.
+    pub fn formatter(&self) -> RateFormatter {
+        let mut formatter = RateFormatter::new();
+        if let Some(precision) = self.precision {
+            formatter = formatter.precision(precision);
+        }
+        if let Some(units) = self.units {
+            formatter = formatter.unit_system(units);
+        }
+        if let Some(bits) = self.bits {
+            formatter = formatter.bits(bits);
+        }
+        if let Some(nice) = self.nice {
+            formatter = formatter.nice(nice);
+        }
+        return formatter.periods(&self.table_periods());
+    }
+
+    /// The periods of the text tables: those chosen, or the built-in and
+    /// custom ones. The month has the length of the configuration.
+    pub fn table_periods(&self) -> Vec<Period> {
+        let mut periods = match &self.periods {
+            Some(periods) => periods.clone(),
+            None => {
+                let mut periods = PERIODS.to_vec();
+                periods.extend_from_slice(&self.custom_periods);
+                periods
+            }
+        };
+        for period in periods.iter_mut().filter(|p| p.name == "month") {
+            period.seconds = self.month.unwrap_or(MONTH);
+        }
+        if self.periods.is_none() {
+            periods.sort_by_key(|p| p.seconds);
+        }
+        return periods;
+    }
+
+    /// The sink that results are written to, on stdout.
+    pub fn sink(&self) -> Box<dyn OutputSink> {
+        return self.sink_to(self.output.as_deref(), std::io::stdout());
+    }
+
+    /// A sink of the output format `format` (text by default) on `out`.
+    pub fn sink_to<'a, W: Write + 'a>(
+        &self,
+        format: Option<&str>,
+        out: W,
+    ) -> Box<dyn OutputSink + 'a> {
+        let formatter = self.formatter();
+        match format {
+            Some("json") => return Box::new(JsonSink::new(out).formatter(formatter)),
+            Some("csv") => return Box::new(CsvSink::new(out).formatter(formatter)),
+            Some("prometheus") => return Box::new(PrometheusSink::new(out).formatter(formatter)),
+            _ => return Box::new(TextSink::new(out).formatter(formatter)),
+        }
+    }
+}
+
+fn unit_system(s: &str) -> Result<UnitSystem, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "si" => return Ok(UnitSystem::Decimal),
+        "iec" => return Ok(UnitSystem::Binary),
+        _ => return Err(format!("unknown unit system {:?} (si iec)", s)),
+    }
+}
+
+pub fn output_format(s: &str) -> Result<String, String> {
+    match s {
+        "text" | "json" | "csv" | "prometheus" => return Ok(s.to_string()),
+        _ => {
+            return Err(format!(
+                "unknown output format {:?} (text json csv prometheus)",
+                s
+            ))
+        }
+    }
+}
+
+/// Looks up the periods of the table, built-in, custom or business ones
+/// (with the lengths of `registry`).
+fn period_list(
+    names: &[&str],
+    custom: &[Period],
+    registry: &Registry,
+) -> Result<Vec<Period>, String> {
+    let mut periods = Vec::new();
+    for name in names {
+        if let Some(period) = custom
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+        {
+            periods.push(*period);
+            continue;
+        }
+        if let Some(period) = registry.business_period(name.trim()) {
+            periods.push(period);
+            continue;
+        }
+        match name.parse::<Period>() {
+            Ok(period) => periods.push(period),
+            Err(e) => return Err(format!("{}: {:?}", e, name.trim())),
+        }
+    }
+    return Ok(periods);
+}
+
+/// A value of the small subset of TOML that the configuration uses.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<String>),
+}
+
+impl Value {
+    /// The value as it is written in the file.
+    fn toml(&self) -> String {
+        match self {
+            Value::String(s) => return format!("\"{}\"", s),
+            Value::Integer(n) => return n.to_string(),
+            Value::Boolean(b) => return b.to_string(),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|i| format!("\"{}\"", i)).collect();
+                return format!("[{}]", items.join(", "));
+            }
+        }
+    }
+
+    /// The value as `rate config set` takes it.
+    fn plain(&self) -> String {
+        match self {
+            Value::String(s) => return s.clone(),
+            Value::Array(items) => return items.join(","),
+            _ => return self.toml(),
+        }
+    }
+}
+
+/// Parses the configuration file. Errors start with their line number.
+fn parse(text: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut section = "";
+    // The periods of the tables may be custom ones, defined further down:
+    // their names are looked up at the end, with the line and the profile
+    // (if any) that they are for.
+    let mut period_names: Vec<(usize, Option<usize>, Vec<String>)> = Vec::new();
+    let mut aliases: Vec<(usize, String, String)> = Vec::new();
+    let mut business: Vec<(usize, &str, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |e: String| format!("{}: {}", i + 1, e);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = match name.trim() {
+                "custom-periods" => "custom-periods",
+                "aliases" => "aliases",
+                name => match name.strip_prefix("profile.") {
+                    Some(profile) if !profile.is_empty() => {
+                        if config.profiles.iter().any(|(p, _)| p == profile) {
+                            return Err(error(format!("profile {:?} defined twice", profile)));
+                        }
+                        config
+                            .profiles
+                            .push((String::from(profile), Config::default()));
+                        "profile"
+                    }
+                    _ => return Err(error(format!("unknown section {:?}", name))),
+                },
+            };
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(error(format!("expected <key> = <value>, got {:?}", line)));
+        };
+        let key = key.trim();
+        let value = parse_value(value.trim()).map_err(error)?;
+        if section == "custom-periods" {
+            let Value::String(length) = value else {
+                return Err(error(format!("expected a duration for {:?}", key)));
+            };
+            let period = custom_period(key, &length).map_err(error)?;
+            config.custom_periods.push(period);
+            continue;
+        }
+        if section == "aliases" {
+            let Value::String(rate) = value else {
+                return Err(error(format!("expected a rate for {:?}", key)));
+            };
+            if key.is_empty() || !key.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                return Err(error(format!(
+                    "an alias starts with a letter, not {:?}",
+                    key
+                )));
+            }
+            aliases.push((i + 1, String::from(key), rate));
+            continue;
+        }
+        let profile = (section == "profile").then(|| config.profiles.len() - 1);
+        let target = match profile {
+            Some(i) => &mut config.profiles[i].1,
+            None => &mut config,
+        };
+        match (key, value) {
+            ("precision", Value::Integer(n)) if (0..=17).contains(&n) => {
+                target.precision = Some(n as usize);
+            }
+            ("output", Value::String(s)) => target.output = Some(output_format(&s).map_err(error)?),
+            ("units", Value::String(s)) => target.units = Some(unit_system(&s).map_err(error)?),
+            ("periods", Value::Array(names)) => period_names.push((i + 1, profile, names)),
+            ("bits", Value::Boolean(bits)) => target.bits = Some(bits),
+            ("nice", Value::Boolean(nice)) => target.nice = Some(nice),
+            ("workday" | "workweek", Value::String(length)) if profile.is_none() => {
+                business.push((i + 1, key, length));
+            }
+            ("month-days", Value::String(days)) if profile.is_none() => {
+                target.month = Some(month_seconds(&days).map_err(error)?);
+            }
+            ("month-days", Value::Integer(days)) if profile.is_none() => {
+                target.month = Some(month_seconds(&days.to_string()).map_err(error)?);
+            }
+            ("precision" | "output" | "units" | "periods" | "bits" | "nice", _) => {
+                return Err(error(format!("invalid value for {:?}", key)));
+            }
+            _ => return Err(error(format!("unknown setting {:?}", key))),
+        }
+    }
+    config.custom_periods.sort_by_key(|p| p.seconds);
+    // A work week may be given in work days, so the work day comes first.
+    business.sort_by_key(|(_, key, _)| *key != "workday");
+    for (line, key, length) in business {
+        let seconds = whole_seconds(&config.registry(), &length);
+        let seconds = Some(seconds.map_err(|e| format!("{}: {}", line, e))?);
+        match key {
+            "workday" => config.workday = seconds,
+            _ => config.workweek = seconds,
+        }
+    }
+    let registry = config.registry();
+    for (line, profile, names) in period_names {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let periods = period_list(&names, &config.custom_periods, &registry);
+        let periods = Some(periods.map_err(|e| format!("{}: {}", line, e))?);
+        match profile {
+            Some(i) => config.profiles[i].1.periods = periods,
+            None => config.periods = periods,
+        }
+    }
+    for (line, name, rate) in aliases {
+        if let Err(e) = registry.parse(&rate) {
+            return Err(format!("{}: {}: {:?}", line, e, rate));
+        }
+        config.aliases.push((name, rate));
+    }
+    return Ok(config);
+}
+
+/// A period such as `sprint = "14 day"`. Its name lives as long as the
+/// program, like those of the built-in periods.
+fn custom_period(name: &str, length: &str) -> Result<Period, String> {
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(format!("a period name has only letters, not {:?}", name));
+    }
+    if period_to_seconds(name).is_ok() {
+        return Err(format!("{:?} is already a period", name));
+    }
+    return Ok(Period {
+        name: String::from(name).leak(),
+        seconds: whole_seconds(&Registry::new(), length)?,
+    });
+}
+
+/// The length of a period, such as "14 day", which is a whole number of
+/// seconds.
+fn whole_seconds(registry: &Registry, length: &str) -> Result<u64, String> {
+    let seconds = registry
+        .parse_duration(length)
+        .map_err(|e| format!("{}: {:?}", e, length))?;
+    if seconds < 1.0 || seconds.fract() != 0.0 {
+        return Err(format!("{:?} is not a whole number of seconds", length));
+    }
+    return Ok(seconds as u64);
+}
+
+/// The length of a month of `days` days, e.g. "31" or "30.44", to the
+/// nearest second.
+fn month_seconds(days: &str) -> Result<u64, String> {
+    match days.trim().parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 && n * DAY as f64 >= 0.5 => {
+            return Ok((n * DAY as f64).round() as u64);
+        }
+        _ => return Err(format!("invalid number of days in a month {:?}", days)),
+    }
+}
+
+/// Parses a string, an integer, a boolean or an array of strings,
+/// followed by an optional comment.
+fn parse_value(s: &str) -> Result<Value, String> {
+    let (value, rest) = if let Some(rest) = s.strip_prefix('[') {
+        let Some(end) = rest.find(']') else {
+            return Err(String::from("unterminated array"));
+        };
+        let mut items = Vec::new();
+        for item in rest[..end].split(',') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            match parse_value(item)? {
+                Value::String(s) => items.push(s),
+                _ => return Err(String::from("expected an array of strings")),
+            }
+        }
+        (Value::Array(items), &rest[end + 1..])
+    } else if let Some(rest) = s.strip_prefix('"') {
+        let Some(end) = rest.find('"') else {
+            return Err(String::from("unterminated string"));
+        };
+        (Value::String(rest[..end].to_string()), &rest[end + 1..])
+    } else {
+        let end = s.find('#').unwrap_or(s.len());
+        match s[..end].trim() {
+            "true" => (Value::Boolean(true), &s[end..]),
+            "false" => (Value::Boolean(false), &s[end..]),
+            n => match n.parse() {
+                Ok(n) => (Value::Integer(n), &s[end..]),
+                Err(_) => return Err(format!("invalid value {:?}", s)),
+            },
+        }
+    };
+    let rest = rest.trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected {:?} after the value", rest));
+    }
+    return Ok(value);
+}
+
+#[test]
+fn test_parse_config() {
+    let config = parse(
+        "# defaults\n\
+         precision = 1\n\
+         units = \"iec\"  # MiB\n\
+         periods = [\"sec\", \"hour\", \"day\"]\n\
+         output = \"text\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.precision, Some(1));
+    assert_eq!(config.units, Some(UnitSystem::Binary));
+    let names: Vec<&str> = config.periods.unwrap().iter().map(|p| p.name).collect();
+    assert_eq!(names, ["sec", "hour", "day"]);
+    assert_eq!(config.output.as_deref(), Some("text"));
+    assert_eq!(parse(""), Ok(Config::default()));
+    assert_eq!(
+        parse("\nprecison = 2"),
+        Err(String::from("2: unknown setting \"precison\""))
+    );
+    assert!(parse("units = \"metric\"").is_err());
+    assert!(parse("periods = [\"fortnight\"]").is_err());
+    assert!(parse("precision = \"2\"").is_err());
+    let config = parse("units = \"iec\"\nperiods = [\"sec\", \"hour\"]").unwrap();
+    assert_eq!(
+        csv_table(&config, rate::Rate::new(1 << 20, 1)),
+        "period,seconds,scaled_value,unit,raw_bytes\n\
+         sec,1,1,MiB,1048576\n\
+         hour,3600,3.515625,GiB,3774873600\n"
+    );
+}
+
+/// The table of `rate` as written with `--output csv`.
+#[cfg(test)]
+fn csv_table(config: &Config, rate: rate::Rate) -> String {
+    let mut out = Vec::new();
+    config.sink_to(Some("csv"), &mut out).table(rate).unwrap();
+    return String::from_utf8(out).unwrap();
+}
+
+#[test]
+fn test_environment() {
+    let config = Config {
+        precision: Some(1),
+        output: Some(String::from("csv")),
+        ..Config::default()
+    };
+    let env = |name: &str| match name {
+        "RATE_PRECISION" => Some(String::from("4")),
+        "RATE_UNITS" => Some(String::from("IEC")),
+        "RATE_FORMAT" => Some(String::new()),
+        _ => None,
+    };
+    let config = config.environment(env).unwrap();
+    assert_eq!(config.precision, Some(4));
+    assert_eq!(config.units, Some(UnitSystem::Binary));
+    assert_eq!(config.output.as_deref(), Some("csv"));
+    let env = |name: &str| (name == "RATE_FORMAT").then(|| String::from("yaml"));
+    assert!(Config::default().environment(env).is_err());
+}
+
+#[test]
+fn test_custom_periods() {
+    let config = parse(
+        "periods = [\"hour\", \"shift\", \"sprint\"]\n\
+         \n\
+         [custom-periods]\n\
+         sprint = \"14 day\"\n\
+         shift = \"8 hour\"\n",
+    )
+    .unwrap();
+    let custom: Vec<(&str, u64)> = config
+        .custom_periods
+        .iter()
+        .map(|p| (p.name, p.seconds))
+        .collect();
+    assert_eq!(custom, [("shift", 28800), ("sprint", 1209600)]);
+    let names: Vec<&str> = config.periods.unwrap().iter().map(|p| p.name).collect();
+    assert_eq!(names, ["hour", "shift", "sprint"]);
+    assert!(parse("[custom-periods]\nday = \"24 hour\"").is_err());
+    assert!(parse("[custom-periods]\ntick = \"0.5s\"").is_err());
+    assert!(parse("[periods]").is_err());
+    let config = parse("[custom-periods]\nshift = \"8 hour\"").unwrap();
+    let table = config.formatter().format_table(rate::Rate::new(1, 1));
+    assert!(table.contains("  3.600 KB / hour\n 28.800 KB / shift\n 86.400 KB / day\n"));
+    assert!(csv_table(&config, rate::Rate::new(1, 1))
+        .contains("\nhour,3600,3.6,KB,3600\nshift,28800,28.8,KB,28800\nday,86400,86.4,KB,86400\n"));
+}
+
+#[test]
+fn test_business_hours() {
+    let config = parse(
+        "workweek = \"4 workday\"\n\
+         workday = \"7.5 hour\"\n\
+         periods = [\"hour\", \"workday\", \"workweek\"]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        (config.workday, config.workweek),
+        (Some(27000), Some(108000))
+    );
+    let periods: Vec<(&str, u64)> = config
+        .periods
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|p| (p.name, p.seconds))
+        .collect();
+    assert_eq!(
+        periods,
+        [("hour", 3600), ("workday", 27000), ("workweek", 108000)]
+    );
+    assert!(csv_table(&config, rate::Rate::new(1, 1))
+        .ends_with("\nworkday,27000,27,KB,27000\nworkweek,108000,108,KB,108000\n"));
+    let config = parse("workday = \"6 hour\"").unwrap();
+    assert_eq!(config.registry().period_to_seconds("workweek"), Ok(108000));
+    assert!(parse("workday = \"0.5s\"").is_err());
+    assert!(parse("[profile.x]\nworkday = \"6 hour\"").is_err());
+}
+
+#[test]
+fn test_month_days() {
+    let config = parse("month-days = \"30.44\"\nperiods = [\"day\", \"month\"]").unwrap();
+    assert_eq!(config.month, Some(2630016));
+    assert_eq!(config.registry().period_to_seconds("month"), Ok(2630016));
+    let periods: Vec<u64> = config.table_periods().iter().map(|p| p.seconds).collect();
+    assert_eq!(periods, [86400, 2630016]);
+    assert!(csv_table(&config, rate::Rate::new(1, 1))
+        .ends_with("\nmonth,2630016,2.630016,MB,2630016\n"));
+    assert_eq!(parse("month-days = 31").unwrap().month, Some(31 * 86400));
+    assert!(parse("month-days = 0").is_err());
+    assert!(parse("month-days = \"a\"").is_err());
+    assert!(parse("[profile.x]\nmonth-days = 31").is_err());
+}
+
+#[test]
+fn test_aliases() {
+    let config = parse(
+        "[aliases]\n\
+         uplink = \"35 Mb/s\"\n\
+         nas = \"280 MB/s\"\n\
+         backlog = \"1 TB/sprint\"\n\
+         [custom-periods]\n\
+         sprint = \"14 day\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.alias("uplink"), Some("35 Mb/s"));
+    assert_eq!(config.alias(" NAS "), Some("280 MB/s"));
+    assert_eq!(config.alias("35 Mb/s"), None);
+    assert_eq!(
+        parse("[aliases]\nwan = \"35 Mbs\""),
+        Err(String::from(
+            "2: not a recognized unit (B KB MB GB TB PB EB ZB YB RB QB): \"35 Mbs\""
+        ))
+    );
+    assert!(parse("[aliases]\n5g = \"1 Gb/s\"").is_err());
+}
+
+#[test]
+fn test_profiles() {
+    let config = parse(
+        "precision = 1\n\
+         [profile.networking]\n\
+         bits = true\n\
+         periods = [\"sec\", \"shift\"]\n\
+         [profile.storage]\n\
+         units = \"iec\"\n\
+         [custom-periods]\n\
+         shift = \"8 hour\"\n",
+    )
+    .unwrap();
+    let (name, networking) = &config.profiles[0];
+    assert_eq!(name, "networking");
+    assert_eq!(networking.bits, Some(true));
+    assert_eq!(networking.precision, None);
+    assert_eq!(networking.periods.as_ref().unwrap()[1].seconds, 28800);
+    let mut storage = config.clone();
+    storage.overlay(&config.profiles[1].1);
+    assert_eq!(storage.precision, Some(1));
+    assert_eq!(storage.units, Some(UnitSystem::Binary));
+    assert!(parse("[profile.a]\n[profile.a]").is_err());
+    assert!(parse("[profile.a]\nbits = 1").is_err());
+}
+
+#[test]
+fn test_set() {
+    let text = "# defaults\nprecision = 1\n\n[aliases]\nuplink = \"35 Mb/s\"\n";
+    assert_eq!(
+        set(text, "precision", "2").unwrap(),
+        "# defaults\nprecision = 2\n\n[aliases]\nuplink = \"35 Mb/s\"\n"
+    );
+    assert_eq!(
+        set(text, "periods", "sec, hour").unwrap(),
+        "# defaults\nprecision = 1\nperiods = [\"sec\", \"hour\"]\n\n[aliases]\nuplink = \"35 Mb/s\"\n"
+    );
+    assert_eq!(
+        set(text, "aliases.nas", "280 MB/s").unwrap(),
+        "# defaults\nprecision = 1\n\n[aliases]\nuplink = \"35 Mb/s\"\nnas = \"280 MB/s\"\n"
+    );
+    assert_eq!(
+        set("", "profile.networking.bits", "true").unwrap(),
+        "[profile.networking]\nbits = true\n"
+    );
+    assert_eq!(
+        set("[aliases]\n", "units", "iec").unwrap(),
+        "units = \"iec\"\n[aliases]\n"
+    );
+    assert_eq!(
+        set(text, "aliases.wan", "35 Mbs"),
+        Err(String::from(
+            "aliases.wan: not a recognized unit (B KB MB GB TB PB EB ZB YB RB QB): \"35 Mbs\""
+        ))
+    );
+    assert_eq!(
+        set(text, "precison", "2"),
+        Err(String::from("precison: unknown setting \"precison\""))
+    );
+    assert!(set(text, "bits", "yes").is_err());
+    assert!(set(text, "custom-periods.tick", "0.5s").is_err());
+
+    let text = set(text, "custom-periods.sprint", "14 day").unwrap();
+    let settings: Vec<(String, String)> = settings(&text)
+        .unwrap()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.plain()))
+        .collect();
+    assert_eq!(
+        settings,
+        [
+            (String::from("precision"), String::from("1")),
+            (String::from("aliases.uplink"), String::from("35 Mb/s")),
+            (
+                String::from("custom-periods.sprint"),
+                String::from("14 day")
+            ),
+        ]
+    );
+}
@@ -0,0 +1,177 @@
+//! `rate countdown`: a live display of the time left in a transfer.
+
+use std::io::{IsTerminal, Write};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output_format;
+use crate::time::{format_timestamp, now};
+use crate::{
+    compression_option, config, format_duration, format_rate, format_size, parse, parse_duration,
+    parse_input, parse_size, remaining_args, split_keyword, Error, ParseError, ParseErrorKind,
+    Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " countdown <size> at <rate> [--stdin] [--refresh <duration>] [--compress <ratio>]\n",
+    "       Shows the remaining time and the projected finish (UTC).\n",
+    "       With --stdin, each line read is the amount done so far (e.g.,\n",
+    "       \"120GB\" or a byte count) and the rate is re-estimated from it.\n",
+    "       --compress (e.g., 2.3:1 or 57%) scales the rate by a compression\n",
+    "       ratio, for a <size> counted before compression."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let from_stdin = pargs.contains("--stdin");
+    let compression = compression_option(&mut pargs)?;
+    let refresh: f64 = pargs
+        .opt_value_from_fn("--refresh", parse_duration)?
+        .unwrap_or(1.0);
+    let refresh = Duration::try_from_secs_f64(refresh)
+        .map_err(|_| Error::Usage(messages::text(Message::InvalidRefresh, &[&refresh])))?;
+    let format = output_format(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((size, rate)) = split_keyword(&args, "at") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<size> at <rate>"],
+        )));
+    };
+    let total = parse_input(&args, size, parse_size)?;
+    let rate = parse_input(&args, rate, parse)? * compression;
+
+    let updates = if from_stdin {
+        Some(spawn_reader())
+    } else {
+        None
+    };
+    let mut progress = Progress::new(now(), rate);
+    let live = format.is_none() && std::io::stdout().is_terminal();
+    let mut out = config::get().sink_to(format.as_deref(), std::io::stdout());
+    loop {
+        if let Some(updates) = &updates {
+            for (t, done) in updates.try_iter() {
+                progress.update(t, done);
+            }
+        }
+        let t = now();
+        let done = progress.done_at(t).min(total);
+        let left = (total - done) / progress.rate;
+        let line = format!(
+            "{} of {} at {}, {} left, done at {} UTC",
+            format_size(done),
+            format_size(total),
+            format_rate(progress.rate),
+            format_duration(left),
+            format_timestamp(t + left)
+        );
+        if live {
+            print!("\r{}\x1b[K", line);
+            std::io::stdout().flush()?;
+        } else {
+            out.line(
+                &line,
+                &[
+                    ("done", Value::Size(done)),
+                    ("total", Value::Size(total)),
+                    ("rate", Value::Rate(Rate::from_decimal(progress.rate))),
+                    ("left", Value::Duration(left)),
+                    ("finish", Value::Text(&format_timestamp(t + left))),
+                ],
+            )?;
+        }
+        if done >= total {
+            break;
+        }
+        let left = Duration::try_from_secs_f64(left.max(0.01)).unwrap_or(Duration::MAX);
+        std::thread::sleep(refresh.min(left));
+    }
+    if live {
+        println!();
+    }
+    return Ok(());
+}
+
+/// Reads progress reports from stdin in the background so that the
+/// display keeps ticking between them.
+fn spawn_reader() -> mpsc::Receiver<(f64, f64)> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if let Ok(done) = parse_done(&line) {
+                if tx.send((now(), done)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    return rx;
+}
+
+fn parse_done(s: &str) -> Result<f64, ParseError> {
+    match s.trim().parse::<f64>() {
+        Ok(b) if b.is_finite() && b >= 0.0 => return Ok(b),
+        Ok(_) => return Err(ParseErrorKind::InvalidNumber.into()),
+        Err(_) => return parse_size(s),
+    }
+}
+
+/// Tracks how much is done, extrapolating from the last known point.
+struct Progress {
+    first: Option<(f64, f64)>,
+    last: (f64, f64),
+    rate: f64,
+}
+
+impl Progress {
+    fn new(start: f64, rate: f64) -> Progress {
+        return Progress {
+            first: None,
+            last: (start, 0.0),
+            rate,
+        };
+    }
+
+    fn update(&mut self, t: f64, done: f64) {
+        match self.first {
+            None => self.first = Some((t, done)),
+            Some((t0, done0)) => {
+                if t > t0 && done > done0 {
+                    self.rate = (done - done0) / (t - t0);
+                }
+            }
+        }
+        self.last = (t, done);
+    }
+
+    fn done_at(&self, t: f64) -> f64 {
+        return self.last.1 + self.rate * (t - self.last.0).max(0.0);
+    }
+}
+
+#[test]
+fn test_parse_done() {
+    assert_eq!(parse_done("1500"), Ok(1500.0));
+    assert_eq!(parse_done("1.5 KB"), Ok(1500.0));
+    assert!(parse_done("nan").is_err());
+    assert!(parse_done("inf").is_err());
+    assert!(parse_done("-1").is_err());
+}
+
+#[test]
+fn test_progress() {
+    let mut p = Progress::new(0.0, 10.0);
+    assert_eq!(p.done_at(5.0), 50.0);
+    p.update(10.0, 200.0);
+    assert_eq!(p.rate, 10.0);
+    assert_eq!(p.done_at(12.0), 220.0);
+    p.update(20.0, 240.0);
+    assert_eq!(p.rate, 4.0);
+    assert_eq!(p.done_at(25.0), 260.0);
+}
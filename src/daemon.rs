@@ -0,0 +1,202 @@
+//! `rate daemon`: sample sources in the background and answer queries
+//! from `rate query` over a Unix socket.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::messages::{self, Message};
+use crate::{parse_duration, Error};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " daemon <source>... [--interval <duration>] [--socket <path>]\n",
+    "       Samples every source every --interval (1s by default) and serves\n",
+    "       the latest rates to `rate query`. A source is a network interface\n",
+    "       (e.g. eth0), a file whose growth is measured, or a named pipe\n",
+    "       whose data is read and discarded. The socket defaults to\n",
+    "       $XDG_RUNTIME_DIR/rate.sock."
+);
+
+/// The socket used when --socket is not given.
+pub fn default_socket() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    return dir.join("rate.sock");
+}
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let interval: f64 = pargs
+        .opt_value_from_fn("--interval", parse_duration)?
+        .unwrap_or(1.0);
+    let socket: PathBuf = pargs
+        .opt_value_from_str("--socket")?
+        .unwrap_or_else(default_socket);
+    let sources: Vec<String> = pargs
+        .finish()
+        .into_iter()
+        .map(|s| s.to_string_lossy().into_owned())
+        .collect();
+    if sources.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NoSource, &[])));
+    }
+    if let Some(option) = sources.iter().find(|s| s.starts_with('-')) {
+        let option = format!("{:?}", option);
+        return Err(Error::Usage(messages::text(
+            Message::UnknownOption,
+            &[&option],
+        )));
+    }
+    if interval <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+    let interval = Duration::try_from_secs_f64(interval)
+        .map_err(|_| Error::Usage(messages::text(Message::IntervalTooLong, &[])))?;
+    return serve(&sources, interval, &socket);
+}
+
+#[cfg(unix)]
+fn serve(sources: &[String], interval: Duration, socket: &std::path::Path) -> Result<(), Error> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use crate::format_rate;
+    use crate::sampler::{Interfaces, Sampler};
+
+    enum Source {
+        Interface(String),
+        File(PathBuf),
+        Pipe(Arc<AtomicU64>),
+    }
+
+    let mut watched = Vec::new();
+    for name in sources {
+        let source = match std::fs::metadata(name) {
+            Ok(meta) if meta.file_type().is_fifo() => {
+                let counter = Arc::new(AtomicU64::new(0));
+                spawn_pipe_reader(PathBuf::from(name), Arc::clone(&counter));
+                Source::Pipe(counter)
+            }
+            Ok(_) => Source::File(PathBuf::from(name)),
+            Err(_) => Source::Interface(name.clone()),
+        };
+        watched.push((name.clone(), source));
+    }
+
+    // Reads every counter, keyed by "<source>" or "<interface> rx|tx".
+    let sample = move || -> Vec<(String, f64)> {
+        let net = Interfaces.sample().unwrap_or_default();
+        let mut counters = Vec::new();
+        for (name, source) in &watched {
+            match source {
+                Source::Interface(iface) => {
+                    for dir in ["rx", "tx"] {
+                        let key = format!("{} {}", iface, dir);
+                        if let Some((_, v)) = net.iter().find(|(k, _)| *k == key) {
+                            counters.push((key, *v));
+                        }
+                    }
+                }
+                Source::File(path) => {
+                    if let Ok(meta) = std::fs::metadata(path) {
+                        counters.push((name.clone(), meta.len() as f64));
+                    }
+                }
+                Source::Pipe(counter) => {
+                    counters.push((name.clone(), counter.load(Ordering::Relaxed) as f64));
+                }
+            }
+        }
+        return counters;
+    };
+
+    let rates: Arc<Mutex<Vec<(String, f64)>>> = Arc::new(Mutex::new(Vec::new()));
+    let latest = Arc::clone(&rates);
+    std::thread::spawn(move || {
+        let mut before = sample();
+        let mut then = Instant::now();
+        loop {
+            std::thread::sleep(interval);
+            let after = sample();
+            let now = Instant::now();
+            let seconds = (now - then).as_secs_f64();
+            let mut current = Vec::new();
+            for (key, new) in &after {
+                if let Some((_, old)) = before.iter().find(|(k, _)| k == key) {
+                    current.push((key.clone(), (new - old).max(0.0) / seconds));
+                }
+            }
+            *latest.lock().unwrap() = current;
+            before = after;
+            then = now;
+        }
+    });
+
+    let _ = std::fs::remove_file(socket);
+    let listener = UnixListener::bind(socket)?;
+    eprintln!(
+        "{}",
+        messages::text(Message::Listening, &[&socket.display()])
+    );
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let mut name = String::new();
+        if BufReader::new(&stream).read_line(&mut name).is_err() {
+            continue;
+        }
+        let name = name.trim();
+        let rates = rates.lock().unwrap().clone();
+        let mut reply = String::new();
+        for (key, rate) in &rates {
+            if name.is_empty()
+                || key == name
+                || key.strip_prefix(name).is_some_and(|r| r.starts_with(' '))
+            {
+                reply.push_str(&format!("{} : {}\n", key, format_rate(*rate)));
+            }
+        }
+        if reply.is_empty() {
+            reply = format!("error: no rate for {:?} yet\n", name);
+        }
+        let _ = stream.write_all(reply.as_bytes());
+    }
+    return Ok(());
+}
+
+/// Reads a named pipe forever, adding the bytes read to `counter`, and
+/// reopening it whenever its writer goes away.
+#[cfg(unix)]
+fn spawn_pipe_reader(path: PathBuf, counter: std::sync::Arc<std::sync::atomic::AtomicU64>) {
+    use std::io::Read;
+    use std::sync::atomic::Ordering;
+    std::thread::spawn(move || {
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let Ok(mut pipe) = std::fs::File::open(&path) else {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                continue;
+            };
+            while let Ok(n) = pipe.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                counter.fetch_add(n as u64, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn serve(_sources: &[String], _interval: Duration, _socket: &std::path::Path) -> Result<(), Error> {
+    return Err(Error::Usage(messages::text(
+        Message::UnixOnly,
+        &[&"daemon"],
+    )));
+}
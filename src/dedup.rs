@@ -0,0 +1,66 @@
+//! `rate dedup`: backup throughput with deduplication and compression.
+
+use rate::sink::Value;
+
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_compression, parse_input, parse_size,
+    remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " dedup <logical size> --ratio <ratio> --device <rate> [--source <rate>]\n",
+    "       --ratio is the combined dedup and compression ratio (e.g., 4:1\n",
+    "       or 25%), --device the rate the target device writes at, and\n",
+    "       --source the rate the data can be read at, if it is a limit."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let ratio: f64 = pargs.value_from_fn("--ratio", parse_compression)?;
+    let device: f64 = pargs.value_from_fn("--device", parse)?;
+    let source: Option<f64> = pargs.opt_value_from_fn("--source", parse)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let logical = parse_input(&args, &args, parse_size)?;
+
+    let mut effective = device * ratio;
+    let limited_by_source = source.is_some_and(|s| s < effective);
+    if let Some(source) = source {
+        effective = effective.min(source);
+    }
+    let mut text = vec![
+        format!("logical   : {}", format_size(logical)),
+        format!(
+            "stored    : {} ({:.2}:1)",
+            format_size(logical / ratio),
+            ratio
+        ),
+        format!("device    : {}", format_rate(device)),
+    ];
+    if limited_by_source {
+        text.push(format!(
+            "effective : {} (limited by the source)",
+            format_rate(effective)
+        ));
+    } else {
+        text.push(format!("effective : {} (logical)", format_rate(effective)));
+    }
+    text.push(format!(
+        "duration  : {}",
+        format_duration(logical / effective)
+    ));
+    out.line(
+        &text.join("\n"),
+        &[
+            ("logical", Value::Size(logical)),
+            ("stored", Value::Size(logical / ratio)),
+            ("ratio", Value::Number(ratio)),
+            ("device", Value::Rate(Rate::from_decimal(device))),
+            ("effective", Value::Rate(Rate::from_decimal(effective))),
+            ("duration", Value::Duration(logical / effective)),
+        ],
+    )?;
+    return Ok(());
+}
@@ -0,0 +1,324 @@
+//! `rate diff`: rates from two snapshots of the same counters.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::sampler::{Interfaces, Sampler};
+use crate::{format_rate, parse_duration, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " diff [<before> [<after>]] [--interval <duration>]\n",
+    "       ",
+    env!("CARGO_BIN_NAME"),
+    " diff --live [--interval <duration>]\n",
+    "       Snapshots have one \"<key> <counter>\" per line, or are dumps\n",
+    "       of /proc/net/dev or `ip -s link`. The interval defaults to the\n",
+    "       difference between the modification times of the two files.\n",
+    "       Without <after>, the counters of /proc/net/dev or `ip -s link`\n",
+    "       are read now, and the interval defaults to the age of <before>.\n",
+    "       Without files, one or two snapshots are read from stdin (pasted\n",
+    "       one after the other), and --interval is required.\n",
+    "       With --live, the interface counters of the system are sampled\n",
+    "       twice, --interval (1s by default) apart."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let interval: Option<f64> = pargs.opt_value_from_fn("--interval", parse_duration)?;
+    let live = pargs.contains("--live");
+    let mut out = output(&mut pargs)?;
+    let before: Option<String> = pargs.opt_free_from_str()?;
+    let after: Option<String> = pargs.opt_free_from_str()?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    let (before, after, seconds) = match (before, after) {
+        (Some(_), _) if live => {
+            return Err(Error::Usage(messages::text(Message::LiveNoFiles, &[])));
+        }
+        (None, _) if live => {
+            let interval = interval.unwrap_or(1.0);
+            if interval <= 0.0 {
+                return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+            }
+            let interval = Duration::try_from_secs_f64(interval)
+                .map_err(|_| Error::Usage(messages::text(Message::IntervalTooLong, &[])))?;
+            let start = Instant::now();
+            let before = Interfaces.sample()?;
+            std::thread::sleep(interval);
+            let after = Interfaces.sample()?;
+            (before, after, start.elapsed().as_secs_f64())
+        }
+        (Some(before), Some(after)) => {
+            let seconds = match interval {
+                Some(s) => s,
+                None => mtime_difference(Path::new(&before), Path::new(&after))?,
+            };
+            let before = parse_snapshot(&std::fs::read_to_string(&before)?);
+            let after = parse_snapshot(&std::fs::read_to_string(&after)?);
+            (before, after, seconds)
+        }
+        (Some(before), None) => {
+            let seconds = match interval {
+                Some(s) => s,
+                None => age(Path::new(&before))?,
+            };
+            let before = std::fs::read_to_string(&before)?;
+            let after = parse_snapshot(&current_snapshot(&before)?);
+            (parse_snapshot(&before), after, seconds)
+        }
+        (None, _) => {
+            let Some(seconds) = interval else {
+                return Err(Error::Usage(messages::text(Message::IntervalForStdin, &[])));
+            };
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            let mut before = parse_snapshot(&text);
+            let after = match second_snapshot(&before) {
+                Some(i) => before.split_off(i),
+                None => parse_snapshot(&current_snapshot(&text)?),
+            };
+            (before, after, seconds)
+        }
+    };
+    if seconds <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+
+    let width = after.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    for (key, new) in &after {
+        let Some((_, old)) = before.iter().find(|(k, _)| k == key) else {
+            continue;
+        };
+        if new < old {
+            let text = format!("{:<width$} : counter reset", key, width = width);
+            out.line(
+                &text,
+                &[
+                    ("key", Value::Text(key)),
+                    ("status", Value::Text("counter reset")),
+                ],
+            )?;
+        } else {
+            let rate = (new - old) / seconds;
+            let text = format!("{:<width$} : {}", key, format_rate(rate), width = width);
+            out.line(
+                &text,
+                &[
+                    ("key", Value::Text(key)),
+                    ("rate", Value::Rate(Rate::from_decimal(rate))),
+                ],
+            )?;
+        }
+    }
+    return Ok(());
+}
+
+fn mtime_difference(before: &Path, after: &Path) -> Result<f64, Error> {
+    let before = std::fs::metadata(before)?.modified()?;
+    let after = std::fs::metadata(after)?.modified()?;
+    match after.duration_since(before) {
+        Ok(d) => return Ok(d.as_secs_f64()),
+        Err(_) => return Err(Error::Usage(messages::text(Message::AfterOlder, &[]))),
+    }
+}
+
+/// How long ago a snapshot was taken, from its modification time.
+fn age(path: &Path) -> Result<f64, Error> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    match SystemTime::now().duration_since(modified) {
+        Ok(d) => return Ok(d.as_secs_f64()),
+        Err(_) => return Err(Error::Usage(messages::text(Message::BeforeFuture, &[]))),
+    }
+}
+
+/// Takes a snapshot of the kind of `before` now: /proc/net/dev, or the
+/// output of `ip -s link`.
+fn current_snapshot(before: &str) -> Result<String, Error> {
+    if is_proc_net_dev(before) {
+        return Ok(std::fs::read_to_string("/proc/net/dev")?);
+    }
+    if is_ip_link(before) {
+        let output = Command::new("ip").args(["-s", "link"]).output()?;
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    return Err(Error::Usage(messages::text(Message::SnapshotKind, &[])));
+}
+
+/// Where the second of two snapshots pasted one after the other starts:
+/// at the first counter seen again.
+fn second_snapshot(counters: &[(String, f64)]) -> Option<usize> {
+    return (1..counters.len()).find(|&i| counters[..i].iter().any(|(k, _)| *k == counters[i].0));
+}
+
+fn is_proc_net_dev(text: &str) -> bool {
+    return text.trim_start().starts_with("Inter-|");
+}
+
+fn is_ip_link(text: &str) -> bool {
+    return text.lines().any(|l| l.trim_start().starts_with("RX:"));
+}
+
+/// Extracts the named counters of a snapshot, in file order.
+pub fn parse_snapshot(text: &str) -> Vec<(String, f64)> {
+    if is_proc_net_dev(text) {
+        return parse_proc_net_dev(text);
+    }
+    if is_ip_link(text) {
+        return parse_ip_link(text);
+    }
+    let mut counters = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.rsplit_once(char::is_whitespace) else {
+            continue;
+        };
+        if let Ok(value) = value.parse::<f64>() {
+            counters.push((key.trim().trim_end_matches(':').to_string(), value));
+        }
+    }
+    return counters;
+}
+
+/// /proc/net/dev has two header lines, then "<iface>: <rx...> <tx...>"
+/// where the received and transmitted bytes are the 1st and 9th columns.
+fn parse_proc_net_dev(text: &str) -> Vec<(String, f64)> {
+    let mut counters = Vec::new();
+    for line in text.lines() {
+        let Some((iface, columns)) = line.split_once(':') else {
+            continue;
+        };
+        if iface.contains('|') {
+            continue;
+        }
+        let columns: Vec<f64> = columns
+            .split_whitespace()
+            .filter_map(|c| c.parse().ok())
+            .collect();
+        if columns.len() < 9 {
+            continue;
+        }
+        counters.push((format!("{} rx", iface.trim()), columns[0]));
+        counters.push((format!("{} tx", iface.trim()), columns[8]));
+    }
+    return counters;
+}
+
+/// `ip -s link` has a "<index>: <iface>: <flags> ..." line per interface,
+/// then "RX:" and "TX:" headers, each followed by a line of counters that
+/// starts with the bytes.
+fn parse_ip_link(text: &str) -> Vec<(String, f64)> {
+    let mut counters = Vec::new();
+    let mut iface = "";
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if line.starts_with(|c: char| c.is_ascii_digit()) {
+            let mut fields = line.split(": ");
+            // veth0@if5 is veth0.
+            let name = fields.nth(1).unwrap_or("");
+            iface = name.split('@').next().unwrap_or(name);
+            continue;
+        }
+        let direction = match line.split_whitespace().next() {
+            Some("RX:") => "rx",
+            Some("TX:") => "tx",
+            _ => continue,
+        };
+        let bytes = lines
+            .next()
+            .and_then(|l| l.split_whitespace().next())
+            .and_then(|b| b.parse::<f64>().ok());
+        if let Some(bytes) = bytes {
+            counters.push((format!("{} {}", iface, direction), bytes));
+        }
+    }
+    return counters;
+}
+
+#[test]
+fn test_parse_snapshot() {
+    let text = "# comment\nrequests_bytes 1000\ndisk.sda: 42\nnot a counter\n";
+    assert_eq!(
+        parse_snapshot(text),
+        vec![
+            (String::from("requests_bytes"), 1000.0),
+            (String::from("disk.sda"), 42.0),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_proc_net_dev() {
+    let text = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0
+  eth0: 5000000    4000    0    0    0     0          0         0   700000     900    0    0    0     0       0          0
+";
+    assert_eq!(
+        parse_snapshot(text),
+        vec![
+            (String::from("lo rx"), 1000.0),
+            (String::from("lo tx"), 1000.0),
+            (String::from("eth0 rx"), 5000000.0),
+            (String::from("eth0 tx"), 700000.0),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_ip_link() {
+    let text = "\
+1: lo: <LOOPBACK,UP,LOWER_UP> mtu 65536 qdisc noqueue state UNKNOWN mode DEFAULT group default qlen 1000
+    link/loopback 00:00:00:00:00:00 brd 00:00:00:00:00:00
+    RX:  bytes packets errors dropped  missed   mcast
+          1000      10      0       0       0       0
+    TX:  bytes packets errors dropped carrier collsns
+          1000      10      0       0       0       0
+3: veth0@if5: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc noqueue state UP mode DEFAULT group default
+    link/ether 02:42:ac:11:00:02 brd ff:ff:ff:ff:ff:ff link-netnsid 0
+    RX: bytes  packets  errors  dropped overrun mcast
+    5000000    4000     0       0       0       0
+    TX: bytes  packets  errors  dropped carrier collsns
+    700000     900      0       0       0       0
+";
+    assert_eq!(
+        parse_snapshot(text),
+        vec![
+            (String::from("lo rx"), 1000.0),
+            (String::from("lo tx"), 1000.0),
+            (String::from("veth0 rx"), 5000000.0),
+            (String::from("veth0 tx"), 700000.0),
+        ]
+    );
+}
+
+#[test]
+fn test_second_snapshot() {
+    let text = "a 1\nb 2\na 11\nb 22\n";
+    assert_eq!(second_snapshot(&parse_snapshot(text)), Some(2));
+    assert_eq!(second_snapshot(&parse_snapshot("a 1\nb 2\n")), None);
+    let dev = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+  eth0: 5000000    4000    0    0    0     0          0         0   700000     900    0    0    0     0       0          0
+";
+    let pasted = format!("{}{}", dev, dev.replace("5000000", "6000000"));
+    let counters = parse_snapshot(&pasted);
+    assert_eq!(second_snapshot(&counters), Some(2));
+    assert_eq!(counters[2], (String::from("eth0 rx"), 6000000.0));
+}
@@ -0,0 +1,131 @@
+//! `rate duplex`: links with different rates up and down.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_duration, format_size, parse, parse_input, parse_size, remaining_args,
+    split_keyword, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " duplex <rate> down <rate> up [--download <size>] [--upload <size>]\n",
+    "       Plans the use of an asymmetric link: how long --download and\n",
+    "       --upload take on their own, and together, when the ACKs of\n",
+    "       each direction's TCP traffic take some of the other direction."
+);
+
+/// The share of a TCP stream's rate that its ACKs take the other way:
+/// a 40-byte ACK for every two 1460-byte segments.
+const ACK_RATIO: f64 = 40.0 / (2.0 * 1460.0);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let download: Option<f64> = pargs.opt_value_from_fn("--download", parse_size)?;
+    let upload: Option<f64> = pargs.opt_value_from_fn("--upload", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let usage = || {
+        Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<rate> down <rate> up"],
+        ))
+    };
+    let (down, rest) = split_keyword(&args, "down").ok_or_else(usage)?;
+    let (up, rest) = split_keyword(rest, "up").ok_or_else(usage)?;
+    if !rest.is_empty() {
+        return Err(usage());
+    }
+    let down = parse_input(&args, down, parse)?;
+    let up = parse_input(&args, up, parse)?;
+    if down <= 0.0 || up <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::RatesPositive, &[])));
+    }
+
+    let mut text = vec![
+        format!("down     : {}", format_bitrate(down)),
+        format!(
+            "up       : {} ({:.1}% of down)",
+            format_bitrate(up),
+            up / down * 100.0
+        ),
+    ];
+    let mut fields = vec![
+        ("down", Value::Bitrate(Rate::from_decimal(down))),
+        ("up", Value::Bitrate(Rate::from_decimal(up))),
+    ];
+    let alone = [
+        ("download", download, down.min(up / ACK_RATIO)),
+        ("upload", upload, up.min(down / ACK_RATIO)),
+    ];
+    for (name, size, rate) in alone {
+        if let Some(size) = size {
+            text.push(format!(
+                "{:<8} : {} in {}",
+                name,
+                format_size(size),
+                format_duration(size / rate)
+            ));
+            fields.push((name, Value::Duration(size / rate)));
+        }
+    }
+    let (Some(download), Some(upload)) = (download, upload) else {
+        out.line(&text.join("\n"), &fields)?;
+        return Ok(());
+    };
+    let (d, u) = together(down, up);
+    text.push(format!(
+        "together : {} down, {} up",
+        format_bitrate(d),
+        format_bitrate(u)
+    ));
+    if u <= 0.0 {
+        text.push(String::from(
+            "warning  : the download's ACKs fill the uplink, the upload waits",
+        ));
+    }
+    // Whichever finishes first leaves the other the whole link.
+    let (first, second) = (download / d, upload / u);
+    let (download, upload) = match first <= second {
+        true => (first, first + (upload - u * first) / alone[1].2),
+        false => (second + (download - d * second) / alone[0].2, second),
+    };
+    text.push(format!(
+        "both     : download in {}, upload in {}",
+        format_duration(download),
+        format_duration(upload)
+    ));
+    fields.push(("together_down", Value::Bitrate(Rate::from_decimal(d))));
+    fields.push(("together_up", Value::Bitrate(Rate::from_decimal(u))));
+    fields.push(("both_download", Value::Duration(download)));
+    fields.push(("both_upload", Value::Duration(upload)));
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// The rates of a download and an upload that run at once, each taking
+/// `ACK_RATIO` of the other's rate in the other direction.
+fn together(down: f64, up: f64) -> (f64, f64) {
+    let d = (down - ACK_RATIO * up) / (1.0 - ACK_RATIO * ACK_RATIO);
+    let u = up - ACK_RATIO * d;
+    if u < 0.0 {
+        return (down.min(up / ACK_RATIO), 0.0);
+    }
+    return (d, u);
+}
+
+#[test]
+fn test_together() {
+    // Symmetric: each direction loses its share of the other's ACKs.
+    let (d, u) = together(100.0, 100.0);
+    assert!((d - 100.0 / (1.0 + ACK_RATIO)).abs() < 1e-9);
+    assert!((u - d).abs() < 1e-9);
+    // 1000 down leaves 50 up with 50 - 13.7 for the upload.
+    let (d, u) = together(1000.0, 50.0);
+    assert!((d + ACK_RATIO * u - 1000.0).abs() < 1e-9);
+    assert!((u + ACK_RATIO * d - 50.0).abs() < 1e-9);
+    // An uplink too thin for the ACKs holds the upload back.
+    assert_eq!(together(1000.0, 10.0), (10.0 / ACK_RATIO, 0.0));
+}
@@ -0,0 +1,143 @@
+//! `rate egress`: monthly bill for data leaving a cloud provider.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::plan::parse_allowance;
+use crate::report::output;
+use crate::{format_rate, format_size, month, parse, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " egress <aws|gcp|azure> <size>/<period> | --rate <rate>\n",
+    "       e.g. egress aws 40TB/month, egress gcp --rate 15MB/s\n",
+    "       Prices the monthly internet egress of a provider with its tiers\n",
+    "       (list prices in USD for North America, which change over time)."
+);
+
+struct Provider {
+    name: &'static str,
+    /// Upper bound of each tier in GB per month and its price per GB.
+    /// Providers bill binary gigabytes, so 10TB is 10,240GB.
+    tiers: &'static [(f64, f64)],
+}
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+const PROVIDERS: &[Provider] = &[
+    Provider {
+        name: "aws",
+        tiers: &[
+            (100.0, 0.0),
+            (10_240.0, 0.09),
+            (51_200.0, 0.085),
+            (153_600.0, 0.07),
+            (f64::INFINITY, 0.05),
+        ],
+    },
+    Provider {
+        name: "gcp",
+        tiers: &[(1_024.0, 0.12), (10_240.0, 0.11), (f64::INFINITY, 0.08)],
+    },
+    Provider {
+        name: "azure",
+        tiers: &[
+            (100.0, 0.0),
+            (10_240.0, 0.087),
+            (51_200.0, 0.083),
+            (153_600.0, 0.07),
+            (f64::INFINITY, 0.05),
+        ],
+    },
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let rate: Option<f64> = pargs.opt_value_from_fn("--rate", parse)?;
+    let mut out = output(&mut pargs)?;
+    let name: String = pargs.free_from_str()?;
+    let args = remaining_args(pargs)?;
+    let Some(provider) = PROVIDERS.iter().find(|p| p.name == name) else {
+        let names: Vec<&str> = PROVIDERS.iter().map(|p| p.name).collect();
+        return Err(Error::Usage(messages::text(
+            Message::UnknownProvider,
+            &[&format!("{:?}", name), &names.join(" ")],
+        )));
+    };
+    let monthly = match (rate, args.is_empty()) {
+        (Some(rate), true) => rate * month() as f64,
+        (None, false) => {
+            let (size, seconds) = parse_allowance(&args)?;
+            size / seconds * month() as f64
+        }
+        _ => {
+            return Err(Error::Usage(messages::text(
+                Message::ExpectedEither,
+                &[&"<size>/<period>", &"--rate"],
+            )))
+        }
+    };
+
+    let average = monthly / month() as f64;
+    let text = format!(
+        "egress  : {} / month\naverage : {}",
+        format_size(monthly),
+        format_rate(average)
+    );
+    out.line(
+        &text,
+        &[
+            ("egress", Value::Size(monthly)),
+            ("average", Value::Rate(Rate::from_decimal(average))),
+        ],
+    )?;
+    let mut total = 0.0;
+    for (gb, price) in bill(provider.tiers, monthly / GIB) {
+        let text = format!(
+            "tier    : {:>12.1} GB at {:.3} = {:>10.2}",
+            gb,
+            price,
+            gb * price
+        );
+        out.line(
+            &text,
+            &[
+                ("gb", Value::Number(gb)),
+                ("price", Value::Number(price)),
+                ("cost", Value::Number(gb * price)),
+            ],
+        )?;
+        total += gb * price;
+    }
+    out.line(
+        &format!("total   : {:.2} USD / month", total),
+        &[("total", Value::Number(total))],
+    )?;
+    return Ok(());
+}
+
+/// Splits `gb` gigabytes of monthly egress over the tiers, returning
+/// the amount billed at each price.
+fn bill(tiers: &[(f64, f64)], gb: f64) -> Vec<(f64, f64)> {
+    let mut lines = Vec::new();
+    let mut floor = 0.0;
+    for (ceiling, price) in tiers {
+        if gb <= floor {
+            break;
+        }
+        lines.push((gb.min(*ceiling) - floor, *price));
+        floor = *ceiling;
+    }
+    return lines;
+}
+
+#[test]
+fn test_bill() {
+    let aws = PROVIDERS[0].tiers;
+    assert_eq!(bill(aws, 50.0), vec![(50.0, 0.0)]);
+    assert_eq!(
+        bill(aws, 20_000.0),
+        vec![(100.0, 0.0), (10_140.0, 0.09), (9_760.0, 0.085)]
+    );
+    assert!(bill(aws, 0.0).is_empty());
+}
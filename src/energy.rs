@@ -0,0 +1,74 @@
+//! `rate energy`: electricity used to move data at a given rate.
+
+use rate::sink::Value;
+
+use crate::report::output;
+use crate::{format_rate, month, parse, parse_input, remaining_args, Error, Rate, DAY, HOUR};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " energy <rate> [--kwh-per-gb <n>] [--price <per kWh>]\n",
+    "       Prints the energy consumed per hour, day and month by a transfer\n",
+    "       at <rate>, from an intensity in kWh per GB (0.06 by default, the\n",
+    "       estimate for transmission networks by Aslan et al.), and its\n",
+    "       cost at an electricity price."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let intensity: f64 = pargs.opt_value_from_str("--kwh-per-gb")?.unwrap_or(0.06);
+    let price: Option<f64> = pargs.opt_value_from_str("--price")?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let rate = parse_input(&args, &args, parse)?;
+
+    let mut text = vec![
+        format!("rate      : {}", format_rate(rate)),
+        format!("intensity : {} kWh / GB", intensity),
+    ];
+    let mut fields = vec![
+        ("rate", Value::Rate(Rate::from_decimal(rate))),
+        ("intensity", Value::Number(intensity)),
+    ];
+    for (name, seconds, kwh_field, cost_field) in [
+        ("hour", HOUR, "kwh_per_hour", "cost_per_hour"),
+        ("day", DAY, "kwh_per_day", "cost_per_day"),
+        ("month", month(), "kwh_per_month", "cost_per_month"),
+    ] {
+        let kwh = rate * seconds as f64 / 1e9 * intensity;
+        fields.push((kwh_field, Value::Number(kwh)));
+        match price {
+            Some(price) => {
+                text.push(format!(
+                    "per {:<5} : {} ({:.2})",
+                    name,
+                    format_energy(kwh),
+                    kwh * price
+                ));
+                fields.push((cost_field, Value::Number(kwh * price)));
+            }
+            None => text.push(format!("per {:<5} : {}", name, format_energy(kwh))),
+        }
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Formats an amount of energy given in kWh with the nearest prefix.
+fn format_energy(kwh: f64) -> String {
+    let mut wh = kwh * 1000.0;
+    for prefix in ["", "k", "M", "G"] {
+        if wh < 1000.0 || prefix == "G" {
+            return format!("{:.3} {}Wh", wh, prefix);
+        }
+        wh /= 1000.0;
+    }
+    unreachable!();
+}
+
+#[test]
+fn test_format_energy() {
+    assert_eq!(format_energy(0.5), "500.000 Wh");
+    assert_eq!(format_energy(1.5), "1.500 kWh");
+    assert_eq!(format_energy(2500.0), "2.500 MWh");
+}
@@ -0,0 +1,136 @@
+//! Streaming estimation of a rate from byte counters.
+
+use std::collections::VecDeque;
+
+use crate::Rate;
+
+/// Estimates a rate from `(timestamp, bytes)` samples of a growing byte
+/// counter, such as the total copied so far or an interface counter.
+/// Timestamps are in seconds from any fixed origin.
+///
+/// ```
+/// let mut estimator = rate::RateEstimator::new(10.0, 60.0);
+/// estimator.push(0.0, 0);
+/// estimator.push(1.0, 1_000_000);
+/// estimator.push(2.0, 3_000_000);
+/// assert_eq!(estimator.instantaneous().unwrap().to_string(), "2.000 MB / sec");
+/// assert_eq!(estimator.windowed().unwrap().to_string(), "1.500 MB / sec");
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateEstimator {
+    half_life: f64,
+    window: f64,
+    first: f64,
+    /// The last sample, as read from the counter.
+    last: Option<(f64, u64)>,
+    /// The samples within `window` of the latest, and the one before,
+    /// as bytes counted since the first sample, across counter resets.
+    samples: VecDeque<(f64, u64)>,
+    instantaneous: Option<f64>,
+    ewma: Option<f64>,
+}
+
+impl RateEstimator {
+    /// An estimator whose moving average gives half its weight to the
+    /// last `half_life` seconds, and whose windowed average covers the
+    /// last `window` seconds.
+    pub fn new(half_life: f64, window: f64) -> RateEstimator {
+        return RateEstimator {
+            half_life,
+            window,
+            first: 0.0,
+            last: None,
+            samples: VecDeque::new(),
+            instantaneous: None,
+            ewma: None,
+        };
+    }
+
+    /// Records that the counter read `bytes` at `timestamp`. A counter
+    /// that went down is taken to have been reset to zero in between.
+    /// Samples that are not newer than the last one are ignored.
+    pub fn push(&mut self, timestamp: f64, bytes: u64) {
+        let Some((then, before)) = self.last else {
+            self.first = timestamp;
+            self.last = Some((timestamp, bytes));
+            self.samples.push_back((timestamp, 0));
+            return;
+        };
+        let elapsed = timestamp - then;
+        if elapsed <= 0.0 {
+            return;
+        }
+        self.last = Some((timestamp, bytes));
+        let delta = bytes.checked_sub(before).unwrap_or(bytes);
+        let rate = delta as f64 / elapsed;
+        self.instantaneous = Some(rate);
+        self.ewma = match self.ewma {
+            None => Some(rate),
+            Some(average) => {
+                let weight = 0.5f64.powf(elapsed / self.half_life);
+                Some(weight * average + (1.0 - weight) * rate)
+            }
+        };
+        let (_, total) = *self.samples.back().unwrap();
+        self.samples.push_back((timestamp, total + delta));
+        while self.samples.len() > 2 && timestamp - self.samples[1].0 >= self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The rate between the last two samples.
+    pub fn instantaneous(&self) -> Option<Rate> {
+        return self.instantaneous.map(Rate::from_bytes_per_second);
+    }
+
+    /// The exponentially-weighted moving average of the rate.
+    pub fn ewma(&self) -> Option<Rate> {
+        return self.ewma.map(Rate::from_bytes_per_second);
+    }
+
+    /// The average rate over the last `window` seconds, or since the
+    /// first sample if that is more recent.
+    pub fn windowed(&self) -> Option<Rate> {
+        let (&(start, before), &(end, after)) = (self.samples.front()?, self.samples.back()?);
+        if end <= start {
+            return None;
+        }
+        return Some(Rate::from_bytes_per_second(
+            (after - before) as f64 / (end - start),
+        ));
+    }
+
+    /// The average rate since the first sample.
+    pub fn mean(&self) -> Option<Rate> {
+        let (end, total) = *self.samples.back()?;
+        if end <= self.first {
+            return None;
+        }
+        return Some(Rate::from_bytes_per_second(
+            total as f64 / (end - self.first),
+        ));
+    }
+}
+
+#[test]
+fn test_rate_estimator() {
+    let mut e = RateEstimator::new(1.0, 2.0);
+    assert_eq!(e.instantaneous(), None);
+    e.push(0.0, 100);
+    assert_eq!(e.mean(), None);
+    e.push(1.0, 1100);
+    assert_eq!(e.ewma(), Some(Rate::new(1000, 1)));
+    e.push(2.0, 4100);
+    assert_eq!(e.instantaneous(), Some(Rate::new(3000, 1)));
+    assert_eq!(e.ewma(), Some(Rate::new(2000, 1)));
+    // The counter was reset: 500 bytes were counted since.
+    e.push(3.0, 500);
+    assert_eq!(e.instantaneous(), Some(Rate::new(500, 1)));
+    assert_eq!(e.windowed(), Some(Rate::new(3500, 2)));
+    assert_eq!(e.mean(), Some(Rate::new(4500, 3)));
+    e.push(3.0, 9999);
+    assert_eq!(e.instantaneous(), Some(Rate::new(500, 1)));
+    e.push(4.0, 600);
+    assert_eq!(e.instantaneous(), Some(Rate::new(100, 1)));
+    assert_eq!(e.windowed(), Some(Rate::new(600, 2)));
+}
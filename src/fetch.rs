@@ -0,0 +1,99 @@
+//! `rate fetch`: download speed of a URL.
+
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use crate::messages::{self, Message};
+use crate::report::{Reports, SampleLog};
+use crate::{
+    format_bitrate, format_duration, format_size, parse_duration, remaining_args, Error, Rate,
+    RateEstimator,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " fetch <url> [--duration <duration>] [--interval <duration>] [--log <file>]\n",
+    "       [--summary-only] [--output <format>] [--size <size>]\n",
+    "       Downloads <url>, discarding the data, until it ends or for\n",
+    "       --duration, printing the rate every --interval (1s by default)\n",
+    "       and the average at the end. --log appends each sample to <file>\n",
+    "       as a line of JSON, which `rate log` reads. --summary-only prints\n",
+    "       the average only. --output json or csv prints a record per\n",
+    "       interval and one for the average. --size shows a progress bar,\n",
+    "       the percentage and the time left towards <size>, as pv does."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let duration: Option<f64> = pargs.opt_value_from_fn("--duration", parse_duration)?;
+    let interval: f64 = pargs
+        .opt_value_from_fn("--interval", parse_duration)?
+        .unwrap_or(1.0);
+    let mut log = SampleLog::option(&mut pargs)?;
+    let mut reports = Reports::options(&mut pargs, Box::new(std::io::stdout()))?;
+    let url: String = pargs.free_from_str()?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if interval <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+    let limit = duration
+        .map(Duration::try_from_secs_f64)
+        .transpose()
+        .map_err(|_| Error::Usage(messages::text(Message::DurationTooLong, &[])))?;
+    let interval = Duration::try_from_secs_f64(interval)
+        .map_err(|_| Error::Usage(messages::text(Message::IntervalTooLong, &[])))?;
+
+    let response = ureq::get(&url)
+        .call()
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    let mut body = response.into_reader();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut total: u64 = 0;
+    let mut estimator = RateEstimator::new(interval.as_secs_f64(), interval.as_secs_f64());
+    estimator.push(0.0, 0);
+    let start = Instant::now();
+    let mut last = start;
+    loop {
+        let n = body.read(&mut buf)?;
+        total += n as u64;
+        let now = Instant::now();
+        if now - last >= interval || n == 0 {
+            estimator.push((now - start).as_secs_f64(), total);
+            if let Some(rate) = estimator.instantaneous() {
+                let seconds = (now - start).as_secs_f64();
+                let line = format!(
+                    "{:>8.1}s : {} ({})",
+                    seconds,
+                    rate,
+                    format_bitrate(rate.bytes_per_second())
+                );
+                reports.sample(seconds, total, rate, &line)?;
+            }
+            if let Some(log) = &mut log {
+                log.write(total, &estimator)?;
+            }
+            last = now;
+        }
+        if n == 0 || limit.is_some_and(|limit| now - start >= limit) {
+            break;
+        }
+    }
+
+    let seconds = start.elapsed().as_secs_f64();
+    let line = format!(
+        "\nreceived : {} in {}",
+        format_size(total as f64),
+        format_duration(seconds)
+    );
+    let average = Rate::from_bytes_per_second(total as f64 / seconds);
+    if reports.summary(seconds, total, average, &line)? {
+        reports.table(average)?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,97 @@
+//! C bindings, with the `cdylib` feature. The header is include/rate.h.
+//!
+//! Build the shared library with
+//! `cargo rustc --lib --release --no-default-features --features cdylib
+//! --crate-type cdylib`.
+
+use std::ffi::{c_char, CStr};
+
+use crate::{format_rate, parse, ParseErrorKind};
+
+/// The expression was parsed.
+pub const RATE_OK: i32 = 0;
+/// A pointer argument was null, or the expression was not UTF-8.
+pub const RATE_INVALID_ARGUMENT: i32 = -1;
+pub const RATE_INVALID_NUMBER: i32 = 1;
+pub const RATE_INVALID_UNIT: i32 = 2;
+pub const RATE_INVALID_PERIOD: i32 = 3;
+pub const RATE_UNEXPECTED_CHARACTER: i32 = 4;
+pub const RATE_TRAILING_INPUT: i32 = 5;
+
+/// Parses the NUL-terminated rate expression `s` (e.g. "12 MB/s") and
+/// stores it in bytes per second into `bytes_per_second`. Returns
+/// `RATE_OK`, or the error code of why the expression is invalid.
+///
+/// # Safety
+///
+/// `s` must be null or a NUL-terminated string, and `bytes_per_second`
+/// null or a valid pointer to a double. Null pointers are rejected with
+/// `RATE_INVALID_ARGUMENT`.
+#[no_mangle]
+pub unsafe extern "C" fn rate_parse(s: *const c_char, bytes_per_second: *mut f64) -> i32 {
+    if s.is_null() || bytes_per_second.is_null() {
+        return RATE_INVALID_ARGUMENT;
+    }
+    let Ok(s) = CStr::from_ptr(s).to_str() else {
+        return RATE_INVALID_ARGUMENT;
+    };
+    match parse(s).map_err(|e| e.kind) {
+        Ok(rate) => {
+            *bytes_per_second = rate.bytes_per_second();
+            return RATE_OK;
+        }
+        Err(ParseErrorKind::InvalidNumber) => return RATE_INVALID_NUMBER,
+        Err(ParseErrorKind::InvalidUnit) => return RATE_INVALID_UNIT,
+        Err(ParseErrorKind::InvalidPeriod) => return RATE_INVALID_PERIOD,
+        Err(ParseErrorKind::UnexpectedCharacter { .. }) => return RATE_UNEXPECTED_CHARACTER,
+        Err(ParseErrorKind::TrailingInput) => return RATE_TRAILING_INPUT,
+    }
+}
+
+/// Writes `bytes_per_second` as e.g. "50.000 MB / sec" into `buf`,
+/// truncated to `len` bytes including the terminating NUL, like
+/// snprintf. Returns the length of the full string, without the NUL.
+///
+/// # Safety
+///
+/// `buf` must point to at least `len` writable bytes, or be null with a
+/// `len` of 0.
+#[no_mangle]
+pub unsafe extern "C" fn rate_format(bytes_per_second: f64, buf: *mut c_char, len: usize) -> usize {
+    let s = format_rate(bytes_per_second);
+    if !buf.is_null() && len > 0 {
+        let n = s.len().min(len - 1);
+        std::ptr::copy_nonoverlapping(s.as_ptr(), buf as *mut u8, n);
+        *buf.add(n) = 0;
+    }
+    return s.len();
+}
+
+#[test]
+fn test_rate_parse() {
+    let mut bps = 0.0;
+    let status = unsafe { rate_parse(c"12 MB/s".as_ptr(), &mut bps) };
+    assert_eq!((status, bps), (RATE_OK, 12e6));
+    let status = unsafe { rate_parse(c"12 MB/fortnight".as_ptr(), &mut bps) };
+    assert_eq!(status, RATE_INVALID_PERIOD);
+    let status = unsafe { rate_parse(std::ptr::null(), &mut bps) };
+    assert_eq!(status, RATE_INVALID_ARGUMENT);
+}
+
+#[test]
+fn test_rate_format() {
+    let mut buf = [1 as c_char; 8];
+    let n = unsafe { rate_format(50e6, buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(n, "50.000 MB / sec".len());
+    let s = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    assert_eq!(s.to_str(), Ok("50.000 "));
+    assert_eq!(unsafe { rate_format(50e6, std::ptr::null_mut(), 0) }, n);
+}
+
+#[test]
+fn test_header() {
+    assert!(
+        include_str!(concat!(env!("OUT_DIR"), "/rate.h")) == include_str!("../include/rate.h"),
+        "include/rate.h is out of date: rebuild with RATE_UPDATE_HEADER=1"
+    );
+}
@@ -0,0 +1,72 @@
+//! `rate fill`: when a filesystem fills up at a given ingest rate.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::time::{format_timestamp, now};
+use crate::{
+    format_duration, format_size, parse, parse_input, remaining_args, split_keyword, Error,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " fill <path> at <rate>\n",
+    "       Reports when the filesystem holding <path> runs out of space."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((path, rate)) = split_keyword(&args, "at") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<path> at <rate>"],
+        )));
+    };
+    let rate = parse_input(&args, rate, parse)?;
+    let (free, total) = free_space(path)?;
+    let left = free / rate;
+    let mut text = vec![
+        format!(
+            "free    : {} of {} ({:.1}%)",
+            format_size(free),
+            format_size(total),
+            100.0 * free / total
+        ),
+        format!("full in : {}", format_duration(left)),
+    ];
+    let mut fields = vec![
+        ("free", Value::Size(free)),
+        ("total", Value::Size(total)),
+        ("full_in", Value::Duration(left)),
+    ];
+    let full_at = left.is_finite().then(|| format_timestamp(now() + left));
+    if let Some(full_at) = &full_at {
+        text.push(format!("full at : {} UTC", full_at));
+        fields.push(("full_at", Value::Text(full_at)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Returns the space available to unprivileged users and the total size
+/// of the filesystem holding `path`, in bytes.
+#[cfg(unix)]
+fn free_space(path: &str) -> Result<(f64, f64), Error> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(std::ffi::OsStr::new(path).as_bytes())
+        .map_err(|_| Error::Usage(messages::text(Message::NulByte, &[])))?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut st) } != 0 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    let block = st.f_frsize as f64;
+    return Ok((st.f_bavail as f64 * block, st.f_blocks as f64 * block));
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &str) -> Result<(f64, f64), Error> {
+    return Err(Error::Usage(messages::text(Message::UnixOnly, &[&"fill"])));
+}
@@ -0,0 +1,353 @@
+//! Configurable rendering of rates, for embedders that need other
+//! options than the defaults of `format_table` and `Rate`'s `Display`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{gcd, scale_fraction, to_f64, Period, Rate, PERIODS, UNITS};
+
+/// Whether units go by powers of 1000 (KB, MB, ...) or 1024 (KiB, MiB, ...).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnitSystem {
+    #[default]
+    Decimal,
+    Binary,
+}
+
+/// Formats rates with a chosen precision, unit system, data unit,
+/// decimal separator and set of periods.
+///
+/// ```
+/// use rate::{RateFormatter, UnitSystem, PERIODS};
+///
+/// let f = RateFormatter::new()
+///     .precision(1)
+///     .unit_system(UnitSystem::Binary)
+///     .bits(true)
+///     .decimal_separator(',')
+///     .periods(&PERIODS[..2]);
+/// let rate = rate::parse("1 Gb/s").unwrap();
+/// assert_eq!(f.format(rate), "953,7 Mib / sec");
+/// assert_eq!(f.format_table(rate), " 953,7 Mib / sec\n  55,9 Gib / min\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateFormatter {
+    precision: usize,
+    system: UnitSystem,
+    bits: bool,
+    nice: bool,
+    decimal_separator: char,
+    periods: Vec<Period>,
+}
+
+impl Default for RateFormatter {
+    fn default() -> RateFormatter {
+        return RateFormatter::new();
+    }
+}
+
+impl RateFormatter {
+    /// The formatter of `format_table`: three decimals, metric byte
+    /// units, and every period of the table.
+    pub fn new() -> RateFormatter {
+        return RateFormatter {
+            precision: 3,
+            system: UnitSystem::Decimal,
+            bits: false,
+            nice: false,
+            decimal_separator: '.',
+            periods: PERIODS.to_vec(),
+        };
+    }
+
+    /// The number of decimals shown.
+    pub fn precision(mut self, precision: usize) -> RateFormatter {
+        self.precision = precision;
+        return self;
+    }
+
+    pub fn unit_system(mut self, system: UnitSystem) -> RateFormatter {
+        self.system = system;
+        return self;
+    }
+
+    /// Shows rates in bits (Mb) rather than bytes (MB).
+    pub fn bits(mut self, bits: bool) -> RateFormatter {
+        self.bits = bits;
+        return self;
+    }
+
+    /// Rounds amounts to a multiple of 1, 2, 2.5 or 5 times a power of
+    /// ten, about a tenth of the amount, marked with "≈": 86.4 GB shows as
+    /// "≈ 85 GB". For slides rather than for sizing.
+    pub fn nice(mut self, nice: bool) -> RateFormatter {
+        self.nice = nice;
+        return self;
+    }
+
+    /// The character between the integer part and the decimals, e.g.
+    /// ',' for most European locales.
+    pub fn decimal_separator(mut self, separator: char) -> RateFormatter {
+        self.decimal_separator = separator;
+        return self;
+    }
+
+    /// The periods of the table. `format` uses the first one.
+    pub fn periods(mut self, periods: &[Period]) -> RateFormatter {
+        self.periods = periods.to_vec();
+        return self;
+    }
+
+    /// Formats a rate as a single line, e.g. "50.000 MB / sec".
+    pub fn format(&self, rate: Rate) -> String {
+        let period = self.periods.first().unwrap_or(&PERIODS[0]);
+        let (amount, unit) = self.amount(rate, *period);
+        return format!("{} {} / {}", amount, unit, period.name);
+    }
+
+    /// Formats how much data `rate` amounts to over each period, one
+    /// line per period, with the amounts and units aligned.
+    pub fn format_table(&self, rate: Rate) -> String {
+        // Amounts stay under 1000 (or 1024), so their integer part has
+        // at most 3 (or 4) digits, unless they are past the largest unit
+        // or in scientific notation.
+        let mut width = match self.system {
+            UnitSystem::Decimal => 3,
+            UnitSystem::Binary => 4,
+        };
+        if self.precision > 0 {
+            width += 1 + self.precision;
+        }
+        let amounts: Vec<(String, String)> =
+            self.periods.iter().map(|p| self.amount(rate, *p)).collect();
+        let width = amounts
+            .iter()
+            .map(|(a, _)| a.chars().count())
+            .fold(width, usize::max);
+        let unit_width = self.symbol(1).len();
+        let mut table = String::new();
+        for (period, (amount, unit)) in self.periods.iter().zip(amounts) {
+            table.push_str(&format!(
+                "{:>width$} {:>unit_width$} / {}\n",
+                amount, unit, period.name
+            ));
+        }
+        return table;
+    }
+
+    /// The rows of `format_table` as data: each period with the amount
+    /// over it, in the unit shown, and the symbol of that unit. For
+    /// outputs that render the table themselves, e.g. as JSON.
+    pub fn rows(&self, rate: Rate) -> Vec<(Period, f64, String)> {
+        let mut rows = Vec::new();
+        for period in &self.periods {
+            let (amount, exponent) = match self.scaled(rate, *period) {
+                Some((numerator, denominator, exponent)) => {
+                    (to_f64(numerator, denominator), exponent)
+                }
+                None => (f64::INFINITY, 0),
+            };
+            let amount = match self.nice {
+                true => nice_round(amount).0,
+                false => amount,
+            };
+            rows.push((*period, amount, self.symbol(exponent)));
+        }
+        return rows;
+    }
+
+    /// The amount of data over `period`, formatted, and its unit.
+    fn amount(&self, rate: Rate, period: Period) -> (String, String) {
+        let Some((numerator, denominator, exponent)) = self.scaled(rate, period) else {
+            return (
+                format!("{:.*}", self.precision, f64::INFINITY),
+                self.symbol(0),
+            );
+        };
+        let amount = if self.nice {
+            nice_number(to_f64(numerator, denominator))
+        } else if tiny(numerator, denominator, self.precision) {
+            format!("{:.*e}", self.precision, to_f64(numerator, denominator))
+        } else {
+            format_fraction(numerator, denominator, self.precision)
+        };
+        let amount = match self.decimal_separator {
+            '.' => amount,
+            separator => amount.replacen('.', separator.encode_utf8(&mut [0; 4]), 1),
+        };
+        return (amount, self.symbol(exponent));
+    }
+
+    /// The amount of data over `period` as a fraction in the unit of
+    /// 1000^exponent (or 1024^exponent) bytes or bits, and the exponent.
+    /// None for an infinite rate.
+    fn scaled(&self, rate: Rate, period: Period) -> Option<(u128, u128, usize)> {
+        let (mut numerator, mut denominator) = rate.over(period.seconds);
+        if self.bits {
+            match numerator.checked_mul(8) {
+                Some(bits) => numerator = bits,
+                None => {
+                    let bits = Rate::from_bytes_per_second(rate.bytes_per(period) * 8.0);
+                    (numerator, denominator) = (bits.bytes, bits.seconds);
+                }
+            }
+        }
+        let base = match self.system {
+            UnitSystem::Decimal => 1000,
+            UnitSystem::Binary => 1024,
+        };
+        if denominator == 0 {
+            return None;
+        }
+        let (denominator, exponent) = scale_fraction(numerator, denominator, base);
+        return Some((numerator, denominator, exponent));
+    }
+
+    /// The symbol of the unit of 1000^exponent (or 1024^exponent) bytes
+    /// or bits, e.g. "MB", "Mb", "MiB" or "Mib".
+    fn symbol(&self, exponent: usize) -> String {
+        let symbol = UNITS[exponent].symbol;
+        let prefix = &symbol[..symbol.len() - 1];
+        let binary = if self.system == UnitSystem::Binary && !prefix.is_empty() {
+            "i"
+        } else {
+            ""
+        };
+        let unit = if self.bits { "b" } else { "B" };
+        return format!("{}{}{}", prefix, binary, unit);
+    }
+}
+
+/// An amount rounded to the nearest multiple of the largest step of the
+/// 1, 2, 2.5, 5 series that is at most a tenth of it, e.g. "≈ 85" for
+/// 86.4 (a step of 5) or "≈ 1.4" for 1.44 (a step of 0.1).
+fn nice_number(amount: f64) -> String {
+    if !(amount > 0.0 && amount.is_finite()) {
+        return format!("{}", amount);
+    }
+    let (rounded, decimals) = nice_round(amount);
+    // The trailing zeros go: 2.5 rather than 2.50.
+    let rounded = format!("{:.*}", decimals, rounded);
+    let rounded = match decimals {
+        0 => rounded.as_str(),
+        _ => rounded.trim_end_matches('0').trim_end_matches('.'),
+    };
+    return format!("≈ {}", rounded);
+}
+
+/// The amount of `nice_number`, and the decimals of its step.
+fn nice_round(amount: f64) -> (f64, usize) {
+    if !(amount > 0.0 && amount.is_finite()) {
+        return (amount, 0);
+    }
+    let target = amount / 10.0;
+    let mut power = 1.0;
+    while power * 10.0 <= target {
+        power *= 10.0;
+    }
+    while power > target {
+        power /= 10.0;
+    }
+    let step = [5.0, 2.5, 2.0, 1.0]
+        .iter()
+        .map(|m| m * power)
+        .find(|step| *step <= target)
+        .unwrap_or(power);
+    let rounded = (amount / step + 0.5) as u64 as f64 * step;
+    // The step has as many decimals as it takes to write it.
+    let step = format!("{}", step);
+    let decimals = step.split_once('.').map_or(0, |(_, d)| d.len());
+    return (rounded, decimals);
+}
+
+/// Whether a fraction of a byte (or bit) would show fewer than three
+/// significant digits with `precision` decimals, like 0.001 B for 2 B an
+/// hour over a second. Such amounts are written in scientific notation,
+/// e.g. 5.556e-4 B, which says how small they are.
+fn tiny(numerator: u128, denominator: u128, precision: usize) -> bool {
+    if numerator == 0 || numerator >= denominator {
+        return false;
+    }
+    let scaled = 10u128
+        .checked_pow(precision as u32)
+        .and_then(|p| numerator.checked_mul(p));
+    return match (scaled, denominator.checked_mul(100)) {
+        (Some(scaled), Some(hundred)) => scaled < hundred,
+        // The numerator can't be scaled: it is already large enough.
+        (None, _) => false,
+        (Some(_), None) => true,
+    };
+}
+
+/// Writes `numerator / denominator` with `precision` decimals, rounded
+/// half to even like floats are, from the exact fraction where it fits,
+/// so that 86.4 MB doesn't come out as 86.39999999999999147 MB.
+fn format_fraction(numerator: u128, denominator: u128, precision: usize) -> String {
+    let g = gcd(numerator, denominator).max(1);
+    let (numerator, denominator) = (numerator / g, denominator / g);
+    let scaled = 10u128
+        .checked_pow(precision as u32)
+        .and_then(|p| numerator.checked_mul(p));
+    let Some(scaled) = scaled else {
+        return format!("{:.*}", precision, to_f64(numerator, denominator));
+    };
+    let (mut quotient, remainder) = (scaled / denominator, scaled % denominator);
+    let half = denominator - remainder;
+    if remainder > half || (remainder == half && quotient % 2 == 1) {
+        quotient += 1;
+    }
+    let digits = format!("{:0>width$}", quotient, width = precision + 1);
+    if precision == 0 {
+        return digits;
+    }
+    let (whole, decimals) = digits.split_at(digits.len() - precision);
+    return format!("{}.{}", whole, decimals);
+}
+
+#[test]
+fn test_rate_formatter() {
+    let rate = crate::parse("1 GB / s").unwrap();
+    assert_eq!(RateFormatter::new().format(rate), format!("{}", rate));
+    assert_eq!(
+        RateFormatter::new().format_table(rate),
+        crate::format_table(rate)
+    );
+    let f = RateFormatter::new().precision(0).periods(&PERIODS[..1]);
+    assert_eq!(f.format_table(rate), "  1 GB / sec\n");
+    let f = f.unit_system(UnitSystem::Binary);
+    assert_eq!(f.format_table(rate), " 954 MiB / sec\n");
+    let f = RateFormatter::new().bits(true).periods(&[]);
+    assert_eq!(f.format(rate), "8.000 Gb / sec");
+    assert_eq!(f.format_table(rate), "");
+    let f = RateFormatter::new().nice(true).periods(&PERIODS[1..2]);
+    assert_eq!(f.rows(rate), [(PERIODS[1], 60.0, String::from("GB"))]);
+}
+
+#[test]
+fn test_format_fraction() {
+    assert_eq!(format_fraction(864, 10, 17), "86.40000000000000000");
+    assert_eq!(format_fraction(2, 3, 3), "0.667");
+    assert_eq!(format_fraction(5, 2, 0), "2");
+    assert_eq!(format_fraction(7, 2, 0), "4");
+    assert_eq!(format_fraction(1, 8, 2), "0.12");
+    assert_eq!(format_fraction(1, 1000, 2), "0.00");
+    let f = RateFormatter::new().periods(&PERIODS[..4]);
+    assert_eq!(
+        f.format_table(crate::parse("2 B/hour").unwrap()),
+        "5.556e-4  B / sec\n3.333e-2  B / min\n   2.000  B / hour\n  48.000  B / day\n"
+    );
+    assert_eq!(f.format(crate::parse("0.5 B/s").unwrap()), "0.500 B / sec");
+    assert_eq!(f.format(Rate::new(0, 1)), "0.000 B / sec");
+    let f = RateFormatter::new().nice(true).periods(&PERIODS[..4]);
+    assert_eq!(
+        f.format_table(crate::parse("1 MB/s").unwrap()),
+        "    ≈ 1 MB / sec\n   ≈ 60 MB / min\n  ≈ 3.5 GB / hour\n   ≈ 85 GB / day\n"
+    );
+    assert_eq!(nice_number(604.8), "≈ 600");
+    assert_eq!(nice_number(2.592), "≈ 2.5");
+    assert_eq!(nice_number(0.000555), "≈ 0.00055");
+    let f = RateFormatter::new().precision(17);
+    let rate = crate::parse("1 KB/s").unwrap() * 2.3;
+    assert_eq!(f.format(rate), "2.30000000000000000 KB / sec");
+}
@@ -0,0 +1,313 @@
+//! `rate goodput`: the payload left of a link rate after the headers.
+
+use rate::sink::Value;
+
+use crate::report::output;
+use crate::{format_bitrate, parse, parse_input, parse_size, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " goodput <rate> [--stack <layers>] [--tunnel <name>] [--packets <sizes>]\n",
+    "       Prints the share of a link rate left for the payload once each\n",
+    "       packet pays for its headers. --stack is a preset (tcp4 by\n",
+    "       default) or layers joined with +, e.g. ethernet+vlan+ipv6+tcp.\n",
+    "       --tunnel carries it in wireguard, openvpn-udp, openvpn-tcp or\n",
+    "       ipsec, over IPv4.\n",
+    "       --packets are the sizes of the IP packets: 1500 by default,\n",
+    "       \"imix\", or sizes with weights such as 64:7,576:4,1500:1.\n",
+    "       Run with \"list\" to see the layers and the presets."
+);
+
+struct Layer {
+    name: &'static str,
+    /// The bytes that the layer adds to each packet.
+    bytes: f64,
+    /// Whether the layer is below IP, outside of the packet size.
+    link: bool,
+}
+
+const LAYERS: &[Layer] = &[
+    // Header, FCS, preamble, start delimiter and inter-frame gap.
+    Layer {
+        name: "ethernet",
+        bytes: 38.0,
+        link: true,
+    },
+    Layer {
+        name: "vlan",
+        bytes: 4.0,
+        link: true,
+    },
+    // An Ethernet header carried inside a tunnel.
+    Layer {
+        name: "mac",
+        bytes: 14.0,
+        link: false,
+    },
+    Layer {
+        name: "ipv4",
+        bytes: 20.0,
+        link: false,
+    },
+    Layer {
+        name: "ipv6",
+        bytes: 40.0,
+        link: false,
+    },
+    Layer {
+        name: "tcp",
+        bytes: 20.0,
+        link: false,
+    },
+    Layer {
+        name: "udp",
+        bytes: 8.0,
+        link: false,
+    },
+    Layer {
+        name: "gre",
+        bytes: 4.0,
+        link: false,
+    },
+    Layer {
+        name: "vxlan",
+        bytes: 8.0,
+        link: false,
+    },
+    // Type, receiver index and counter, and the authentication tag.
+    Layer {
+        name: "wireguard",
+        bytes: 32.0,
+        link: false,
+    },
+    // The data channel with AES-GCM: opcode, peer ID, packet ID and tag.
+    Layer {
+        name: "openvpn",
+        bytes: 24.0,
+        link: false,
+    },
+    // The same, with the length of each packet in the TCP stream.
+    Layer {
+        name: "openvpn-tcp",
+        bytes: 26.0,
+        link: false,
+    },
+    // Tunnel mode with AES-GCM: SPI, sequence number, IV, the trailer
+    // without its padding, and the ICV.
+    Layer {
+        name: "esp",
+        bytes: 34.0,
+        link: false,
+    },
+];
+
+/// The stacks that have a name.
+const STACKS: &[(&str, &str)] = &[
+    ("tcp4", "ethernet+ipv4+tcp"),
+    ("tcp6", "ethernet+ipv6+tcp"),
+    ("udp4", "ethernet+ipv4+udp"),
+    ("udp6", "ethernet+ipv6+udp"),
+    ("vlan-tcp6", "ethernet+vlan+ipv6+tcp"),
+    ("gre", "ethernet+ipv4+gre+ipv4+tcp"),
+    ("vxlan", "ethernet+ipv4+udp+vxlan+mac+ipv4+tcp"),
+];
+
+/// The layers that each tunnel adds around the packets it carries.
+const TUNNELS: &[(&str, &str)] = &[
+    ("wireguard", "ipv4+udp+wireguard"),
+    ("openvpn-udp", "ipv4+udp+openvpn"),
+    ("openvpn-tcp", "ipv4+tcp+openvpn-tcp"),
+    ("ipsec", "ipv4+esp"),
+];
+
+/// The simple IMIX, in sizes of IP packets and their weights.
+const IMIX: &[(f64, f64)] = &[(40.0, 7.0), (576.0, 4.0), (1500.0, 1.0)];
+
+/// The smallest Ethernet frame, from the addresses to the FCS.
+const MIN_FRAME: f64 = 64.0;
+
+/// The bytes of Ethernet on the wire that are not in the frame.
+const PREAMBLE_AND_GAP: f64 = 20.0;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let stack: String = pargs
+        .opt_value_from_str("--stack")?
+        .unwrap_or_else(|| String::from("tcp4"));
+    let tunnel: Option<String> = pargs.opt_value_from_str("--tunnel")?;
+    let packets: String = pargs
+        .opt_value_from_str("--packets")?
+        .unwrap_or_else(|| String::from("1500"));
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    if args == "list" {
+        let layers: Vec<&str> = LAYERS.iter().map(|l| l.name).collect();
+        let layers = layers.join(" ");
+        out.line(
+            &format!("layers      : {}", layers),
+            &[
+                ("name", Value::Text("layers")),
+                ("kind", Value::Text("layers")),
+                ("layers", Value::Text(&layers)),
+            ],
+        )?;
+        for (name, layers) in STACKS {
+            out.line(
+                &format!("{:<11} : {}", name, layers),
+                &[
+                    ("name", Value::Text(name)),
+                    ("kind", Value::Text("stack")),
+                    ("layers", Value::Text(layers)),
+                ],
+            )?;
+        }
+        for (name, layers) in TUNNELS {
+            out.line(
+                &format!("{:<11} : tunnel of {}", name, layers),
+                &[
+                    ("name", Value::Text(name)),
+                    ("kind", Value::Text("tunnel")),
+                    ("layers", Value::Text(layers)),
+                ],
+            )?;
+        }
+        return Ok(());
+    }
+    let rate = parse_input(&args, &args, parse)?;
+    let mut layers = stack_layers(&stack).map_err(Error::Usage)?;
+    if let Some(tunnel) = tunnel {
+        let outer = tunnel_layers(&tunnel).map_err(Error::Usage)?;
+        let link = layers.iter().take_while(|l| l.link).count();
+        layers.splice(link..link, outer);
+    }
+    let mix = packet_mix(&packets).map_err(Error::Usage)?;
+
+    let headers: f64 = layers.iter().map(|l| l.bytes).sum();
+    let efficiency = efficiency(&layers, &mix);
+    let names: Vec<&str> = layers.iter().map(|l| l.name).collect();
+    let stack = names.join("+");
+    let text = [
+        format!("stack      : {} ({} B per packet)", stack, headers),
+        format!("packets    : {}", packets),
+        format!("efficiency : {:.1}%", efficiency * 100.0),
+        format!("goodput    : {}", format_bitrate(rate * efficiency)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("stack", Value::Text(&stack)),
+            ("headers", Value::Size(headers)),
+            ("packets", Value::Text(&packets)),
+            ("efficiency", Value::Number(efficiency)),
+            (
+                "goodput",
+                Value::Bitrate(Rate::from_decimal(rate * efficiency)),
+            ),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// The layers of a preset or of a list such as "ethernet+ipv6+tcp".
+fn stack_layers(stack: &str) -> Result<Vec<&'static Layer>, String> {
+    let stack = stack.trim().to_ascii_lowercase();
+    let list = match STACKS.iter().find(|(name, _)| *name == stack) {
+        Some((_, layers)) => layers,
+        None => stack.as_str(),
+    };
+    let mut layers = Vec::new();
+    for name in list.split('+') {
+        match LAYERS.iter().find(|l| l.name == name.trim()) {
+            Some(layer) => layers.push(layer),
+            None => {
+                return Err(format!(
+                    "unknown layer {:?} (see \"goodput list\")",
+                    name.trim()
+                ))
+            }
+        }
+    }
+    return Ok(layers);
+}
+
+/// The layers of a tunnel, e.g. "wireguard".
+fn tunnel_layers(tunnel: &str) -> Result<Vec<&'static Layer>, String> {
+    let tunnel = tunnel.trim().to_ascii_lowercase();
+    match TUNNELS.iter().find(|(name, _)| *name == tunnel) {
+        Some((_, layers)) => return stack_layers(layers),
+        None => {
+            let names: Vec<&str> = TUNNELS.iter().map(|(name, _)| *name).collect();
+            return Err(format!("unknown tunnel {:?} ({})", tunnel, names.join(" ")));
+        }
+    }
+}
+
+/// Parses "imix" or sizes with optional weights, e.g. "64:7,1500:1".
+fn packet_mix(s: &str) -> Result<Vec<(f64, f64)>, String> {
+    if s.trim().eq_ignore_ascii_case("imix") {
+        return Ok(IMIX.to_vec());
+    }
+    let mut mix = Vec::new();
+    for item in s.split(',') {
+        let (size, weight) = item.split_once(':').unwrap_or((item, "1"));
+        let size = match size.trim().parse::<f64>() {
+            Ok(size) => size,
+            Err(_) => parse_size(size).map_err(|e| format!("{}: {:?}", e, size.trim()))?,
+        };
+        let weight: f64 = match weight.trim().parse() {
+            Ok(weight) if weight > 0.0 => weight,
+            _ => return Err(format!("invalid weight {:?}", weight.trim())),
+        };
+        mix.push((size, weight));
+    }
+    return Ok(mix);
+}
+
+/// The share of the bytes on the wire that is payload, over the packets
+/// of the mix. A packet smaller than the headers is taken as the headers
+/// alone, like a bare ACK, and Ethernet frames too short are padded to
+/// `MIN_FRAME`.
+fn efficiency(layers: &[&Layer], mix: &[(f64, f64)]) -> f64 {
+    let packet_headers: f64 = layers.iter().filter(|l| !l.link).map(|l| l.bytes).sum();
+    let link_headers: f64 = layers.iter().filter(|l| l.link).map(|l| l.bytes).sum();
+    let ethernet = layers.iter().any(|l| l.name == "ethernet");
+    let (mut payload, mut wire) = (0.0, 0.0);
+    for (size, weight) in mix {
+        let size = size.max(packet_headers);
+        let mut bytes = size + link_headers;
+        if ethernet {
+            bytes = bytes.max(MIN_FRAME + PREAMBLE_AND_GAP);
+        }
+        payload += weight * (size - packet_headers);
+        wire += weight * bytes;
+    }
+    return payload / wire;
+}
+
+#[test]
+fn test_efficiency() {
+    let tcp4 = stack_layers("tcp4").unwrap();
+    // 1460 bytes of payload in 1538 on the wire.
+    assert_eq!(efficiency(&tcp4, &[(1500.0, 1.0)]), 1460.0 / 1538.0);
+    // A 40-byte packet is padded to the smallest frame.
+    let imix = packet_mix("imix").unwrap();
+    let expected = (4.0 * 536.0 + 1460.0) / (7.0 * 84.0 + 4.0 * 614.0 + 1538.0);
+    assert_eq!(efficiency(&tcp4, &imix), expected);
+    let vxlan = stack_layers("vxlan").unwrap();
+    assert_eq!(efficiency(&vxlan, &[(1500.0, 1.0)]), 1410.0 / 1538.0);
+    // Too small for the headers of a tunnel: headers only.
+    assert_eq!(
+        efficiency(&vxlan, &[(40.0, 1.0), (1500.0, 1.0)]),
+        1410.0 / 1666.0
+    );
+    assert!(stack_layers("ethernet+ipx").is_err());
+    // WireGuard takes 60 bytes of a 1500-byte packet.
+    let mut wireguard = stack_layers("tcp4").unwrap();
+    wireguard.splice(1..1, tunnel_layers("WireGuard").unwrap());
+    assert_eq!(efficiency(&wireguard, &[(1500.0, 1.0)]), 1400.0 / 1538.0);
+    assert!(tunnel_layers("pptp").is_err());
+    assert_eq!(
+        packet_mix("64:7, 1500"),
+        Ok(vec![(64.0, 7.0), (1500.0, 1.0)])
+    );
+}
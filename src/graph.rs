@@ -0,0 +1,116 @@
+//! `rate graph`: an ASCII chart of the data accumulated at a given rate.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_size, parse, parse_input, parse_size, remaining_args, Error, DAY};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " graph <rate> [--days <n>] [--cap <size>]... [--height <rows>]\n",
+    "       Charts the cumulative data transferred over <n> days (default 30),\n",
+    "       with a horizontal line for each --cap (e.g., a quota)."
+);
+
+const MAX_WIDTH: usize = 60;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let days: f64 = pargs.opt_value_from_str("--days")?.unwrap_or(30.0);
+    let height: usize = pargs.opt_value_from_str("--height")?.unwrap_or(12);
+    let caps: Vec<f64> = pargs.values_from_fn("--cap", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let rate = parse_input(&args, &args, parse)?;
+    if !(days.is_finite() && days > 0.0) || height == 0 {
+        return Err(Error::Usage(messages::text(
+            Message::DaysHeightPositive,
+            &[],
+        )));
+    }
+    for line in render(rate, days, &caps, height) {
+        out.line(&line, &[])?;
+    }
+    for cap in &caps {
+        let reached = cap / (rate * DAY as f64);
+        if reached <= days {
+            let text = format!("cap {} reached on day {:.1}", format_size(*cap), reached);
+            out.line(
+                &text,
+                &[("cap", Value::Size(*cap)), ("day", Value::Number(reached))],
+            )?;
+        } else {
+            let text = format!("cap {} not reached", format_size(*cap));
+            out.line(
+                &text,
+                &[("cap", Value::Size(*cap)), ("day", Value::Number(f64::NAN))],
+            )?;
+        }
+    }
+    return Ok(());
+}
+
+fn render(rate: f64, days: f64, caps: &[f64], height: usize) -> Vec<String> {
+    let width = (days.ceil() as usize).clamp(1, MAX_WIDTH);
+    let total = rate * days * DAY as f64;
+    let top = caps.iter().copied().fold(total, f64::max);
+    let step = top / height as f64;
+    let labels: Vec<String> = (0..=height)
+        .map(|r| format_size(top - r as f64 * step))
+        .collect();
+    let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (row, label) in labels.iter().enumerate().take(height) {
+        // Each row stands for the value on its label, give or take half
+        // a step, so that caps land on the row closest to them.
+        let mid = top - (row as f64 + 0.5) * step;
+        let cap_here = caps.iter().any(|&c| c > mid && c <= mid + step);
+        let mut line = format!("{:>w$} |", label, w = label_width);
+        for col in 0..width {
+            let amount = total * (col + 1) as f64 / width as f64;
+            if amount >= mid {
+                line.push('#');
+            } else if cap_here {
+                line.push('-');
+            } else {
+                line.push(' ');
+            }
+        }
+        lines.push(line);
+    }
+    lines.push(format!(
+        "{:>w$} +{}",
+        labels[height],
+        "-".repeat(width),
+        w = label_width
+    ));
+    // The last day ends under the last column, or a space after day 0
+    // when the chart is too narrow for both.
+    let last_day = format!("day {}", days);
+    lines.push(format!(
+        "{:>w$}  day 0{:>pad$}",
+        "",
+        last_day,
+        w = label_width,
+        pad = width.saturating_sub(5).max(last_day.len() + 1)
+    ));
+    return lines;
+}
+
+#[test]
+fn test_render() {
+    let rate = 1e6 / DAY as f64; // 1 MB per day
+    let lines = render(rate, 4.0, &[], 4);
+    assert_eq!(lines[0], "4.000 MB |   #");
+    assert_eq!(lines[3], "1.000 MB |####");
+    assert_eq!(lines[4], " 0.000 B +----");
+    assert_eq!(lines[5], "          day 0 day 4");
+    let lines = render(rate, 30.0, &[], 4);
+    assert_eq!(lines[5], format!("{:11}day 0{:>25}", "", "day 30"));
+
+    let lines = render(rate, 4.0, &[8e6], 4);
+    assert_eq!(lines[0], "8.000 MB |----");
+    assert_eq!(lines[2], "4.000 MB |  ##");
+}
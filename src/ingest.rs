@@ -0,0 +1,71 @@
+//! `rate ingest`: sizing a log or event pipeline.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_count, format_rate, format_size, month, parse_frequency, parse_input, parse_size,
+    remaining_args, split_keyword, Error, Rate, DAY,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " ingest <events>/<period> @ <size> [--index-factor <x>] [--replicas <n>]\n",
+    "       e.g. ingest 40k/s @ 1.2KB\n",
+    "       Prints the byte rate of the events, then the storage they take\n",
+    "       once indexed (--index-factor, 1 by default: the on-disk size per\n",
+    "       raw byte) and replicated (--replicas extra copies, 0 by default)."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let index_factor: f64 = pargs.opt_value_from_str("--index-factor")?.unwrap_or(1.0);
+    let replicas: f64 = pargs.opt_value_from_str("--replicas")?.unwrap_or(0.0);
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((events, size)) = split_keyword(&args, "@").or(split_keyword(&args, "at")) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<events>/<period> @ <size>"],
+        )));
+    };
+    let events = parse_input(&args, events, parse_frequency)?;
+    let size = parse_input(&args, size, parse_size)?;
+
+    let raw = events * size;
+    let stored = raw * index_factor * (1.0 + replicas);
+    let text = format!(
+        "events    : {}/sec\nraw       : {}",
+        format_count(events),
+        format_rate(raw)
+    );
+    let raw = Rate::from_decimal(raw);
+    out.line(
+        &text,
+        &[("events", Value::Number(events)), ("raw", Value::Rate(raw))],
+    )?;
+    out.table(raw)?;
+    out.line("", &[])?;
+    let text = [
+        format!(
+            "stored    : {} ({}x index, {} replicas)",
+            format_rate(stored),
+            index_factor,
+            replicas
+        ),
+        format!("per day   : {}", format_size(stored * DAY as f64)),
+        format!("per month : {}", format_size(stored * month() as f64)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("stored", Value::Rate(Rate::from_decimal(stored))),
+            ("index_factor", Value::Number(index_factor)),
+            ("replicas", Value::Number(replicas)),
+            ("per_day", Value::Size(stored * DAY as f64)),
+            ("per_month", Value::Size(stored * month() as f64)),
+        ],
+    )?;
+    return Ok(());
+}
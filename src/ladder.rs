@@ -0,0 +1,110 @@
+//! `rate ladder`: bandwidth of an adaptive bitrate (ABR) ladder.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, parse, parse_percent, parse_quantity, Error, ParseError, ParseErrorKind, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " ladder [<name>=]<rate>:<share>... [--viewers <n>]\n",
+    "       e.g. ladder 1080p=6Mb/s:40% 720p=3Mb/s:35% 480p=1.5Mb/s:25%\n",
+    "       Prints the blended bitrate of the renditions weighted by their\n",
+    "       share of viewers, and the total egress for --viewers."
+);
+
+struct Rendition {
+    name: String,
+    rate: f64,
+    share: f64,
+}
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let viewers: Option<f64> = pargs.opt_value_from_fn("--viewers", parse_quantity)?;
+    let mut out = output(&mut pargs)?;
+    let mut renditions = Vec::new();
+    for (i, arg) in pargs.finish().iter().enumerate() {
+        let arg = arg.to_string_lossy();
+        renditions.push(
+            parse_rendition(&arg, i + 1).map_err(|e| Error::Line(i + 1, arg.to_string(), e))?,
+        );
+    }
+    if renditions.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NoRenditions, &[])));
+    }
+    let total_share: f64 = renditions.iter().map(|r| r.share).sum();
+    if total_share <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::SharesZero, &[])));
+    }
+    if (total_share - 1.0).abs() > 1e-6 {
+        let total = format!("{:.1}", total_share * 100.0);
+        eprintln!("{}", messages::text(Message::SharesScaled, &[&total]));
+    }
+
+    let width = renditions.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let mut blended = 0.0;
+    for r in &renditions {
+        let share = r.share / total_share;
+        blended += r.rate * share;
+        let text = format!(
+            "{:<w$} : {} x {:.1}%",
+            r.name,
+            format_bitrate(r.rate),
+            share * 100.0,
+            w = width
+        );
+        out.line(
+            &text,
+            &[
+                ("rendition", Value::Text(&r.name)),
+                ("rate", Value::Bitrate(Rate::from_decimal(r.rate))),
+                ("share", Value::Number(share)),
+            ],
+        )?;
+    }
+    out.line("", &[])?;
+    out.line(
+        &format!("blended : {}", format_bitrate(blended)),
+        &[("blended", Value::Bitrate(Rate::from_decimal(blended)))],
+    )?;
+    if let Some(viewers) = viewers {
+        let egress = Rate::from_decimal(blended * viewers);
+        out.line(
+            &format!("egress  : {}", format_bitrate(blended * viewers)),
+            &[("egress", Value::Bitrate(egress))],
+        )?;
+        out.table(egress)?;
+    }
+    return Ok(());
+}
+
+/// Parses "[<name>=]<rate>:<share>", naming unnamed renditions "#<n>".
+fn parse_rendition(s: &str, n: usize) -> Result<Rendition, ParseError> {
+    let (name, rest) = match s.split_once('=') {
+        Some((name, rest)) => (name.to_string(), rest),
+        None => (format!("#{}", n), s),
+    };
+    let (rate, share) = rest.rsplit_once(':').ok_or(ParseErrorKind::InvalidNumber)?;
+    // Errors point into `s`, the whole rendition.
+    let offset = |part: &str| part.as_ptr() as usize - s.as_ptr() as usize;
+    return Ok(Rendition {
+        name,
+        rate: parse(rate).map_err(|e| e.shift(offset(rate)))?,
+        share: parse_percent(share).map_err(|e| e.shift(offset(share)))?,
+    });
+}
+
+#[test]
+fn test_parse_rendition() {
+    let r = parse_rendition("1080p=6Mb/s:40%", 1).unwrap();
+    assert_eq!((r.name.as_str(), r.rate, r.share), ("1080p", 750e3, 0.4));
+    let r = parse_rendition("3 Mb/s:0.35", 2).unwrap();
+    assert_eq!((r.name.as_str(), r.rate, r.share), ("#2", 375e3, 0.35));
+    assert!(parse_rendition("1080p=6Mb/s", 1).is_err());
+    let e = parse_rendition("1080p=6Mbs/s:40%", 1).err().unwrap();
+    assert_eq!(e.span, Some(7..10));
+}
@@ -0,0 +1,1958 @@
+//! Parsing and formatting of data rates such as "12 MB/s".
+//!
+//! A rate is an amount of data (with a metric byte or bit unit) over a
+//! time period. This crate parses such expressions and converts them to
+//! any other unit and period, as the `rate` command-line tool does.
+//!
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! let rate = rate::parse("1 TB / day").unwrap();
+//! assert_eq!(rate.to_string(), "11.574 MB / sec");
+//! print!("{}", rate::format_table(rate));
+//! # }
+//! ```
+//!
+//! The parser and the unit math only need `core`: without the default
+//! `std` feature the crate is `no_std`, and the `alloc` feature brings
+//! back the functions that return a `String`.
+//!
+//! With the `serde` feature, [`Rate`], [`Unit`] and [`Period`] can be
+//! (de)serialized as strings such as "12 MB/s". With the `wasm-bindgen`
+//! feature, [`wasm`] exposes the parser and the table to JavaScript, and
+//! with the `cdylib` feature, [`ffi`] exposes them to C. The `python`
+//! feature builds a Python extension module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::needless_return)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod estimator;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+#[cfg(feature = "alloc")]
+mod formatter;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "std")]
+pub mod sink;
+#[cfg(feature = "std")]
+mod token_bucket;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+use core::ops::Range;
+use core::time::Duration;
+#[cfg(feature = "std")]
+pub use estimator::RateEstimator;
+#[cfg(feature = "alloc")]
+pub use formatter::{RateFormatter, UnitSystem};
+#[cfg(feature = "std")]
+pub use token_bucket::TokenBucket;
+
+pub const SECOND: u64 = 1;
+pub const MINUTE: u64 = 60 * SECOND;
+pub const HOUR: u64 = 60 * MINUTE;
+pub const DAY: u64 = 24 * HOUR;
+pub const WEEK: u64 = 7 * DAY;
+/// A month is 30 days.
+pub const MONTH: u64 = 30 * DAY;
+/// A year is 365 days.
+pub const YEAR: u64 = 365 * DAY;
+/// A work day is 8 hours, by default.
+pub const WORKDAY: u64 = 8 * HOUR;
+/// A work week is 5 work days, by default.
+pub const WORKWEEK: u64 = 5 * WORKDAY;
+
+/// A data unit, in powers of 1000 bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub bytes: u128,
+}
+
+/// Every unit, from the smallest to the largest, which is that of the
+/// quetta- prefix of 2022. Larger amounts are shown in it.
+#[rustfmt::skip]
+pub const UNITS: &[Unit] = &[
+    Unit { symbol: "B", bytes: 1 },
+    Unit { symbol: "KB", bytes: 1000 },
+    Unit { symbol: "MB", bytes: 1000u128.pow(2) },
+    Unit { symbol: "GB", bytes: 1000u128.pow(3) },
+    Unit { symbol: "TB", bytes: 1000u128.pow(4) },
+    Unit { symbol: "PB", bytes: 1000u128.pow(5) },
+    Unit { symbol: "EB", bytes: 1000u128.pow(6) },
+    Unit { symbol: "ZB", bytes: 1000u128.pow(7) },
+    Unit { symbol: "YB", bytes: 1000u128.pow(8) },
+    Unit { symbol: "RB", bytes: 1000u128.pow(9) },
+    Unit { symbol: "QB", bytes: 1000u128.pow(10) },
+];
+
+/// A time period over which a rate is expressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Period {
+    pub name: &'static str,
+    pub seconds: u64,
+}
+
+/// The periods of the conversion table, from the shortest to the longest.
+#[rustfmt::skip]
+pub const PERIODS: &[Period] = &[
+    Period { name: "sec", seconds: SECOND },
+    Period { name: "min", seconds: MINUTE },
+    Period { name: "hour", seconds: HOUR },
+    Period { name: "day", seconds: DAY },
+    Period { name: "week", seconds: WEEK },
+    Period { name: "month", seconds: MONTH },
+    Period { name: "year", seconds: YEAR },
+];
+
+/// The periods of office hours, with their default lengths. They are not
+/// in the table unless asked for, and a `Registry` can change their
+/// lengths.
+#[rustfmt::skip]
+pub const BUSINESS_PERIODS: &[Period] = &[
+    Period { name: "workday", seconds: WORKDAY },
+    Period { name: "workweek", seconds: WORKWEEK },
+];
+
+/// A data rate, kept as the exact fraction of `bytes` transferred in
+/// `seconds`, so that conversions to other periods don't accumulate
+/// floating-point error. It is only rounded to a float for display, or
+/// when asked for one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rate {
+    bytes: u128,
+    seconds: u128,
+}
+
+impl Rate {
+    /// The rate of `bytes` transferred in `seconds`. Zero seconds make
+    /// an infinite rate.
+    pub fn new(bytes: u128, seconds: u128) -> Rate {
+        let g = gcd(bytes, seconds);
+        if g <= 1 {
+            return Rate { bytes, seconds };
+        }
+        return Rate {
+            bytes: bytes / g,
+            seconds: seconds / g,
+        };
+    }
+
+    /// Converts a float exactly: every finite float is a fraction with a
+    /// power of two as denominator. Negative rates are clamped to zero,
+    /// and rates beyond 2^127 bytes per second are infinite.
+    pub fn from_bytes_per_second(bytes_per_second: f64) -> Rate {
+        if bytes_per_second.is_nan() {
+            return Rate {
+                bytes: 0,
+                seconds: 0,
+            };
+        }
+        if bytes_per_second <= 0.0 {
+            return Rate::new(0, 1);
+        }
+        let bits = bytes_per_second.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = (bits & ((1 << 52) - 1)) as u128;
+        let (mut mantissa, mut exponent) = match exponent {
+            0x7ff => return Rate::new(1, 0),
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), exponent - 1075),
+        };
+        if exponent >= 0 {
+            if exponent > 74 {
+                return Rate::new(1, 0);
+            }
+            return Rate::new(mantissa << exponent, 1);
+        }
+        let zeros = mantissa.trailing_zeros().min(exponent.unsigned_abs());
+        mantissa >>= zeros;
+        exponent += zeros as i32;
+        if exponent < -127 {
+            // Tinier than 2^-127 bytes per second: keep what fits.
+            mantissa = mantissa.checked_shr((-127 - exponent) as u32).unwrap_or(0);
+            exponent = -127;
+        }
+        return Rate::new(mantissa, 1 << exponent.unsigned_abs());
+    }
+
+    /// Converts a float through the shortest decimal that reads back as
+    /// it, e.g. 0.1 as 1/10 rather than the binary fraction nearest to
+    /// it, so that round figures make round tables. Floats whose decimal
+    /// does not fit are converted exactly, as by `from_bytes_per_second`.
+    pub fn from_decimal(bytes_per_second: f64) -> Rate {
+        if bytes_per_second.is_finite() && bytes_per_second > 0.0 {
+            let mut buf = DecimalBuf::default();
+            if fmt::Write::write_fmt(&mut buf, format_args!("{:e}", bytes_per_second)).is_ok() {
+                if let Some((numerator, denominator)) = decimal_fraction(buf.as_str()) {
+                    return Rate::new(numerator, denominator);
+                }
+            }
+        }
+        return Rate::from_bytes_per_second(bytes_per_second);
+    }
+
+    pub fn bytes_per_second(self) -> f64 {
+        return to_f64(self.bytes, self.seconds);
+    }
+
+    pub fn bits_per_second(self) -> f64 {
+        return to_f64(self.bytes, self.seconds) * 8.0;
+    }
+
+    /// The number of bytes transferred at this rate over `period`.
+    pub fn bytes_per(self, period: Period) -> f64 {
+        let (bytes, seconds) = self.over(period.seconds);
+        return to_f64(bytes, seconds);
+    }
+
+    /// The bytes transferred over `period` seconds, as a fraction.
+    fn over(self, period: u64) -> (u128, u128) {
+        let g = gcd(period as u128, self.seconds).max(1);
+        match self.bytes.checked_mul(period as u128 / g) {
+            Some(bytes) => return (bytes, self.seconds / g),
+            None => {
+                let bytes = to_f64(self.bytes, self.seconds) * period as f64;
+                let rate = Rate::from_bytes_per_second(bytes);
+                return (rate.bytes, rate.seconds);
+            }
+        }
+    }
+}
+
+impl core::ops::Add for Rate {
+    type Output = Rate;
+
+    fn add(self, other: Rate) -> Rate {
+        let bytes = (self.bytes.checked_mul(other.seconds))
+            .zip(other.bytes.checked_mul(self.seconds))
+            .and_then(|(a, b)| a.checked_add(b));
+        match (bytes, self.seconds.checked_mul(other.seconds)) {
+            (Some(bytes), Some(seconds)) if seconds != 0 => return Rate::new(bytes, seconds),
+            _ => {
+                return Rate::from_bytes_per_second(
+                    self.bytes_per_second() + other.bytes_per_second(),
+                )
+            }
+        }
+    }
+}
+
+/// Subtracting a larger rate gives zero: rates are never negative.
+impl core::ops::Sub for Rate {
+    type Output = Rate;
+
+    fn sub(self, other: Rate) -> Rate {
+        if self <= other {
+            return Rate::new(0, 1);
+        }
+        let bytes = (self.bytes.checked_mul(other.seconds))
+            .zip(other.bytes.checked_mul(self.seconds))
+            .map(|(a, b)| a - b);
+        match (bytes, self.seconds.checked_mul(other.seconds)) {
+            (Some(bytes), Some(seconds)) if seconds != 0 => return Rate::new(bytes, seconds),
+            _ => {
+                return Rate::from_bytes_per_second(
+                    self.bytes_per_second() - other.bytes_per_second(),
+                )
+            }
+        }
+    }
+}
+
+/// Scales a rate, e.g. by a compression ratio. The factor is taken
+/// exactly; negative factors give zero.
+impl core::ops::Mul<f64> for Rate {
+    type Output = Rate;
+
+    fn mul(self, factor: f64) -> Rate {
+        let factor = Rate::from_decimal(factor);
+        let bytes = self.bytes.checked_mul(factor.bytes);
+        match (bytes, self.seconds.checked_mul(factor.seconds)) {
+            (Some(bytes), Some(seconds)) => return Rate::new(bytes, seconds),
+            _ => {
+                return Rate::from_bytes_per_second(
+                    self.bytes_per_second() * factor.bytes_per_second(),
+                )
+            }
+        }
+    }
+}
+
+impl core::ops::Div<f64> for Rate {
+    type Output = Rate;
+
+    fn div(self, divisor: f64) -> Rate {
+        let divisor = Rate::from_decimal(divisor);
+        let bytes = self.bytes.checked_mul(divisor.seconds);
+        match (bytes, self.seconds.checked_mul(divisor.bytes)) {
+            (Some(bytes), Some(seconds)) => return Rate::new(bytes, seconds),
+            _ => {
+                return Rate::from_bytes_per_second(
+                    self.bytes_per_second() / divisor.bytes_per_second(),
+                )
+            }
+        }
+    }
+}
+
+/// The ratio of two rates, e.g. the utilization of a link.
+impl core::ops::Div for Rate {
+    type Output = f64;
+
+    fn div(self, other: Rate) -> f64 {
+        match (
+            self.bytes.checked_mul(other.seconds),
+            other.bytes.checked_mul(self.seconds),
+        ) {
+            (Some(a), Some(b)) => return to_f64(a, b),
+            _ => return self.bytes_per_second() / other.bytes_per_second(),
+        }
+    }
+}
+
+impl core::ops::AddAssign for Rate {
+    fn add_assign(&mut self, other: Rate) {
+        *self = *self + other;
+    }
+}
+
+impl core::ops::SubAssign for Rate {
+    fn sub_assign(&mut self, other: Rate) {
+        *self = *self - other;
+    }
+}
+
+impl core::iter::Sum for Rate {
+    fn sum<I: Iterator<Item = Rate>>(iter: I) -> Rate {
+        return iter.fold(Rate::new(0, 1), |a, b| a + b);
+    }
+}
+
+impl PartialOrd for Rate {
+    fn partial_cmp(&self, other: &Rate) -> Option<core::cmp::Ordering> {
+        let left = self.bytes.checked_mul(other.seconds);
+        let right = other.bytes.checked_mul(self.seconds);
+        match (left, right) {
+            (Some(l), Some(r)) if self.seconds != 0 || other.seconds != 0 => {
+                return Some(l.cmp(&r))
+            }
+            _ => {
+                return self
+                    .bytes_per_second()
+                    .partial_cmp(&other.bytes_per_second())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rate, unit) = nearest_power_of_1000_exact(self.bytes, self.seconds);
+        return write!(f, "{:.3} {} / {}", rate, unit, PERIODS[0].name);
+    }
+}
+
+/// An amount of data, in whole bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DataSize {
+    bytes: u128,
+}
+
+impl DataSize {
+    pub const fn from_bytes(bytes: u128) -> DataSize {
+        return DataSize { bytes };
+    }
+
+    pub const fn bytes(self) -> u128 {
+        return self.bytes;
+    }
+
+    pub fn bits(self) -> u128 {
+        return self.bytes.saturating_mul(8);
+    }
+}
+
+impl core::ops::Add for DataSize {
+    type Output = DataSize;
+
+    fn add(self, other: DataSize) -> DataSize {
+        return DataSize::from_bytes(self.bytes.saturating_add(other.bytes));
+    }
+}
+
+/// Subtracting a larger size gives zero.
+impl core::ops::Sub for DataSize {
+    type Output = DataSize;
+
+    fn sub(self, other: DataSize) -> DataSize {
+        return DataSize::from_bytes(self.bytes.saturating_sub(other.bytes));
+    }
+}
+
+impl core::iter::Sum for DataSize {
+    fn sum<I: Iterator<Item = DataSize>>(iter: I) -> DataSize {
+        return iter.fold(DataSize::default(), |a, b| a + b);
+    }
+}
+
+impl fmt::Display for DataSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (size, unit) = nearest_power_of_1000_exact(self.bytes, 1);
+        return write!(f, "{:.3} {}", size, unit);
+    }
+}
+
+impl core::str::FromStr for Rate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Rate, ParseError> {
+        return parse(s);
+    }
+}
+
+/// Parses a size like "800GB" or "1.5 kB". Sizes in bits are rounded
+/// down to a whole byte.
+impl core::str::FromStr for DataSize {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<DataSize, ParseError> {
+        return with_registry(|registry| registry.parse_data_size(s));
+    }
+}
+
+/// Parses any spelling of a period of the table, e.g. "hr" or "Week".
+impl core::str::FromStr for Period {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Period, ParseError> {
+        let name = s.trim();
+        let offset = s.len() - s.trim_start().len();
+        let seconds = period_to_seconds(name).map_err(|e| e.shift(offset))?;
+        let business = with_registry(|registry| registry.business_period(name));
+        if let Some(period) = business {
+            return Ok(period);
+        }
+        let month = PERIOD_SPELLINGS
+            .iter()
+            .any(|(spelling, seconds)| *seconds == MONTH && spelling.eq_ignore_ascii_case(name));
+        if month {
+            return Ok(Period {
+                name: "month",
+                seconds,
+            });
+        }
+        match PERIODS.iter().find(|p| p.seconds == seconds) {
+            Some(period) => return Ok(*period),
+            None => {
+                let span = offset..offset + name.len();
+                return Err(ParseError::new(ParseErrorKind::InvalidPeriod, span));
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for Rate {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Rate, ParseError> {
+        return s.parse();
+    }
+}
+
+impl TryFrom<&str> for DataSize {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<DataSize, ParseError> {
+        return s.parse();
+    }
+}
+
+impl TryFrom<&str> for Period {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Period, ParseError> {
+        return s.parse();
+    }
+}
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// The rate at which `size` is transferred in `duration`.
+impl core::ops::Div<Duration> for DataSize {
+    type Output = Rate;
+
+    fn div(self, duration: Duration) -> Rate {
+        match self.bytes.checked_mul(NANOS_PER_SECOND) {
+            Some(bytes) => return Rate::new(bytes, duration.as_nanos()),
+            None => return Rate::from_bytes_per_second(self.bytes as f64 / duration.as_secs_f64()),
+        }
+    }
+}
+
+/// How long it takes to transfer `size` at `rate`. A zero rate takes
+/// `Duration::MAX`.
+impl core::ops::Div<Rate> for DataSize {
+    type Output = Duration;
+
+    fn div(self, rate: Rate) -> Duration {
+        if rate.bytes == 0 {
+            return Duration::MAX;
+        }
+        let nanos = (self.bytes.checked_mul(rate.seconds))
+            .and_then(|n| n.checked_mul(NANOS_PER_SECOND))
+            .map(|n| n / rate.bytes);
+        match nanos {
+            Some(nanos) if nanos <= u64::MAX as u128 => return Duration::from_nanos(nanos as u64),
+            Some(_) => return Duration::MAX,
+            None => {
+                let seconds = self.bytes as f64 / rate.bytes_per_second();
+                return Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX);
+            }
+        }
+    }
+}
+
+/// The data transferred at `rate` during `duration`, rounded down to a
+/// whole byte.
+impl core::ops::Mul<Duration> for Rate {
+    type Output = DataSize;
+
+    fn mul(self, duration: Duration) -> DataSize {
+        let bytes = (self.bytes.checked_mul(duration.as_nanos()))
+            .zip(self.seconds.checked_mul(NANOS_PER_SECOND))
+            .map(|(n, d)| n.checked_div(d).unwrap_or(u128::MAX));
+        match bytes {
+            Some(bytes) => return DataSize::from_bytes(bytes),
+            None => {
+                let bytes = self.bytes_per_second() * duration.as_secs_f64();
+                return DataSize::from_bytes(bytes as u128);
+            }
+        }
+    }
+}
+
+impl core::ops::Mul<Rate> for Duration {
+    type Output = DataSize;
+
+    fn mul(self, rate: Rate) -> DataSize {
+        return rate * self;
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    return a;
+}
+
+/// Rounds a fraction to the nearest float.
+fn to_f64(numerator: u128, denominator: u128) -> f64 {
+    return numerator as f64 / denominator as f64;
+}
+
+/// A decimal number such as "12.5" or "1.25e1" as a fraction, if it fits.
+fn decimal_fraction(s: &str) -> Option<(u128, u128)> {
+    let (digits, exponent) = match s.split_once(['e', 'E']) {
+        Some((digits, exponent)) => (digits, exponent.parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (whole, decimals) = digits.split_once('.').unwrap_or((digits, ""));
+    let mut mantissa: u128 = 0;
+    for b in whole.bytes().chain(decimals.bytes()) {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        mantissa = mantissa.checked_mul(10)?.checked_add((b - b'0') as u128)?;
+    }
+    let exponent = exponent - decimals.len() as i32;
+    let power = 10u128.checked_pow(exponent.unsigned_abs())?;
+    if exponent >= 0 {
+        return Some((mantissa.checked_mul(power)?, 1));
+    }
+    return Some((mantissa, power));
+}
+
+/// Room for a float written with `{:e}`: at most 17 digits, a point and
+/// an exponent.
+#[derive(Default)]
+struct DecimalBuf {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl DecimalBuf {
+    fn as_str(&self) -> &str {
+        return core::str::from_utf8(&self.bytes[..self.len]).unwrap_or("");
+    }
+}
+
+impl fmt::Write for DecimalBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            return Err(fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        return Ok(());
+    }
+}
+
+/// Returns the table of how much data `rate` amounts to over each
+/// period, one line per period.
+#[cfg(feature = "alloc")]
+pub fn format_table(rate: Rate) -> String {
+    return RateFormatter::new().format_table(rate);
+}
+
+/// One line of the conversion table, as data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateRow {
+    pub period: Period,
+    pub seconds: u64,
+    /// The amount of data over the period, in `unit`s.
+    pub scaled_value: f64,
+    pub unit: Unit,
+    /// The amount of data over the period, in bytes.
+    pub raw_bytes: f64,
+}
+
+/// Returns the rows of `format_table`, for callers that render the
+/// table themselves.
+#[cfg(feature = "alloc")]
+pub fn table_rows(rate: Rate) -> Vec<RateRow> {
+    let mut rows = Vec::new();
+    for period in PERIODS {
+        let (bytes, seconds) = rate.over(period.seconds);
+        let (scaled_value, exponent) = scale_exact(bytes, seconds, 1000);
+        rows.push(RateRow {
+            period: *period,
+            seconds: period.seconds,
+            scaled_value,
+            unit: UNITS[exponent],
+            raw_bytes: to_f64(bytes, seconds),
+        });
+    }
+    return rows;
+}
+
+/// Formats a rate as a single line, e.g. "50.000 MB / sec".
+#[cfg(feature = "alloc")]
+pub fn format_rate(bytes_per_second: f64) -> String {
+    let (rate, unit) = nearest_power_of_1000_rate(bytes_per_second);
+    return format!("{:.3} {} / {}", rate, unit, PERIODS[0].name);
+}
+
+/// Formats a rate in bits, e.g. "1.000 Gb / sec", for network links.
+#[cfg(feature = "alloc")]
+pub fn format_bitrate(bytes_per_second: f64) -> String {
+    let (rate, unit) = nearest_power_of_1000_rate(bytes_per_second * 8.0);
+    let prefix = &unit[..unit.len() - 1];
+    return format!("{:.3} {}b / {}", rate, prefix, PERIODS[0].name);
+}
+
+/// Formats a plain count with a metric prefix, e.g. "1.200 M".
+#[cfg(feature = "alloc")]
+pub fn format_count(mut n: f64) -> String {
+    for prefix in ["", "K", "M", "G", "T"] {
+        if n < 1000.0 || prefix == "T" {
+            return String::from(format!("{:.3} {}", n, prefix).trim_end());
+        }
+        n /= 1000.0;
+    }
+    unreachable!();
+}
+
+/// Formats a data size, e.g. "800.000 GB".
+#[cfg(feature = "alloc")]
+pub fn format_size(bytes: f64) -> String {
+    let (size, unit) = nearest_power_of_1000_rate(bytes);
+    return format!("{:.3} {}", size, unit);
+}
+
+/// Formats a number of seconds as e.g. "2d 3h 4m 5s", dropping the
+/// leading zero fields. Sub-second durations are shown in milliseconds.
+#[cfg(feature = "alloc")]
+pub fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return String::from("forever");
+    }
+    if seconds < 0.9995 {
+        return format!("{:.0}ms", seconds * 1000.0);
+    }
+    let mut left = (seconds + 0.5) as u64;
+    let mut parts: Vec<String> = Vec::new();
+    for (unit, name) in [(DAY, "d"), (HOUR, "h"), (MINUTE, "m"), (SECOND, "s")] {
+        if left >= unit || !parts.is_empty() {
+            parts.push(format!("{}{}", left / unit, name));
+            left %= unit;
+        }
+    }
+    return parts.join(" ");
+}
+
+#[cfg(feature = "alloc")]
+fn nearest_power_of_1000_rate(mut bytes: f64) -> (f64, &'static str) {
+    if !bytes.is_finite() {
+        return (bytes, UNITS[0].symbol);
+    }
+    let (largest, units) = UNITS.split_last().unwrap();
+    for unit in units {
+        if bytes < 1000.0 {
+            return (bytes, unit.symbol);
+        }
+        bytes /= 1000.0;
+    }
+    return (bytes, largest.symbol);
+}
+
+/// Every accepted spelling of the periods, matched case-insensitively.
+const PERIOD_SPELLINGS: &[(&str, u64)] = &[
+    ("s", SECOND),
+    ("sec", SECOND),
+    ("second", SECOND),
+    ("m", MINUTE),
+    ("min", MINUTE),
+    ("minute", MINUTE),
+    ("h", HOUR),
+    ("hr", HOUR),
+    ("hour", HOUR),
+    ("d", DAY),
+    ("day", DAY),
+    ("w", WEEK),
+    ("wk", WEEK),
+    ("week", WEEK),
+    ("mon", MONTH),
+    ("month", MONTH),
+    ("y", YEAR),
+    ("yr", YEAR),
+    ("year", YEAR),
+];
+
+/// The spellings of the business periods, with their index in
+/// `BUSINESS_PERIODS`.
+const BUSINESS_SPELLINGS: &[(&str, usize)] = &[
+    ("workday", 0),
+    ("businessday", 0),
+    ("workweek", 1),
+    ("businessweek", 1),
+];
+
+/// Like `nearest_power_of_1000_rate` for the fraction `bytes / seconds`,
+/// dividing exactly before rounding to a float.
+fn nearest_power_of_1000_exact(bytes: u128, seconds: u128) -> (f64, &'static str) {
+    let (amount, exponent) = scale_exact(bytes, seconds, 1000);
+    return (amount, UNITS[exponent].symbol);
+}
+
+/// Divides the fraction `numerator / denominator` by the largest power
+/// of `base` it reaches, up to the largest unit, and returns the quotient
+/// and the exponent. Past the largest unit, the quotient is in it.
+fn scale_exact(numerator: u128, denominator: u128, base: u128) -> (f64, usize) {
+    if denominator == 0 {
+        return (f64::INFINITY, 0);
+    }
+    let (denominator, exponent) = scale_fraction(numerator, denominator, base);
+    return (to_f64(numerator, denominator), exponent);
+}
+
+/// Like `scale_exact`, but returns the denominator of the quotient,
+/// `denominator` times the power of `base`, instead of rounding it.
+fn scale_fraction(numerator: u128, mut denominator: u128, base: u128) -> (u128, usize) {
+    for exponent in 0..UNITS.len() - 1 {
+        match denominator.checked_mul(base) {
+            Some(next) if numerator >= next => denominator = next,
+            // Also when the next power no longer fits: neither can `numerator`.
+            _ => return (denominator, exponent),
+        }
+    }
+    return (denominator, UNITS.len() - 1);
+}
+
+/// The units and periods that the parser knows: the built-in `UNITS`
+/// and `PERIODS`, and those added at runtime, e.g. from a configuration
+/// file. The free functions such as `parse` use the registry installed
+/// with `Registry::install`, or the built-ins only.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// let mut registry = rate::Registry::new();
+/// registry.add_unit("block", 4096);
+/// registry.add_period("sol", 88740);
+/// let rate = registry.parse("2 block/sol").unwrap();
+/// assert_eq!(rate, rate::Rate::new(8192, 88740));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Registry {
+    #[cfg(feature = "alloc")]
+    units: Vec<(String, u128)>,
+    #[cfg(feature = "alloc")]
+    periods: Vec<(String, u64)>,
+    /// The lengths of the `BUSINESS_PERIODS`.
+    business: [u64; 2],
+    /// The length of a month, 30 days unless set otherwise.
+    month: u64,
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        return Registry::new();
+    }
+}
+
+/// The registry of the built-in units and periods only.
+static BUILTIN: Registry = Registry::new();
+
+#[cfg(feature = "std")]
+static INSTALLED: std::sync::RwLock<Option<Registry>> = std::sync::RwLock::new(None);
+
+/// Runs `f` with the installed registry, or the built-in one.
+fn with_registry<T>(f: impl FnOnce(&Registry) -> T) -> T {
+    #[cfg(feature = "std")]
+    {
+        let installed = INSTALLED.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(registry) = installed.as_ref() {
+            return f(registry);
+        }
+    }
+    return f(&BUILTIN);
+}
+
+impl Registry {
+    /// A registry of the built-in units and periods.
+    pub const fn new() -> Registry {
+        return Registry {
+            #[cfg(feature = "alloc")]
+            units: Vec::new(),
+            #[cfg(feature = "alloc")]
+            periods: Vec::new(),
+            business: [WORKDAY, WORKWEEK],
+            month: MONTH,
+        };
+    }
+
+    /// Adds a unit of `bytes` bytes, e.g. a "block" of 4096 bytes. Its
+    /// name is matched case-insensitively, after the built-in units.
+    #[cfg(feature = "alloc")]
+    pub fn add_unit(&mut self, name: &str, bytes: u128) {
+        self.units.push((String::from(name), bytes));
+    }
+
+    /// Adds a period of `seconds` seconds, e.g. a "sol" of 24h39m. Its
+    /// name is matched case-insensitively, after the built-in spellings.
+    #[cfg(feature = "alloc")]
+    pub fn add_period(&mut self, name: &str, seconds: u64) {
+        self.periods.push((String::from(name), seconds));
+    }
+
+    /// Sets the lengths of a work day and a work week, e.g. 7.5 hours and
+    /// 4 days of them for a shorter week.
+    pub fn set_business_hours(&mut self, workday: u64, workweek: u64) {
+        self.business = [workday, workweek];
+    }
+
+    /// Sets the length of a month, e.g. 31 days for contracts billed on
+    /// long months.
+    pub fn set_month(&mut self, seconds: u64) {
+        self.month = seconds;
+    }
+
+    /// The length of a month in this registry.
+    pub fn month(&self) -> u64 {
+        return self.month;
+    }
+
+    /// The business period of a spelling, e.g. "workday", with its length
+    /// in this registry.
+    pub fn business_period(&self, name: &str) -> Option<Period> {
+        let (_, i) = BUSINESS_SPELLINGS
+            .iter()
+            .find(|(spelling, _)| spelling.eq_ignore_ascii_case(name))?;
+        return Some(Period {
+            name: BUSINESS_PERIODS[*i].name,
+            seconds: self.business[*i],
+        });
+    }
+
+    /// Makes this registry the one that `parse`, `parse_size`,
+    /// `parse_duration`, `period_to_seconds` and the `FromStr`
+    /// implementations consult.
+    #[cfg(feature = "std")]
+    pub fn install(self) {
+        *INSTALLED.write().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+
+    /// The size in bytes of an added unit.
+    fn unit(&self, name: &[u8]) -> Option<u128> {
+        #[cfg(feature = "alloc")]
+        for (unit, bytes) in &self.units {
+            if unit.as_bytes().eq_ignore_ascii_case(name) {
+                return Some(*bytes);
+            }
+        }
+        let _ = name;
+        return None;
+    }
+
+    /// Parses a rate like "12 MB/s", "1.5 Gb / min" or "14TB/day".
+    pub fn parse(&self, s: &str) -> Result<Rate, ParseError> {
+        return Ok(self.parse_expression(s)?.rate);
+    }
+
+    /// Parses a rate like `parse`, and returns the parts it is made of.
+    pub fn parse_expression<'a>(&self, s: &'a str) -> Result<Expression<'a>, ParseError> {
+        let mut p = Parser::new(s, self);
+        p.skip_whitespace();
+        let start = p.pos;
+        let (mantissa, decimals) = p.parse_decimal()?;
+        let number = &s[start..p.pos];
+        p.skip_whitespace();
+        let unit_start = p.pos;
+        let (unit_bytes, unit_divisor) = p.parse_unit()?;
+        let unit = &s[unit_start..p.pos];
+        p.skip_whitespace();
+        p.expect(b'/')?;
+        p.skip_whitespace();
+        let period_start = p.pos;
+        let period = p.parse_word();
+        let seconds = self
+            .period_to_seconds(period)
+            .map_err(|e| p.error(e.kind, period_start))?;
+        let bytes = mantissa.checked_mul(unit_bytes);
+        let rate_seconds = 10u128
+            .checked_pow(decimals)
+            .and_then(|d| d.checked_mul(unit_divisor * seconds as u128));
+        match (bytes, rate_seconds) {
+            (Some(bytes), Some(rate_seconds)) => {
+                return Ok(Expression {
+                    number,
+                    unit,
+                    unit_bytes,
+                    bits: unit_divisor == 8,
+                    period: &s[period_start..p.pos],
+                    seconds,
+                    rate: Rate::new(bytes, rate_seconds),
+                })
+            }
+            _ => return Err(p.error(ParseErrorKind::InvalidNumber, start)),
+        }
+    }
+
+    /// Returns the length in seconds of a period given by one of its
+    /// spellings, e.g. "s", "min" or "week".
+    pub fn period_to_seconds(&self, period_name: &str) -> Result<u64, ParseError> {
+        for (spelling, seconds) in PERIOD_SPELLINGS {
+            if spelling.eq_ignore_ascii_case(period_name) {
+                if *seconds == MONTH {
+                    return Ok(self.month);
+                }
+                return Ok(*seconds);
+            }
+        }
+        if let Some(period) = self.business_period(period_name) {
+            return Ok(period.seconds);
+        }
+        #[cfg(feature = "alloc")]
+        for (name, seconds) in &self.periods {
+            if name.eq_ignore_ascii_case(period_name) {
+                return Ok(*seconds);
+            }
+        }
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidPeriod,
+            0..period_name.len(),
+        ));
+    }
+
+    /// Parses a data size like "800 GB" and returns it in bytes. The
+    /// product is exact where it fits, so that "1.1 KB" is 1100 bytes.
+    pub fn parse_size(&self, s: &str) -> Result<f64, ParseError> {
+        let mut p = Parser::new(s, self);
+        p.skip_whitespace();
+        let start = p.pos;
+        let size: f64 = p.parse_number()?;
+        let number = &s[start..p.pos];
+        p.skip_whitespace();
+        let (unit_bytes, unit_divisor) = p.parse_unit()?;
+        p.skip_whitespace();
+        if !p.eof() {
+            return Err(p.trailing());
+        }
+        let exact = decimal_fraction(number).and_then(|(numerator, denominator)| {
+            let bytes = numerator.checked_mul(unit_bytes)?;
+            let rate = Rate::new(bytes, denominator.checked_mul(unit_divisor)?);
+            return Some(rate.bytes_per_second());
+        });
+        return Ok(exact.unwrap_or(size * unit_bytes as f64 / unit_divisor as f64));
+    }
+
+    /// Parses a duration like "60s", "1.5 hour" or "1h30m" and returns it
+    /// in seconds. Milliseconds ("ms") are accepted on top of the periods.
+    pub fn parse_duration(&self, s: &str) -> Result<f64, ParseError> {
+        let mut p = Parser::new(s, self);
+        let mut seconds: f64 = 0.0;
+        p.skip_whitespace();
+        loop {
+            let n: f64 = p.parse_number()?;
+            p.skip_whitespace();
+            let unit_start = p.pos;
+            let unit = p.parse_word();
+            if unit.eq_ignore_ascii_case("ms") {
+                seconds += n / 1000.0;
+            } else {
+                let period = self
+                    .period_to_seconds(unit)
+                    .map_err(|e| p.error(e.kind, unit_start))?;
+                seconds += n * period as f64;
+            }
+            p.skip_whitespace();
+            if p.eof() {
+                return Ok(seconds);
+            }
+        }
+    }
+
+    /// Parses a size like `DataSize::from_str`.
+    pub fn parse_data_size(&self, s: &str) -> Result<DataSize, ParseError> {
+        let mut p = Parser::new(s, self);
+        p.skip_whitespace();
+        let start = p.pos;
+        let (mantissa, decimals) = p.parse_decimal()?;
+        p.skip_whitespace();
+        let (unit_bytes, unit_divisor) = p.parse_unit()?;
+        let end = p.pos;
+        p.skip_whitespace();
+        if !p.eof() {
+            return Err(p.trailing());
+        }
+        let bytes = mantissa.checked_mul(unit_bytes);
+        let divisor = 10u128
+            .checked_pow(decimals)
+            .and_then(|d| d.checked_mul(unit_divisor));
+        match (bytes, divisor) {
+            (Some(bytes), Some(divisor)) => return Ok(DataSize::from_bytes(bytes / divisor)),
+            _ => return Err(ParseError::new(ParseErrorKind::InvalidNumber, start..end)),
+        }
+    }
+}
+
+/// Parses a rate like "12 MB/s", "1.5 Gb / min" or "14TB/day".
+pub fn parse(s: &str) -> Result<Rate, ParseError> {
+    return with_registry(|registry| registry.parse(s));
+}
+
+/// The parts of a rate expression, e.g. for "1.5 Gb / min": the number
+/// "1.5", the unit "Gb" of 10^9 bits, and the period "min" of 60 seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Expression<'a> {
+    pub number: &'a str,
+    pub unit: &'a str,
+    /// The size of the unit, in bytes or in bits if `bits` is set.
+    pub unit_bytes: u128,
+    pub bits: bool,
+    pub period: &'a str,
+    pub seconds: u64,
+    pub rate: Rate,
+}
+
+/// Parses a rate like `parse`, and returns the parts it is made of.
+pub fn parse_expression(s: &str) -> Result<Expression<'_>, ParseError> {
+    return with_registry(|registry| registry.parse_expression(s));
+}
+
+/// Returns the length in seconds of a period given by one of its
+/// spellings, e.g. "s", "min" or "week".
+pub fn period_to_seconds(period_name: &str) -> Result<u64, ParseError> {
+    return with_registry(|registry| registry.period_to_seconds(period_name));
+}
+
+/// The length of a month in the installed registry, 30 days by default.
+pub fn month() -> u64 {
+    return with_registry(|registry| registry.month());
+}
+
+/// Parses a data size like "800 GB" and returns it in bytes.
+pub fn parse_size(s: &str) -> Result<f64, ParseError> {
+    return with_registry(|registry| registry.parse_size(s));
+}
+
+/// Parses a duration like "60s", "1.5 hour" or "1h30m" and returns it
+/// in seconds. Milliseconds ("ms") are accepted on top of the periods.
+pub fn parse_duration(s: &str) -> Result<f64, ParseError> {
+    return with_registry(|registry| registry.parse_duration(s));
+}
+
+/// Parses a count with an optional metric prefix, e.g. "40k" or "1.2M".
+pub fn parse_quantity(s: &str) -> Result<f64, ParseError> {
+    let mut p = Parser::new(s, &BUILTIN);
+    p.skip_whitespace();
+    let n: f64 = p.parse_number()?;
+    p.skip_whitespace();
+    let prefix_start = p.pos;
+    let prefix = p.parse_word();
+    let multiplier = match [("", 1.0), ("k", 1e3), ("m", 1e6), ("g", 1e9), ("t", 1e12)]
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(prefix))
+    {
+        Some((_, multiplier)) => *multiplier,
+        None => return Err(p.error(ParseErrorKind::InvalidNumber, prefix_start)),
+    };
+    p.skip_whitespace();
+    if !p.eof() {
+        return Err(p.trailing());
+    }
+    return Ok(n * multiplier);
+}
+
+/// Parses a ratio given as a percentage ("95%") or a fraction ("0.95").
+pub fn parse_percent(s: &str) -> Result<f64, ParseError> {
+    let offset = s.len() - s.trim_start().len();
+    let s = s.trim();
+    let (number, scale) = match s.strip_suffix('%') {
+        Some(n) => (n.trim_end(), 100.0),
+        None => (s, 1.0),
+    };
+    match number.parse::<f64>() {
+        Ok(x) if x >= 0.0 => return Ok(x / scale),
+        _ => {
+            let span = offset..offset + number.len();
+            return Err(ParseError::new(ParseErrorKind::InvalidNumber, span));
+        }
+    }
+}
+
+/// Parses a number of events per period, e.g. "40k/s" or "1.2 M / min",
+/// and returns it per second.
+pub fn parse_frequency(s: &str) -> Result<f64, ParseError> {
+    let Some((count, period)) = s.split_once('/') else {
+        let kind = ParseErrorKind::UnexpectedCharacter {
+            expected: b'/',
+            actual: 0,
+        };
+        return Err(ParseError::new(kind, s.len()..s.len()));
+    };
+    let offset = s.len() - period.trim_start().len();
+    let seconds = period_to_seconds(period.trim()).map_err(|e| e.shift(offset))?;
+    return Ok(parse_quantity(count)? / seconds as f64);
+}
+
+/// Why an expression could not be parsed, and where.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// The byte range of the input at fault, when known. It is empty
+    /// when the input ended too early.
+    pub span: Option<Range<usize>>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Range<usize>) -> ParseError {
+        return ParseError {
+            kind,
+            span: Some(span),
+        };
+    }
+
+    /// The byte offset of the error in the input.
+    pub fn offset(&self) -> Option<usize> {
+        return self.span.as_ref().map(|span| span.start);
+    }
+
+    /// The part of `input` at fault.
+    pub fn slice<'a>(&self, input: &'a str) -> Option<&'a str> {
+        return input.get(self.span.clone()?);
+    }
+
+    /// Shows `input` with a caret under the part at fault, e.g.
+    ///
+    /// ```text
+    /// 12 MBs
+    ///    ^^^
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn underline(&self, input: &str) -> Option<String> {
+        let span = self.span.clone()?;
+        let before = input.get(..span.start)?.chars().count();
+        let width = input.get(span)?.chars().count().max(1);
+        return Some(format!(
+            "{}\n{}{}",
+            input,
+            " ".repeat(before),
+            "^".repeat(width)
+        ));
+    }
+
+    /// The known unit or period closest to the invalid one in `input`,
+    /// if it looks like a typo of it: "GB" for "GBs", "month" for "mnth".
+    #[cfg(feature = "alloc")]
+    pub fn suggestion(&self, input: &str) -> Option<String> {
+        let word = self.slice(input)?;
+        let mut candidates: Vec<String> = Vec::new();
+        match self.kind {
+            ParseErrorKind::InvalidUnit => {
+                for unit in UNITS {
+                    candidates.push(String::from(unit.symbol));
+                    let prefix = &unit.symbol[..unit.symbol.len() - 1];
+                    candidates.push(format!("{}b", prefix));
+                }
+            }
+            ParseErrorKind::InvalidPeriod => {
+                for (spelling, _) in PERIOD_SPELLINGS {
+                    candidates.push(String::from(*spelling));
+                }
+                for (spelling, _) in BUSINESS_SPELLINGS {
+                    candidates.push(String::from(*spelling));
+                }
+            }
+            _ => return None,
+        }
+        let mut best: Option<(usize, String)> = None;
+        for candidate in candidates {
+            let distance = edit_distance(word.as_bytes(), candidate.as_bytes());
+            if distance <= 2
+                && distance < word.len()
+                && best.as_ref().is_none_or(|b| distance < b.0)
+            {
+                best = Some((distance, candidate));
+            }
+        }
+        return best.map(|(_, candidate)| candidate);
+    }
+
+    /// Moves the span right by `offset` bytes, for an error in a part of
+    /// a larger input.
+    pub fn shift(mut self, offset: usize) -> ParseError {
+        self.span = self.span.map(|span| span.start + offset..span.end + offset);
+        return self;
+    }
+}
+
+/// The number of insertions, deletions and substitutions that turn `a`
+/// into `b` (the Levenshtein distance).
+#[cfg(feature = "alloc")]
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    return row[b.len()];
+}
+
+impl From<ParseErrorKind> for ParseError {
+    fn from(kind: ParseErrorKind) -> ParseError {
+        return ParseError { kind, span: None };
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return self.kind.fmt(f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// What was wrong with an expression.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    InvalidNumber,
+    InvalidUnit,
+    InvalidPeriod,
+    UnexpectedCharacter { expected: u8, actual: u8 },
+    TrailingInput,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber => write!(f, "not a valid number"),
+            Self::InvalidUnit => {
+                write!(f, "not a recognized unit (")?;
+                for (i, unit) in UNITS.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { " " };
+                    write!(f, "{}{}", sep, unit.symbol)?;
+                }
+                write!(f, ")")
+            }
+            Self::InvalidPeriod => {
+                write!(f, "not a recognized time period (")?;
+                for (i, period) in PERIODS.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { " " };
+                    write!(f, "{}{}", sep, period.name)?;
+                }
+                write!(f, ")")
+            }
+            Self::UnexpectedCharacter { expected, actual } => {
+                write!(
+                    f,
+                    "expected {:?}, found {:?}",
+                    *expected as char, *actual as char
+                )
+            }
+            Self::TrailingInput => write!(f, "unexpected input after the expression"),
+        }
+    }
+}
+
+struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    registry: &'a Registry,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str, registry: &'a Registry) -> Parser<'a> {
+        return Parser {
+            buf: s.as_bytes(),
+            pos: 0,
+            registry,
+        };
+    }
+
+    fn peek(&self) -> u8 {
+        if self.pos >= self.buf.len() {
+            return 0;
+        }
+        return self.buf[self.pos];
+    }
+
+    fn eof(&self) -> bool {
+        return self.peek() == 0;
+    }
+
+    fn advance(&mut self) -> u8 {
+        let b = self.peek();
+        if b == 0 {
+            return b;
+        }
+        self.pos += 1;
+        return b;
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<u8, ParseError> {
+        let start = self.pos;
+        let actual = self.peek();
+        if actual == expected {
+            return Ok(self.advance());
+        }
+        return Err(self.error(
+            ParseErrorKind::UnexpectedCharacter { expected, actual },
+            start,
+        ));
+    }
+
+    /// An error about the input from `start` to the current position,
+    /// or about the next character if nothing was consumed since.
+    fn error(&self, kind: ParseErrorKind, start: usize) -> ParseError {
+        let mut end = self.pos;
+        if end == start {
+            let rest = core::str::from_utf8(&self.buf[start..]).unwrap_or("");
+            end += rest.chars().next().map_or(0, char::len_utf8);
+        }
+        return ParseError::new(kind, start..end);
+    }
+
+    /// The error for input left after a complete expression.
+    fn trailing(&self) -> ParseError {
+        let rest = core::str::from_utf8(&self.buf[self.pos..]).unwrap_or("");
+        return ParseError::new(
+            ParseErrorKind::TrailingInput,
+            self.pos..self.pos + rest.trim_end().len(),
+        );
+    }
+
+    fn skip_whitespace(&mut self) {
+        while !self.eof() && self.peek().is_ascii_whitespace() {
+            self.advance();
+        }
+    }
+
+    /// Parses a number like `parse_number`, but exactly: returns its
+    /// digits as an integer and the count of those after the point.
+    fn parse_decimal(&mut self) -> Result<(u128, u32), ParseError> {
+        let start_pos = self.pos;
+        self.parse_number()?;
+        let mut mantissa: u128 = 0;
+        let mut decimals: Option<u32> = None;
+        for &b in &self.buf[start_pos..self.pos] {
+            if b == b'.' {
+                decimals = Some(0);
+                continue;
+            }
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add((b - b'0') as u128))
+                .ok_or_else(|| self.error(ParseErrorKind::InvalidNumber, start_pos))?;
+            decimals = decimals.map(|d| d + 1);
+        }
+        return Ok((mantissa, decimals.unwrap_or(0)));
+    }
+
+    fn parse_number(&mut self) -> Result<f64, ParseError> {
+        let start_pos = self.pos;
+        while !self.eof() && self.peek().is_ascii_digit() {
+            self.advance();
+        }
+        // No digits
+        // NB(vincent): shouldn't ever trigger, we get inside `parse_number`
+        // because we saw a digit. Still gonna put it for good measure.
+        if start_pos == self.pos {
+            return Err(self.error(ParseErrorKind::InvalidNumber, start_pos));
+        }
+
+        if self.peek() == b'.' {
+            self.advance(); // eat the '.'
+            let decimals_start = self.pos;
+            while !self.eof() && self.peek().is_ascii_digit() {
+                self.advance();
+            }
+            if decimals_start == self.pos {
+                return Err(self.error(ParseErrorKind::InvalidNumber, start_pos));
+            }
+        }
+
+        let s = unsafe { core::str::from_utf8_unchecked(&self.buf[start_pos..self.pos]) };
+        match s.parse::<f64>() {
+            Ok(x) => return Ok(x),
+            Err(_) => return Err(self.error(ParseErrorKind::InvalidNumber, start_pos)),
+        }
+    }
+
+    /// Parses strings like "B", "MB", "TB", etc. and returns how many
+    /// bytes that it (e.g., "B" -> 1, "MB" -> 1e6, "TB" -> 1e12).
+    /// A "bit" suffix, or a lowercase final "b" after an uppercase prefix,
+    /// means bits instead of bytes (e.g., "Mb" and "Mbit" -> 125e3), like
+    /// network rates. Units all in lowercase ("mb", "tb") are bytes.
+    #[cfg(test)]
+    fn parse_bytes(&mut self) -> Result<f64, ParseError> {
+        let (bytes, divisor) = self.parse_unit()?;
+        return Ok(bytes as f64 / divisor as f64);
+    }
+
+    /// Parses a unit like `parse_bytes`, returning its size as the
+    /// fraction of bytes over 8 for bits or 1 for bytes.
+    fn parse_unit(&mut self) -> Result<(u128, u128), ParseError> {
+        let start_pos = self.pos;
+        while !self.eof() && self.peek().is_ascii_alphabetic() {
+            self.advance();
+        }
+        let word = &self.buf[start_pos..self.pos];
+        let bits = strip_suffix_ignore_case(word, b"bits")
+            .or_else(|| strip_suffix_ignore_case(word, b"bit"));
+        let (prefix, divisor) = if let Some(prefix) = bits {
+            (prefix, 8)
+        } else if word.ends_with(b"b") && word.iter().any(u8::is_ascii_uppercase) {
+            (&word[..word.len() - 1], 8)
+        } else if word.ends_with(b"B") || word.ends_with(b"b") {
+            (&word[..word.len() - 1], 1)
+        } else if let Some(bytes) = self.registry.unit(word) {
+            return Ok((bytes, 1));
+        } else {
+            return Err(self.error(ParseErrorKind::InvalidUnit, start_pos));
+        };
+        for unit in UNITS {
+            let symbol = unit.symbol.as_bytes();
+            if symbol[..symbol.len() - 1].eq_ignore_ascii_case(prefix) {
+                return Ok((unit.bytes, divisor));
+            }
+        }
+        if let Some(bytes) = self.registry.unit(word) {
+            return Ok((bytes, 1));
+        }
+        return Err(self.error(ParseErrorKind::InvalidUnit, start_pos));
+    }
+
+    /// Consumes a run of letters and returns it.
+    fn parse_word(&mut self) -> &'a str {
+        let start_pos = self.pos;
+        while !self.eof() && self.peek().is_ascii_alphabetic() {
+            self.advance();
+        }
+        return unsafe { core::str::from_utf8_unchecked(&self.buf[start_pos..self.pos]) };
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(word: &'a [u8], suffix: &[u8]) -> Option<&'a [u8]> {
+    if word.len() < suffix.len() {
+        return None;
+    }
+    let (prefix, end) = word.split_at(word.len() - suffix.len());
+    if end.eq_ignore_ascii_case(suffix) {
+        return Some(prefix);
+    }
+    return None;
+}
+
+#[test]
+fn test_parse_whitespaces() {
+    // See that we can put whitespaces pretty much everywhere
+    assert!(parse("1B/s").is_ok());
+    assert!(parse("1 B/s").is_ok());
+    assert!(parse("1B /s").is_ok());
+    assert!(parse("1B/ s").is_ok());
+    assert!(parse("1B / s").is_ok());
+    assert!(parse("1 B/ s").is_ok());
+    assert!(parse("1 B / s").is_ok());
+    assert!(parse(" 1 B / s ").is_ok());
+}
+
+#[test]
+fn test_parse_units() {
+    // Try the different units and with different casing.
+    assert!(parse("1 b / s").is_ok());
+    assert!(parse("1 B / s").is_ok());
+    assert!(parse("1 kB / s").is_ok());
+    assert!(parse("1 Kb / s").is_ok());
+    assert!(parse("1 KB / s").is_ok());
+    assert!(parse("1 MB / s").is_ok());
+    assert!(parse("1 GB / s").is_ok());
+    assert!(parse("1 TB / s").is_ok());
+    assert!(parse("1 PB / s").is_ok());
+    assert!(parse("1 EB / s").is_ok());
+    assert!(parse("1 ZB / s").is_ok());
+    assert!(parse("1 YB / s").is_ok());
+    assert!(parse("1 RB / s").is_ok());
+    assert!(parse("1 Qb / s").is_ok());
+}
+
+#[test]
+fn test_parse_periods() {
+    // Try the different period spellings and with different casing.
+    assert!(parse("1 B / s").is_ok());
+    assert!(parse("1 B / S").is_ok());
+    assert!(parse("1 B / sec").is_ok());
+    assert!(parse("1 B / SEC").is_ok());
+    assert!(parse("1 B / SeC").is_ok());
+    assert!(parse("1 B / SEC").is_ok());
+    assert!(parse("1 B / second").is_ok());
+    assert!(parse("1 B / m").is_ok());
+    assert!(parse("1 B / min").is_ok());
+    assert!(parse("1 B / minute").is_ok());
+    assert!(parse("1 B / h").is_ok());
+    assert!(parse("1 B / hr").is_ok());
+    assert!(parse("1 B / hour").is_ok());
+    assert!(parse("1 B / d").is_ok());
+    assert!(parse("1 B / day").is_ok());
+    assert!(parse("1 B / w").is_ok());
+    assert!(parse("1 B / wk").is_ok());
+    assert!(parse("1 B / week").is_ok());
+    assert!(parse("1 B / mon").is_ok());
+    assert!(parse("1 B / month").is_ok());
+    assert!(parse("1 B / y").is_ok());
+    assert!(parse("1 B / yr").is_ok());
+    assert!(parse("1 B / year").is_ok());
+}
+
+#[test]
+fn test_parse_invalid_inputs() {
+    // All sorts of invalid input. I probably cannot think of
+    // all the weird-ass ways to get an error though.
+    assert!(parse("").is_err());
+    assert!(parse("1").is_err());
+    assert!(parse("1B").is_err());
+    assert!(parse("1B/").is_err());
+    assert!(parse("1Bps").is_err());
+    assert!(parse("1Bs").is_err());
+    assert!(parse("1B:s").is_err());
+
+    assert!(parse("x MB/s").is_err());
+    assert!(parse("1e7 MB/s").is_err());
+    assert!(parse("-33 MB/s").is_err());
+    assert!(parse("192.168.1.1 MB/s").is_err());
+    assert!(parse("４ MB/s").is_err()); // wide digits
+
+    assert!(parse("4 XB/s").is_err());
+    assert!(parse("4 ML/s").is_err());
+    assert!(parse("4 MMMB/s").is_err());
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_parse_number() {
+    let mut p = Parser::new("", &BUILTIN);
+    assert!(p.parse_number().is_err());
+
+    let mut p = Parser::new("x", &BUILTIN);
+    assert!(p.parse_number().is_err());
+
+    let mut p = Parser::new("1", &BUILTIN);
+    assert_eq!(p.parse_number(), Ok(1.0));
+
+    let mut p = Parser::new("123", &BUILTIN);
+    assert_eq!(p.parse_number(), Ok(123.0));
+
+    let mut p = Parser::new("1.", &BUILTIN);
+    assert!(p.parse_number().is_err());
+
+    let mut p = Parser::new("1.25", &BUILTIN);
+    assert_eq!(p.parse_number(), Ok(1.25));
+}
+
+#[test]
+fn test_parse_unit() {
+    let mut p = Parser::new("B", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1.0));
+
+    let mut p = Parser::new("KB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e3));
+
+    let mut p = Parser::new("MB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e6));
+
+    let mut p = Parser::new("GB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e9));
+
+    let mut p = Parser::new("TB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e12));
+
+    let mut p = Parser::new("PB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e15));
+
+    let mut p = Parser::new("EB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e18));
+
+    let mut p = Parser::new("ZB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e21));
+
+    let mut p = Parser::new("YB", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e24));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_parse_bits() {
+    let mut p = Parser::new("bit", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(0.125));
+
+    let mut p = Parser::new("Mb", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(125e3));
+
+    let mut p = Parser::new("Gb", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(125e6));
+
+    // All in lowercase, the units are bytes, as they always were.
+    let mut p = Parser::new("gb", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1e9));
+    let mut p = Parser::new("b", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(1.0));
+    assert_eq!(parse("14tb/day"), parse("14TB/day"));
+    assert_eq!(
+        format!("{}", parse("14tb/day").unwrap()),
+        "162.037 MB / sec"
+    );
+
+    let mut p = Parser::new("Gbit", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(125e6));
+
+    let mut p = Parser::new("kbits", &BUILTIN);
+    assert_eq!(p.parse_bytes(), Ok(125.0));
+
+    assert_eq!(parse("8 Mb/s"), parse("1 MB/s"));
+    assert_eq!(format_bitrate(125e6), "1.000 Gb / sec");
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("60s"), Ok(60.0));
+    assert_eq!(parse_duration("1.5 hour"), Ok(5400.0));
+    assert_eq!(parse_duration("1h30m"), Ok(5400.0));
+    assert_eq!(parse_duration("250ms"), Ok(0.25));
+    assert_eq!(parse_duration("2 d"), Ok(172800.0));
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("60").is_err());
+    assert!(parse_duration("1h x").is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_quantities() {
+    assert_eq!(parse_quantity("40k"), Ok(40e3));
+    assert_eq!(parse_quantity("1.2M"), Ok(1.2e6));
+    assert_eq!(parse_quantity("5000"), Ok(5000.0));
+    assert!(parse_quantity("5x").is_err());
+    assert_eq!(format_count(1.2e6), "1.200 M");
+    assert_eq!(format_count(12.0), "12.000");
+}
+
+#[test]
+fn test_parse_frequency() {
+    assert_eq!(parse_frequency("40k/s"), Ok(40e3));
+    assert_eq!(parse_frequency("1.5 M / min"), Ok(25e3));
+    assert!(parse_frequency("40k").is_err());
+    assert!(parse_frequency("40k/fortnight").is_err());
+}
+
+#[test]
+fn test_parse_percent() {
+    assert_eq!(parse_percent("95%"), Ok(0.95));
+    assert_eq!(parse_percent("0.5"), Ok(0.5));
+    assert!(parse_percent("-5%").is_err());
+    assert!(parse_percent("lots").is_err());
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("800GB"), Ok(800e9));
+    assert_eq!(parse_size(" 1.5 kb "), Ok(1500.0));
+    assert_eq!(parse_size("1.1 KB"), Ok(1100.0));
+    assert_eq!(parse_size("0.3 Mb"), Ok(37500.0));
+    assert!(parse_size("800").is_err());
+    assert!(parse_size("800 GB/s").is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(0.25), "250ms");
+    assert_eq!(format_duration(59.0), "59s");
+    assert_eq!(format_duration(3601.0), "1h 0m 1s");
+    assert_eq!(format_duration(2.0 * DAY as f64 + 5.0), "2d 0h 0m 5s");
+    assert_eq!(format_duration(f64::INFINITY), "forever");
+}
+
+#[test]
+fn test_exact_rates() {
+    let rate = parse("0.1 GB/s").unwrap();
+    assert_eq!(rate.bytes_per(PERIODS[5]), 259_200_000_000_000.0);
+    assert_eq!(rate, Rate::new(100_000_000, 1));
+    assert_eq!(parse("1 TB / day"), Ok(Rate::new(1_000_000_000_000, 86400)));
+    assert_eq!(parse("8 Mb/s"), parse("1 MB/s"));
+    assert!(parse("1 MB/s").unwrap() < parse("9 Mb/s").unwrap());
+    for x in [0.1, 1.0, 12e6, 1.5e-20, 3e37] {
+        assert_eq!(Rate::from_bytes_per_second(x).bytes_per_second(), x);
+    }
+    assert_eq!(Rate::from_bytes_per_second(1e-300).bytes_per_second(), 0.0);
+    assert!(Rate::from_bytes_per_second(f64::INFINITY)
+        .bytes_per_second()
+        .is_infinite());
+    assert!(parse("1234567890123456789012345678901234567890 B/s").is_err());
+    assert_eq!(Rate::from_decimal(0.1), Rate::new(1, 10));
+    assert_eq!(Rate::from_decimal(1.5e-20), Rate::new(15, 10u128.pow(21)));
+    assert_eq!(Rate::from_decimal(12e6), Rate::new(12_000_000, 1));
+    assert_eq!(
+        Rate::from_decimal(1e-300),
+        Rate::from_bytes_per_second(1e-300)
+    );
+    let rate = parse("1 KB/s").unwrap() * 2.3;
+    assert_eq!(rate, Rate::new(2300, 1));
+    assert_eq!(rate.bytes_per(PERIODS[3]), 198_720_000.0);
+    assert_eq!(decimal_fraction("2.5e-1"), Some((25, 100)));
+    assert_eq!(decimal_fraction("1e40"), None);
+}
+
+#[test]
+fn test_rate_arithmetic() {
+    let mb = parse("1 MB/s").unwrap();
+    assert_eq!(mb + parse("8 Mb/s").unwrap(), parse("2 MB/s").unwrap());
+    assert_eq!(
+        parse("1 TB/day").unwrap() * 0.5,
+        parse("500 GB/day").unwrap()
+    );
+    assert_eq!(mb / 4.0, parse("250 KB/s").unwrap());
+    assert_eq!(parse("3 MB/s").unwrap() - mb, parse("2 MB/s").unwrap());
+    assert_eq!(mb - parse("3 MB/s").unwrap(), Rate::new(0, 1));
+    assert_eq!(parse("250 Mb/s").unwrap() / parse("1 Gb/s").unwrap(), 0.25);
+    let total: Rate = [mb, mb, mb].into_iter().sum();
+    assert_eq!(total, parse("3 MB/s").unwrap());
+    assert!(mb > parse("999 KB/s").unwrap());
+}
+
+#[test]
+fn test_data_size() {
+    let size = DataSize::from_bytes(800_000_000_000);
+    let rate = size / Duration::from_secs(8000);
+    assert_eq!(rate, parse("100 MB/s").unwrap());
+    assert_eq!(size / rate, Duration::from_secs(8000));
+    assert_eq!(
+        rate * Duration::from_millis(1500),
+        DataSize::from_bytes(150_000_000)
+    );
+    assert_eq!(
+        Duration::from_secs(1) * rate,
+        DataSize::from_bytes(100_000_000)
+    );
+    assert_eq!(size / Rate::new(0, 1), Duration::MAX);
+    assert_eq!(DataSize::from_bytes(1) - size, DataSize::default());
+    assert_eq!(DataSize::from_bytes(3).bits(), 24);
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!("12 MB/s".parse::<Rate>(), parse("12 MB/s"));
+    assert_eq!(Rate::try_from("1 Gb/s"), Ok(Rate::new(125_000_000, 1)));
+    assert_eq!("800GB".parse(), Ok(DataSize::from_bytes(800_000_000_000)));
+    assert_eq!(" 1.5 kb ".parse(), Ok(DataSize::from_bytes(1500)));
+    assert_eq!("12 bit".parse(), Ok(DataSize::from_bytes(1)));
+    assert_eq!(
+        "800 GB/s".parse::<DataSize>(),
+        Err(ParseError::new(ParseErrorKind::TrailingInput, 6..8))
+    );
+    assert_eq!("Hr".parse::<Period>().map(|p| p.name), Ok("hour"));
+    assert_eq!(
+        Period::try_from(" fortnight"),
+        Err(ParseError::new(ParseErrorKind::InvalidPeriod, 1..10))
+    );
+}
+
+#[test]
+fn test_parse_expression() {
+    let e = parse_expression(" 1.5 Gb / min").unwrap();
+    assert_eq!((e.number, e.unit, e.period), ("1.5", "Gb", "min"));
+    assert_eq!((e.unit_bytes, e.bits, e.seconds), (1_000_000_000, true, 60));
+    assert_eq!(e.rate, parse("1.5 Gb/min").unwrap());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_suggestion() {
+    let suggest = |s: &str| parse(s).unwrap_err().suggestion(s);
+    assert_eq!(suggest("12 GBs/s").as_deref(), Some("GB"));
+    assert_eq!(suggest("12 GBytes/s"), None);
+    assert_eq!(suggest("12 MiB/s").as_deref(), Some("MB"));
+    assert_eq!(suggest("1 GB/mnth").as_deref(), Some("month"));
+    assert_eq!(suggest("1 GB/seconds").as_deref(), Some("second"));
+    assert_eq!(suggest("1 GB/fortnight"), None);
+    assert_eq!(suggest("x GB/s"), None);
+    assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_registry() {
+    let mut registry = Registry::new();
+    registry.add_unit("block", 4096);
+    registry.add_period("sol", 88740);
+    assert_eq!(registry.parse("2 block/sol"), Ok(Rate::new(8192, 88740)));
+    assert_eq!(registry.parse("2 Blocks/s").unwrap_err().span, Some(2..8));
+    assert_eq!(registry.parse("1 KB/SOL"), Ok(Rate::new(1000, 88740)));
+    assert_eq!(registry.parse_size("10 block"), Ok(40960.0));
+    assert_eq!(registry.parse_duration("1 sol 1s"), Ok(88741.0));
+    assert_eq!(
+        registry.parse_data_size("3 block"),
+        Ok(DataSize::from_bytes(12288))
+    );
+    assert_eq!(
+        parse("1 block/s").unwrap_err().kind,
+        ParseErrorKind::InvalidUnit
+    );
+}
+
+#[test]
+fn test_business_periods() {
+    assert_eq!(
+        parse("8 GB/workday"),
+        Ok(Rate::new(8_000_000_000, 8 * HOUR as u128))
+    );
+    assert_eq!(period_to_seconds("WorkWeek"), Ok(40 * HOUR));
+    assert_eq!(parse_duration("2 businessday"), Ok(16.0 * HOUR as f64));
+    assert_eq!("workweek".parse::<Period>(), Ok(BUSINESS_PERIODS[1]));
+    let mut registry = Registry::new();
+    registry.set_business_hours(7 * HOUR + 30 * MINUTE, 4 * (7 * HOUR + 30 * MINUTE));
+    assert_eq!(registry.period_to_seconds("workweek"), Ok(30 * HOUR));
+    assert_eq!(
+        registry.business_period("workday"),
+        Some(Period {
+            name: "workday",
+            seconds: 27000
+        })
+    );
+    assert_eq!(registry.business_period("day"), None);
+}
+
+#[test]
+fn test_month_length() {
+    let mut registry = Registry::new();
+    assert_eq!(registry.period_to_seconds("month"), Ok(MONTH));
+    registry.set_month(31 * DAY);
+    assert_eq!(registry.period_to_seconds("mon"), Ok(31 * DAY));
+    assert_eq!(
+        registry.parse("31 GB/month"),
+        Ok(Rate::new(31_000_000_000, 31 * DAY as u128))
+    );
+    assert_eq!(registry.period_to_seconds("year"), Ok(YEAR));
+}
+
+#[test]
+fn test_error_spans() {
+    let span = |s: &str| parse(s).unwrap_err().span;
+    assert_eq!(span("x MB/s"), Some(0..1));
+    assert_eq!(span("12. MB/s"), Some(0..3));
+    assert_eq!(span("12 MBs/s"), Some(3..6));
+    assert_eq!(span("12 MB s"), Some(6..7));
+    assert_eq!(span("12 MB"), Some(5..5));
+    assert_eq!(span("12 MB/fortnight"), Some(6..15));
+    assert_eq!(span("12 MB/é"), Some(6..8));
+    assert_eq!(
+        span("1234567890123456789012345678901234567890 B/s"),
+        Some(0..40)
+    );
+    assert_eq!(parse_duration("1h 3x").unwrap_err().span, Some(4..5));
+    assert_eq!(
+        parse_frequency("5k / fortnight").unwrap_err().span,
+        Some(5..14)
+    );
+    assert_eq!(parse_percent(" 5x%").unwrap_err().span, Some(1..3));
+    assert_eq!(parse_size("800 GB later").unwrap_err().offset(), Some(7));
+    let e = parse("12 MB s").unwrap_err();
+    assert_eq!(e.slice("12 MB s"), Some("s"));
+    #[cfg(feature = "alloc")]
+    assert_eq!(e.underline("12 MB s").unwrap(), "12 MB s\n      ^");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_table_rows() {
+    let rows = table_rows(parse("1 TB / day").unwrap());
+    assert_eq!(rows.len(), PERIODS.len());
+    assert_eq!(rows[0].unit.symbol, "MB");
+    assert!((rows[0].scaled_value - 11.574).abs() < 1e-3);
+    assert_eq!(rows[3].period.name, "day");
+    assert_eq!(rows[3].seconds, DAY);
+    assert_eq!((rows[3].scaled_value, rows[3].unit.symbol), (1.0, "TB"));
+    assert_eq!(rows[3].raw_bytes, 1e12);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_beyond_the_largest_unit() {
+    // The largest rate there is, about 3.4e38 bytes in a second, is shown
+    // in quettabytes rather than as infinite.
+    let rate = Rate::new(u128::MAX, 1);
+    assert_eq!(format!("{}", rate), "340282366.921 QB / sec");
+    let rows = table_rows(Rate::new(u128::MAX, YEAR as u128));
+    assert!(rows.iter().all(|r| r.scaled_value.is_finite()));
+    assert_eq!(rows[0].unit.symbol, "QB");
+    assert_eq!(format!("{:.3}", rows[6].scaled_value), "340282366.921");
+    assert_eq!(format_size(2e33), "2000.000 QB");
+    assert_eq!(format_rate(1.5e27), "1.500 RB / sec");
+    assert_eq!(format_size(f64::INFINITY), "inf B");
+    let f = RateFormatter::new().unit_system(UnitSystem::Binary);
+    assert_eq!(f.format(parse("1 QB/s").unwrap()), "807.794 RiB / sec");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_format_table() {
+    let table = format_table(parse("1 GB / s").unwrap());
+    assert!(table.starts_with("  1.000 GB / sec\n 60.000 GB / min\n"));
+    assert_eq!(table.lines().count(), PERIODS.len());
+}
@@ -0,0 +1,345 @@
+//! `rate log`: throughput over time from timestamped transfer logs.
+
+use std::io::Read;
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::time::{format_timestamp, parse_timestamp};
+use crate::{
+    format_rate, format_size, optional_arg, parse_duration, parse_size, Error, ParseError,
+    ParseErrorKind, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " log [<file>] [--window <duration>] [--time-column <n>] [--bytes-column <n>]\n",
+    "       [--delimiter <char>] [--pattern <template>]\n",
+    "       Each line has a timestamp (Unix seconds or ISO 8601) and a byte\n",
+    "       count (plain or with a unit). Columns are 1-based and separated\n",
+    "       by whitespace unless --delimiter is given. Defaults: --window 1m,\n",
+    "       --time-column 1, --bytes-column 2. Lines of JSON, as written by\n",
+    "       the --log option of pipe and fetch, are read by field instead.\n",
+    "       --pattern reads the lines that match a template instead, e.g.\n",
+    "       '[{time}] * sent {bytes} bytes': {time} and {bytes} take the text\n",
+    "       up to what follows them (one word at the end), * skips text, and\n",
+    "       the rest matches as is from the start of the line."
+);
+
+/// The most windows a log is split into, so that a typo in a timestamp
+/// doesn't allocate one for every minute since 1970.
+const MAX_WINDOWS: f64 = 1e6;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let window: f64 = pargs
+        .opt_value_from_fn("--window", parse_duration)?
+        .unwrap_or(60.0);
+    let time_column: usize = pargs.opt_value_from_str("--time-column")?.unwrap_or(1);
+    let bytes_column: usize = pargs.opt_value_from_str("--bytes-column")?.unwrap_or(2);
+    let delimiter: Option<char> = pargs.opt_value_from_str("--delimiter")?;
+    let pattern: Option<Vec<Token>> = pargs.opt_value_from_fn("--pattern", parse_pattern)?;
+    let mut out = output(&mut pargs)?;
+    let path = optional_arg(pargs)?;
+    if window <= 0.0 || time_column == 0 || bytes_column == 0 {
+        return Err(Error::Usage(messages::text(
+            Message::WindowColumnsPositive,
+            &[],
+        )));
+    }
+
+    let mut text = String::new();
+    match path {
+        Some(path) => text = std::fs::read_to_string(path)?,
+        None => _ = std::io::stdin().read_to_string(&mut text)?,
+    }
+    let mut samples: Vec<(f64, f64)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if let Some(pattern) = &pattern {
+            let Some((time, bytes)) = match_pattern(pattern, line) else {
+                continue;
+            };
+            let Some(time) = parse_timestamp(time) else {
+                return Err(Error::Usage(messages::text(
+                    Message::LineNoTimestamp,
+                    &[&(i + 1)],
+                )));
+            };
+            let bytes = parse_bytes(bytes).map_err(|e| Error::Line(i + 1, bytes.to_string(), e))?;
+            samples.push((time, bytes));
+            continue;
+        }
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.trim_start().starts_with('{') {
+            let time = json_field(line, "timestamp").and_then(parse_timestamp);
+            let bytes = json_field(line, "bytes").and_then(|v| parse_bytes(v).ok());
+            let (Some(time), Some(bytes)) = (time, bytes) else {
+                return Err(Error::Usage(messages::text(
+                    Message::LineJsonFields,
+                    &[&(i + 1)],
+                )));
+            };
+            samples.push((time, bytes));
+            continue;
+        }
+        let columns: Vec<&str> = match delimiter {
+            Some(d) => line.split(d).map(str::trim).collect(),
+            None => line.split_whitespace().collect(),
+        };
+        let Some(time) = columns
+            .get(time_column - 1)
+            .and_then(|c| parse_timestamp(c))
+        else {
+            return Err(Error::Usage(messages::text(
+                Message::LineNoTimestamp,
+                &[&(i + 1)],
+            )));
+        };
+        let bytes = match columns.get(bytes_column - 1) {
+            Some(c) => parse_bytes(c).map_err(|e| Error::Line(i + 1, c.to_string(), e))?,
+            None => {
+                let e = ParseErrorKind::InvalidNumber.into();
+                return Err(Error::Line(i + 1, line.to_string(), e));
+            }
+        };
+        samples.push((time, bytes));
+    }
+    if samples.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NoSamplesInLog, &[])));
+    }
+
+    let (first, last) = samples
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), s| {
+            (a.min(s.0), b.max(s.0))
+        });
+    if (last - first) / window > MAX_WINDOWS {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyWindows,
+            &[
+                &format_timestamp(first),
+                &format_timestamp(last),
+                &MAX_WINDOWS,
+            ],
+        )));
+    }
+    let windows = bucket(&samples, window);
+    let mut peak = 0;
+    for (i, (start, bytes)) in windows.iter().enumerate() {
+        let time = format_timestamp(*start);
+        out.line(
+            &format!("{}  {}", time, format_rate(bytes / window)),
+            &[
+                ("time", Value::Text(&time)),
+                ("rate", Value::Rate(Rate::from_decimal(bytes / window))),
+            ],
+        )?;
+        if *bytes > windows[peak].1 {
+            peak = i;
+        }
+    }
+    let total: f64 = windows.iter().map(|(_, b)| b).sum();
+    let average = total / (windows.len() as f64 * window);
+    let peak_time = format_timestamp(windows[peak].0);
+    let text = [
+        String::new(),
+        format!("total   : {}", format_size(total)),
+        format!("average : {}", format_rate(average)),
+        format!(
+            "peak    : {} ({})",
+            format_rate(windows[peak].1 / window),
+            peak_time
+        ),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("total", Value::Size(total)),
+            ("average", Value::Rate(Rate::from_decimal(average))),
+            (
+                "peak",
+                Value::Rate(Rate::from_decimal(windows[peak].1 / window)),
+            ),
+            ("peak_time", Value::Text(&peak_time)),
+        ],
+    )?;
+    return Ok(());
+}
+
+fn parse_bytes(s: &str) -> Result<f64, ParseError> {
+    match s.parse::<f64>() {
+        Ok(b) if b.is_finite() && b >= 0.0 => return Ok(b),
+        Ok(_) => return Err(ParseErrorKind::InvalidNumber.into()),
+        Err(_) => return parse_size(s),
+    }
+}
+
+/// A part of a `--pattern` template.
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Time,
+    Bytes,
+    /// `*`: any text.
+    Any,
+}
+
+fn parse_pattern(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if rest.starts_with("{time}") {
+            (Token::Time, "{time}".len())
+        } else if rest.starts_with("{bytes}") {
+            (Token::Bytes, "{bytes}".len())
+        } else if c == '*' {
+            (Token::Any, 1)
+        } else {
+            // Up to the next capture or *; other braces are text.
+            let len = rest[c.len_utf8()..]
+                .find(['{', '*'])
+                .map_or(rest.len(), |i| i + c.len_utf8());
+            (Token::Text(rest[..len].to_string()), len)
+        };
+        rest = &rest[len..];
+        match (tokens.last_mut(), token) {
+            (Some(Token::Text(last)), Token::Text(text)) => last.push_str(&text),
+            (
+                Some(Token::Time | Token::Bytes | Token::Any),
+                Token::Time | Token::Bytes | Token::Any,
+            ) => {
+                return Err(format!(
+                    "{:?}: {{time}}, {{bytes}} and * must be separated by text",
+                    s
+                ));
+            }
+            (_, token) => tokens.push(token),
+        }
+    }
+    let count = |token: Token| tokens.iter().filter(|t| **t == token).count();
+    if count(Token::Time) != 1 || count(Token::Bytes) != 1 {
+        return Err(format!(
+            "{:?}: expected {{time}} and {{bytes}} once each",
+            s
+        ));
+    }
+    return Ok(tokens);
+}
+
+/// The timestamp and the byte count of `line`, if it matches `pattern`.
+fn match_pattern<'a>(pattern: &[Token], line: &'a str) -> Option<(&'a str, &'a str)> {
+    let (mut time, mut bytes) = (None, None);
+    let mut rest = line;
+    for (i, token) in pattern.iter().enumerate() {
+        if let Token::Text(text) = token {
+            rest = rest.strip_prefix(text.as_str())?;
+            continue;
+        }
+        let end = match pattern.get(i + 1) {
+            Some(Token::Text(next)) => rest.find(next.as_str())?,
+            _ => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        let value = rest[..end].trim();
+        match token {
+            Token::Time => time = Some(value),
+            Token::Bytes => bytes = Some(value),
+            _ => {}
+        }
+        rest = &rest[end..];
+    }
+    return Some((time?, bytes?));
+}
+
+/// Finds the value of a top-level field in a flat JSON object, as
+/// written by `SampleLog`.
+fn json_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\":", name);
+    let start = line.find(&key)? + key.len();
+    let rest = &line[start..];
+    let end = rest.find([',', '}']).unwrap_or(rest.len());
+    return Some(rest[..end].trim());
+}
+
+/// Sums the bytes of the samples into consecutive windows aligned on
+/// multiples of `window`, including the empty windows in between.
+fn bucket(samples: &[(f64, f64)], window: f64) -> Vec<(f64, f64)> {
+    let first = samples.iter().map(|s| s.0).fold(f64::INFINITY, f64::min);
+    let last = samples
+        .iter()
+        .map(|s| s.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let start = (first / window).floor() * window;
+    let count = ((last - start) / window).floor() as usize + 1;
+    let mut windows: Vec<(f64, f64)> = (0..count)
+        .map(|i| (start + i as f64 * window, 0.0))
+        .collect();
+    for (time, bytes) in samples {
+        let i = ((time - start) / window).floor() as usize;
+        windows[i.min(count - 1)].1 += bytes;
+    }
+    return windows;
+}
+
+#[test]
+fn test_json_field() {
+    let line = r#"{"timestamp":1700000000.5,"bytes":4096,"total":8192}"#;
+    assert_eq!(json_field(line, "timestamp"), Some("1700000000.5"));
+    assert_eq!(json_field(line, "total"), Some("8192"));
+    assert_eq!(json_field(line, "average"), None);
+}
+
+#[test]
+fn test_bucket() {
+    let samples = [(60.0, 10.0), (90.0, 20.0), (200.0, 5.0)];
+    assert_eq!(
+        bucket(&samples, 60.0),
+        vec![(60.0, 30.0), (120.0, 0.0), (180.0, 5.0)]
+    );
+}
+
+#[test]
+fn test_pattern() {
+    let pattern = parse_pattern("[{time}] * sent {bytes}").unwrap();
+    assert_eq!(
+        pattern,
+        [
+            Token::Text(String::from("[")),
+            Token::Time,
+            Token::Text(String::from("] ")),
+            Token::Any,
+            Token::Text(String::from(" sent ")),
+            Token::Bytes,
+        ]
+    );
+    let line = "[2024-01-02T03:04:05Z] host-1 sent 12MB to backup";
+    assert_eq!(
+        match_pattern(&pattern, line),
+        Some(("2024-01-02T03:04:05Z", "12MB"))
+    );
+    assert_eq!(
+        match_pattern(&pattern, "[1700000000] host-1 received 12MB"),
+        None
+    );
+    assert_eq!(match_pattern(&pattern, "x [1700000000] a sent 1"), None);
+    let pattern = parse_pattern("{time} {bytes} bytes").unwrap();
+    assert_eq!(
+        match_pattern(&pattern, "1700000000 4096 bytes"),
+        Some(("1700000000", "4096"))
+    );
+    assert!(parse_pattern("{time}{bytes}").is_err());
+    assert!(parse_pattern("{time} *{bytes}").is_err());
+    assert!(parse_pattern("{time} {time} {bytes}").is_err());
+    assert!(parse_pattern("{bytes} at {when}").is_err());
+}
+
+#[test]
+fn test_parse_bytes() {
+    assert_eq!(parse_bytes("4096"), Ok(4096.0));
+    assert_eq!(parse_bytes("4 KB"), Ok(4000.0));
+    assert!(parse_bytes("nan").is_err());
+    assert!(parse_bytes("inf").is_err());
+    assert!(parse_bytes("-5").is_err());
+}
@@ -1,25 +1,412 @@
+#![allow(clippy::needless_return)]
+
+mod assert;
+mod bench_disk;
+mod bench_net;
+mod bond;
+mod bottleneck;
+mod bucket;
+mod burstable;
+mod carbon;
+mod catchup;
+mod cctv;
+mod cdn;
+mod cellular;
+mod cgroup;
+mod chunked;
+mod clipboard;
+mod color;
+mod config;
+mod countdown;
+mod daemon;
+mod dedup;
+mod diff;
+mod duplex;
+mod egress;
+mod energy;
+mod fetch;
+mod fill;
+mod goodput;
+mod graph;
+mod ingest;
+mod ladder;
+mod log;
+mod membw;
+mod messages;
+mod migrate;
+mod minmax;
+mod parse_tool;
+mod pcie;
+mod pipe;
+mod plan;
+mod pps;
+mod progress;
+mod prom;
+mod query;
+mod rebuild;
+mod reparse;
+mod repo;
+mod report;
+mod s3;
+mod sampler;
+mod schedule;
+mod simulate;
+mod standard;
+mod stats;
+mod tape;
+mod tcp_model;
+mod telemetry;
+mod time;
+mod topic;
+mod utilization;
+mod wifi;
+
 use std::env;
+use std::ffi::OsString;
 use std::process::exit;
 
+use rate::sink::{OutputSink, Value};
+use rate::{
+    format_bitrate, format_count, format_duration, format_rate, format_size, month, parse_duration,
+    parse_frequency, parse_percent, parse_quantity, parse_size, period_to_seconds, ParseError,
+    ParseErrorKind, Period, Rate, RateEstimator, TokenBucket, BUSINESS_PERIODS, DAY, HOUR, PERIODS,
+    SECOND, UNITS, YEAR,
+};
+
 const PROG_NAME: &str = env!("CARGO_BIN_NAME");
-const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
-const SECOND: u64 = 1;
-const MINUTE: u64 = 60 * SECOND;
-const HOUR: u64 = 60 * MINUTE;
-const DAY: u64 = 24 * HOUR;
-const WEEK: u64 = 7 * DAY;
-const MONTH: u64 = 30 * DAY;
-const YEAR: u64 = 365 * DAY;
-const PERIODS: &[u64] = &[SECOND, MINUTE, HOUR, DAY, WEEK, MONTH, YEAR];
-const PERIOD_NAMES: &[&str] = &["sec", "min", "hour", "day", "week", "month", "year"];
+
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    run: fn(pico_args::Arguments) -> Result<(), Error>,
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "assert",
+        usage: assert::USAGE,
+        run: assert::run,
+    },
+    Command {
+        name: "bench-disk",
+        usage: bench_disk::USAGE,
+        run: bench_disk::run,
+    },
+    Command {
+        name: "bench-net",
+        usage: bench_net::USAGE,
+        run: bench_net::run,
+    },
+    Command {
+        name: "bond",
+        usage: bond::USAGE,
+        run: bond::run,
+    },
+    Command {
+        name: "bottleneck",
+        usage: bottleneck::USAGE,
+        run: bottleneck::run,
+    },
+    Command {
+        name: "bucket",
+        usage: bucket::USAGE,
+        run: bucket::run,
+    },
+    Command {
+        name: "burstable",
+        usage: burstable::USAGE,
+        run: burstable::run,
+    },
+    Command {
+        name: "carbon",
+        usage: carbon::USAGE,
+        run: carbon::run,
+    },
+    Command {
+        name: "catchup",
+        usage: catchup::USAGE,
+        run: catchup::run,
+    },
+    Command {
+        name: "cctv",
+        usage: cctv::USAGE,
+        run: cctv::run,
+    },
+    Command {
+        name: "cdn",
+        usage: cdn::USAGE,
+        run: cdn::run,
+    },
+    Command {
+        name: "cellular",
+        usage: cellular::USAGE,
+        run: cellular::run,
+    },
+    Command {
+        name: "cgroup",
+        usage: cgroup::USAGE,
+        run: cgroup::run,
+    },
+    Command {
+        name: "chunked",
+        usage: chunked::USAGE,
+        run: chunked::run,
+    },
+    Command {
+        name: "config",
+        usage: config::USAGE,
+        run: config::run,
+    },
+    Command {
+        name: "countdown",
+        usage: countdown::USAGE,
+        run: countdown::run,
+    },
+    Command {
+        name: "daemon",
+        usage: daemon::USAGE,
+        run: daemon::run,
+    },
+    Command {
+        name: "dedup",
+        usage: dedup::USAGE,
+        run: dedup::run,
+    },
+    Command {
+        name: "diff",
+        usage: diff::USAGE,
+        run: diff::run,
+    },
+    Command {
+        name: "duplex",
+        usage: duplex::USAGE,
+        run: duplex::run,
+    },
+    Command {
+        name: "egress",
+        usage: egress::USAGE,
+        run: egress::run,
+    },
+    Command {
+        name: "energy",
+        usage: energy::USAGE,
+        run: energy::run,
+    },
+    Command {
+        name: "fetch",
+        usage: fetch::USAGE,
+        run: fetch::run,
+    },
+    Command {
+        name: "fill",
+        usage: fill::USAGE,
+        run: fill::run,
+    },
+    Command {
+        name: "goodput",
+        usage: goodput::USAGE,
+        run: goodput::run,
+    },
+    Command {
+        name: "graph",
+        usage: graph::USAGE,
+        run: graph::run,
+    },
+    Command {
+        name: "ingest",
+        usage: ingest::USAGE,
+        run: ingest::run,
+    },
+    Command {
+        name: "ladder",
+        usage: ladder::USAGE,
+        run: ladder::run,
+    },
+    Command {
+        name: "log",
+        usage: log::USAGE,
+        run: log::run,
+    },
+    Command {
+        name: "membw",
+        usage: membw::USAGE,
+        run: membw::run,
+    },
+    Command {
+        name: "migrate",
+        usage: migrate::USAGE,
+        run: migrate::run,
+    },
+    Command {
+        name: "minmax",
+        usage: minmax::USAGE,
+        run: minmax::run,
+    },
+    Command {
+        name: "parse-tool",
+        usage: parse_tool::USAGE,
+        run: parse_tool::run,
+    },
+    Command {
+        name: "pcie",
+        usage: pcie::USAGE,
+        run: pcie::run,
+    },
+    Command {
+        name: "pipe",
+        usage: pipe::USAGE,
+        run: pipe::run,
+    },
+    Command {
+        name: "plan",
+        usage: plan::USAGE,
+        run: plan::run,
+    },
+    Command {
+        name: "pps",
+        usage: pps::USAGE,
+        run: pps::run,
+    },
+    Command {
+        name: "progress",
+        usage: progress::USAGE,
+        run: progress::run,
+    },
+    Command {
+        name: "prom",
+        usage: prom::USAGE,
+        run: prom::run,
+    },
+    Command {
+        name: "query",
+        usage: query::USAGE,
+        run: query::run,
+    },
+    Command {
+        name: "rebuild",
+        usage: rebuild::USAGE,
+        run: rebuild::run,
+    },
+    Command {
+        name: "reparse",
+        usage: reparse::USAGE,
+        run: reparse::run,
+    },
+    Command {
+        name: "repo",
+        usage: repo::USAGE,
+        run: repo::run,
+    },
+    Command {
+        name: "s3",
+        usage: s3::USAGE,
+        run: s3::run,
+    },
+    Command {
+        name: "schedule",
+        usage: schedule::USAGE,
+        run: schedule::run,
+    },
+    Command {
+        name: "simulate",
+        usage: simulate::USAGE,
+        run: simulate::run,
+    },
+    Command {
+        name: "standard",
+        usage: standard::USAGE,
+        run: standard::run,
+    },
+    Command {
+        name: "stats",
+        usage: stats::USAGE,
+        run: stats::run,
+    },
+    Command {
+        name: "tape",
+        usage: tape::USAGE,
+        run: tape::run,
+    },
+    Command {
+        name: "tcp-model",
+        usage: tcp_model::USAGE,
+        run: tcp_model::run,
+    },
+    Command {
+        name: "telemetry",
+        usage: telemetry::USAGE,
+        run: telemetry::run,
+    },
+    Command {
+        name: "topic",
+        usage: topic::USAGE,
+        run: topic::run,
+    },
+    Command {
+        name: "utilization",
+        usage: utilization::USAGE,
+        run: utilization::run,
+    },
+    Command {
+        name: "wifi",
+        usage: wifi::USAGE,
+        run: wifi::run,
+    },
+];
 
 fn main() {
+    // --color, --lang, --profile and --month-days apply to every command,
+    // so they are taken out first.
     let mut pargs = pico_args::Arguments::from_env();
-    if pargs.contains(["-h", "--help"]) || env::args().len() == 1 {
-        println!("Usage: {} <number> <unit> / <period>", PROG_NAME);
-        println!("       <number>: integer or float (no scientific notation)");
-        println!("       <unit>  : {}", UNITS.join(" "));
-        println!("       <period>: {}", PERIOD_NAMES.join(" "));
+    let color: Option<color::ColorChoice> = match pargs.opt_value_from_str("--color") {
+        Ok(color) => color,
+        Err(e) => fail(e.into()),
+    };
+    color::init(color.unwrap_or(color::ColorChoice::Auto));
+    let lang: Option<String> = match pargs.opt_value_from_str("--lang") {
+        Ok(lang) => lang,
+        Err(e) => fail(e.into()),
+    };
+    if let Err(e) = messages::init(lang.as_deref()) {
+        fail(Error::Usage(e));
+    }
+    let profile: Option<String> = match pargs.opt_value_from_str("--profile") {
+        Ok(profile) => profile,
+        Err(e) => fail(e.into()),
+    };
+    let month_days: Option<String> = match pargs.opt_value_from_str("--month-days") {
+        Ok(days) => days,
+        Err(e) => fail(e.into()),
+    };
+    let mut args: Vec<OsString> = pargs.finish();
+    // `rate config` reads the file itself: `rate config path` still tells
+    // where a broken file is.
+    if args.first().is_none_or(|a| a != "config") {
+        if let Err(e) = config::load(profile.as_deref(), month_days.as_deref()) {
+            fail(e);
+        }
+    }
+    let command = args
+        .first()
+        .and_then(|a| a.to_str())
+        .and_then(|a| COMMANDS.iter().find(|c| c.name == a));
+    if let Some(command) = command {
+        args.remove(0);
+        let mut pargs = pico_args::Arguments::from_vec(args);
+        if pargs.contains(["-h", "--help"]) {
+            println!("{}", command.usage);
+            exit(0);
+        }
+        if let Err(e) = (command.run)(pargs) {
+            fail(e);
+        }
+        exit(0);
+    }
+
+    let no_args = args.is_empty();
+    let mut pargs = pico_args::Arguments::from_vec(args);
+    if pargs.contains(["-h", "--help"]) || no_args {
+        print_usage();
         exit(0);
     }
     if pargs.contains(["-v", "--version"]) {
@@ -27,325 +414,510 @@ fn main() {
         exit(0);
     }
 
-    let mut s = String::new();
-    let mut sep: &str = "";
-    for a in env::args().skip(1) {
-        s.push_str(sep);
-        s.push_str(a.as_str());
-        sep = " ";
-    }
-    match parse(&s) {
-        Ok(bytes_per_second) => {
-            for i in 0..PERIODS.len() {
-                let period = PERIODS[i];
-                let period_name = PERIOD_NAMES[i];
-                let (rate, unit) = nearest_power_of_1000_rate(bytes_per_second * period as f64);
-                println!("{:>7.3?} {:>2} / {}", rate, unit, period_name);
-            }
-        }
-        Err(e) => {
-            eprintln!("{}: {}", PROG_NAME, e);
-            exit(1);
-        }
+    if let Err(e) = convert(pargs) {
+        fail(e);
     }
 }
 
-fn nearest_power_of_1000_rate(mut bytes: f64) -> (f64, &'static str) {
-    for unit in UNITS {
-        if bytes < 1000.0 {
-            return (bytes, unit);
-        }
-        bytes /= 1000.0;
+/// Prints the help of the default command.
+fn print_usage() {
+    use messages::{text, Message};
+    let usage = text(Message::Usage, &[]);
+    let indent = " ".repeat(usage.chars().count() + 2);
+    println!("{}: {} <number> <unit> / <period>", usage, PROG_NAME);
+    println!("{}{} <command> [args...]", indent, PROG_NAME);
+    println!("{}<number>: {}", indent, text(Message::HelpNumber, &[]));
+    let symbols: Vec<&str> = UNITS.iter().map(|u| u.symbol).collect();
+    println!("{}<unit>  : {}", indent, symbols.join(" "));
+    println!("{}          {}", indent, text(Message::HelpBits, &[]));
+    let periods: Vec<&str> = PERIODS.iter().map(|p| p.name).collect();
+    println!("{}<period>: {}", indent, periods.join(" "));
+    let business: Vec<&str> = BUSINESS_PERIODS.iter().map(|p| p.name).collect();
+    println!("{}          {}", indent, business.join(" "));
+    let options = [
+        ("--compress <ratio>", Message::HelpCompress),
+        ("--output <format>", Message::HelpOutput),
+        ("--precision <n>", Message::HelpPrecision),
+        ("--units <system>", Message::HelpUnits),
+        ("--periods <list>", Message::HelpPeriods),
+        ("--bits", Message::HelpBitsOption),
+        ("--nice", Message::HelpNice),
+        ("--explain", Message::HelpExplain),
+        ("--normalize", Message::HelpNormalize),
+        ("--copy", Message::HelpCopy),
+        ("--copy-format <format>", Message::HelpCopyFormat),
+        ("--month-days <n>", Message::HelpMonthDays),
+        ("--profile <name>", Message::HelpProfile),
+        ("--color <when>", Message::HelpColor),
+        ("--lang <language>", Message::HelpLang),
+    ];
+    for (option, message) in options {
+        println!("{}{}: {}", indent, option, text(message, &[]));
+    }
+    if let Some(path) = config::path() {
+        println!(
+            "{}{}",
+            indent,
+            text(Message::HelpDefaults, &[&path.display()])
+        );
+        println!("{}{}", indent, text(Message::HelpEnvironment, &[]));
     }
-    // If you didn't fit in yottabytes, you might as well be considered infinite.
-    return (f64::INFINITY, "B");
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    let commands = text(Message::HelpCommand, &[&names.join(" "), &PROG_NAME]);
+    println!("{}<command>: {}", indent, commands);
+    for line in text(Message::HelpExitStatus, &[]).lines() {
+        println!("{}{}", indent, line);
+    }
+}
+
+/// Reports the error and exits with its status.
+fn fail(e: Error) -> ! {
+    eprintln!("{}: {}", PROG_NAME, e);
+    exit(e.exit_code());
 }
 
-fn period_to_seconds(period_name: &str) -> Result<u64, ParseError> {
-    match period_name {
-        "s" | "sec" | "second" => Ok(SECOND),
-        "m" | "min" | "minute" => Ok(MINUTE),
-        "h" | "hr" | "hour" => Ok(HOUR),
-        "d" | "day" => Ok(DAY),
-        "w" | "wk" | "week" => Ok(WEEK),
-        "mon" | "month" => Ok(MONTH),
-        "y" | "yr" | "year" => Ok(YEAR),
-        _ => Err(ParseError::InvalidPeriod),
+/// The default command: the table of a single rate over every period.
+fn convert(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let compression = compression_option(&mut pargs)?;
+    let config = config::get().clone().options(&mut pargs)?;
+    let explain = pargs.contains("--explain");
+    let normalize = pargs.contains("--normalize");
+    let copy = pargs.contains("--copy");
+    let copy_format = pargs.opt_value_from_fn("--copy-format", |s| match s {
+        "value" => Ok(s.to_string()),
+        _ => config::output_format(s),
+    })?;
+    let input = remaining_args(pargs)?;
+    let input = config.alias(&input).unwrap_or(&input).to_string();
+    let expression = rate::parse_expression(&input).map_err(|e| Error::Input(input.clone(), e))?;
+    let mut out = config.sink();
+    if explain {
+        print_explanation(out.as_mut(), &expression, compression, &config)?;
+    }
+    let rate = expression.rate * compression;
+    if normalize {
+        let text = normalized(&expression, rate, &config);
+        out.line(&text, &[("rate", Value::Rate(rate))])?;
+        return Ok(());
     }
+    // Amounts are kept in 128 bits: past that, a row would be infinite.
+    let periods = config.table_periods();
+    if let Some(period) = periods.iter().find(|p| rate.bytes_per(**p).is_infinite()) {
+        return Err(Error::Usage(messages::text(
+            messages::Message::TooLarge,
+            &[&input.trim(), &period.name],
+        )));
+    }
+    out.table(rate)?;
+    if copy || copy_format.is_some() {
+        let format = copy_format.as_deref().or(config.output.as_deref());
+        clipboard::copy(&copy_text(&config, format, rate)?)?;
+    }
+    return Ok(());
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum ParseError {
-    InvalidNumber,
-    InvalidUnit,
-    InvalidPeriod,
-    UnexpectedCharacter { expected: u8, actual: u8 },
+/// The result that `--copy` puts on the clipboard: the table in the
+/// output format `format`, or its first line only with "value".
+fn copy_text(config: &config::Config, format: Option<&str>, rate: Rate) -> Result<String, Error> {
+    if format == Some("value") {
+        return Ok(config.formatter().format(rate));
+    }
+    let mut out: Vec<u8> = Vec::new();
+    config.sink_to(format, &mut out).table(rate)?;
+    return Ok(String::from_utf8_lossy(&out).into_owned());
 }
 
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidNumber => write!(f, "not a valid number"),
-            Self::InvalidUnit => write!(f, "not a recognized unit ({})", UNITS.join(" ")),
-            Self::InvalidPeriod => write!(
-                f,
-                "not a recognized time period ({})",
-                PERIOD_NAMES.join(" ")
+/// Writes how the table is computed from the parts of the expression,
+/// for text output only.
+fn print_explanation(
+    out: &mut dyn OutputSink,
+    e: &rate::Expression,
+    compression: f64,
+    config: &config::Config,
+) -> Result<(), Error> {
+    out.line(&format!("number   : {}", e.number), &[])?;
+    let unit_bytes = if e.bits {
+        let bytes = e.unit_bytes as f64 / 8.0;
+        out.line(
+            &format!(
+                "unit     : {} = {} bits = {} bytes",
+                e.unit,
+                e.unit_bytes,
+                plain(bytes)
             ),
-            Self::UnexpectedCharacter { expected, actual } => {
-                write!(
-                    f,
-                    "expected {:?}, found {:?}",
-                    *expected as char, *actual as char
-                )
-            }
-        }
+            &[],
+        )?;
+        bytes
+    } else {
+        out.line(
+            &format!("unit     : {} = {} bytes", e.unit, e.unit_bytes),
+            &[],
+        )?;
+        e.unit_bytes as f64
+    };
+    out.line(
+        &format!("period   : {} = {} seconds", e.period, e.seconds),
+        &[],
+    )?;
+    let mut bytes_per_second = e.rate.bytes_per_second();
+    out.line(
+        &format!(
+            "rate     : {} * {} B / {} s = {} B/s",
+            e.number,
+            plain(unit_bytes),
+            e.seconds,
+            plain(bytes_per_second)
+        ),
+        &[],
+    )?;
+    if compression != 1.0 {
+        let before = bytes_per_second;
+        bytes_per_second *= compression;
+        out.line(
+            &format!(
+                "compress : {} B/s * {} = {} B/s",
+                plain(before),
+                plain(compression),
+                plain(bytes_per_second)
+            ),
+            &[],
+        )?;
     }
+    let rate = e.rate * compression;
+    let periods = config.table_periods();
+    let width = periods
+        .iter()
+        .map(|p| p.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(8);
+    for period in periods {
+        let amount = config.formatter().periods(&[period]).format(rate);
+        let amount = amount
+            .rsplit_once(" / ")
+            .map_or(amount.as_str(), |(a, _)| a);
+        out.line(
+            &format!(
+                "{:<width$} : {} B/s * {} s = {} B = {}",
+                period.name,
+                plain(bytes_per_second),
+                period.seconds,
+                plain(bytes_per_second * period.seconds as f64),
+                amount.trim()
+            ),
+            &[],
+        )?;
+    }
+    out.line("", &[])?;
+    return Ok(());
 }
 
-fn parse(s: &str) -> Result<f64, ParseError> {
-    let mut p = Parser {
-        buf: s.as_bytes(),
-        pos: 0,
+/// The canonical spelling of a rate over the period of its expression,
+/// e.g. "12.000 MB/s" for "12000 KB / sec", in the units and precision of
+/// the table.
+fn normalized(e: &rate::Expression, rate: Rate, config: &config::Config) -> String {
+    let custom = config
+        .custom_periods
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(e.period));
+    let period = match (e.period.parse::<Period>(), custom) {
+        (Ok(period), _) => period,
+        (Err(_), Some(period)) => *period,
+        (Err(_), None) => PERIODS[0],
+    };
+    let amount = config.formatter().periods(&[period]).format(rate);
+    let amount = amount
+        .rsplit_once(" / ")
+        .map_or(amount.as_str(), |(a, _)| a);
+    let name = match period.seconds {
+        SECOND => "s",
+        _ => period.name,
     };
-    p.skip_whitespace();
-    let rate: f64 = p.parse_number()?;
-    p.skip_whitespace();
-    let byte_multiplier: f64 = p.parse_bytes()?;
-    p.skip_whitespace();
-    p.expect(b'/')?;
-    p.skip_whitespace();
-    let seconds: f64 = p.parse_period()?;
-    return Ok(rate * byte_multiplier / seconds);
+    return format!("{}/{}", amount, name);
 }
 
-struct Parser<'a> {
-    buf: &'a [u8],
-    pos: usize,
+/// Formats a number with at most three decimals, without trailing zeros.
+fn plain(n: f64) -> String {
+    let s = format!("{:.3}", n);
+    return s.trim_end_matches('0').trim_end_matches('.').to_string();
+}
+
+/// Parses a rate with the library's grammar, or the name of an alias
+/// from the configuration. The subcommands do their arithmetic on plain
+/// bytes per second.
+fn parse(s: &str) -> Result<f64, ParseError> {
+    let s = config::get().alias(s).unwrap_or(s);
+    return Ok(rate::parse(s)?.bytes_per_second());
 }
 
-impl<'a> Parser<'a> {
-    fn peek(&self) -> u8 {
-        if self.pos >= self.buf.len() {
-            return 0;
+/// Reads the `--compress` option shared by the commands that turn a
+/// transfer rate into an amount of data or a duration. The factor it
+/// returns multiplies a rate on the wire into the logical rate.
+fn compression_option(pargs: &mut pico_args::Arguments) -> Result<f64, Error> {
+    let factor = pargs.opt_value_from_fn("--compress", parse_compression)?;
+    return Ok(factor.unwrap_or(1.0));
+}
+
+/// Parses a compression ratio, either as "<logical>:<compressed>" (e.g.,
+/// "2.3:1") or as the compressed size in percent of the original (e.g.,
+/// "57%"), and returns how many logical bytes each byte sent stands for.
+fn parse_compression(s: &str) -> Result<f64, ParseError> {
+    let factor = match s.split_once(':') {
+        Some((logical, compressed)) => {
+            let logical: f64 = logical
+                .trim()
+                .parse()
+                .map_err(|_| ParseErrorKind::InvalidNumber)?;
+            let compressed: f64 = compressed
+                .trim()
+                .parse()
+                .map_err(|_| ParseErrorKind::InvalidNumber)?;
+            logical / compressed
         }
-        return self.buf[self.pos];
+        None if s.trim().ends_with('%') => 1.0 / parse_percent(s)?,
+        None => return Err(ParseErrorKind::InvalidNumber.into()),
+    };
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(ParseErrorKind::InvalidNumber.into());
     }
+    return Ok(factor);
+}
 
-    fn eof(&self) -> bool {
-        return self.peek() == 0;
-    }
+/// Errors that a subcommand can report back to `main`.
+#[derive(Debug)]
+enum Error {
+    Parse(ParseError),
+    /// A parse error in a known input, shown with a caret under the
+    /// position at fault.
+    Input(String, ParseError),
+    Args(pico_args::Error),
+    Usage(String),
+    Assertion(String),
+    /// A parse error in a line of input, shown like `Input`.
+    Line(usize, String, ParseError),
+    Io(std::io::Error),
+}
 
-    fn advance(&mut self) -> u8 {
-        let b = self.peek();
-        if b == 0 {
-            return b;
+impl Error {
+    /// The exit status of the program for this error, so that scripts can
+    /// tell what went wrong.
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Parse(e) | Self::Input(_, e) | Self::Line(_, _, e) => match e.kind {
+                ParseErrorKind::InvalidNumber => return 3,
+                ParseErrorKind::InvalidUnit => return 4,
+                ParseErrorKind::InvalidPeriod => return 5,
+                ParseErrorKind::UnexpectedCharacter { .. } => return 6,
+                ParseErrorKind::TrailingInput => return 7,
+            },
+            Self::Args(_) | Self::Usage(_) => return 2,
+            Self::Assertion(_) => return 1,
+            Self::Io(_) => return 8,
         }
-        self.pos += 1;
-        return b;
     }
+}
 
-    fn expect(&mut self, expected: u8) -> Result<u8, ParseError> {
-        let actual = self.advance();
-        if actual == expected {
-            return Ok(actual);
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", messages::parse_error(e.kind)),
+            Self::Input(s, e) => write_diagnostic(f, s, e),
+            Self::Args(e) => write!(f, "{}", e),
+            Self::Usage(s) => write!(f, "{}", s),
+            Self::Assertion(s) => {
+                let message = messages::text(messages::Message::AssertionFailed, &[s]);
+                write!(f, "{}", message)
+            }
+            Self::Line(n, s, e) => {
+                write!(f, "{}: ", messages::text(messages::Message::Line, &[n]))?;
+                write_diagnostic(f, s, e)
+            }
+            Self::Io(e) => write!(f, "{}", e),
         }
-        return Err(ParseError::UnexpectedCharacter { expected, actual });
     }
+}
 
-    fn skip_whitespace(&mut self) {
-        while !self.eof() && self.peek().is_ascii_whitespace() {
-            self.advance();
+/// Writes a parse error in `input`, with a suggestion if there is one,
+/// and the input with a caret under the position at fault.
+fn write_diagnostic(
+    f: &mut std::fmt::Formatter<'_>,
+    input: &str,
+    e: &ParseError,
+) -> std::fmt::Result {
+    write!(f, "{}", messages::parse_error(e.kind))?;
+    if let Some(suggestion) = e.suggestion(input) {
+        let suggestion = format!("{:?}", suggestion);
+        let message = messages::text(messages::Message::DidYouMean, &[&suggestion]);
+        write!(f, "; {}", message)?;
+    }
+    if let Some(underline) = e.underline(input) {
+        if let Some((line, carets)) = underline.split_once('\n') {
+            let indent = carets.len() - carets.trim_start().len();
+            let carets = color::paint(carets.trim_start(), color::RED, color::stderr());
+            write!(f, "\n    {}\n    {:indent$}{}", line, "", carets)?;
         }
     }
+    return Ok(());
+}
 
-    fn parse_number(&mut self) -> Result<f64, ParseError> {
-        let start_pos = self.pos;
-        while !self.eof() && self.peek().is_ascii_digit() {
-            self.advance();
-        }
-        // No digits
-        // NB(vincent): shouldn't ever trigger, we get inside `parse_number`
-        // because we saw a digit. Still gonna put it for good measure.
-        if start_pos == self.pos {
-            return Err(ParseError::InvalidNumber);
-        }
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        return Error::Parse(e);
+    }
+}
 
-        if self.peek() == b'.' {
-            self.advance(); // eat the '.'
-            let decimals_start = self.pos;
-            while !self.eof() && self.peek().is_ascii_digit() {
-                self.advance();
-            }
-            if decimals_start == self.pos {
-                return Err(ParseError::InvalidNumber);
-            }
-        }
+impl From<pico_args::Error> for Error {
+    fn from(e: pico_args::Error) -> Self {
+        return Error::Args(e);
+    }
+}
 
-        let s = unsafe { std::str::from_utf8_unchecked(&self.buf[start_pos..self.pos]) };
-        match s.parse::<f64>() {
-            Ok(x) => return Ok(x),
-            Err(_) => return Err(ParseError::InvalidNumber),
-        }
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        return Error::Io(e);
     }
+}
 
-    /// Parses strings like "B", "MB", "TB", etc. and returns how many
-    /// bytes that it (e.g., "B" -> 1, "MB" -> 1e6, "TB" -> 1e12).
-    fn parse_bytes(&mut self) -> Result<f64, ParseError> {
-        let start_pos = self.pos;
-        while !self.eof() && self.peek().is_ascii_alphabetic() {
-            self.advance();
+/// Splits "<left> <word> <right>" around a standalone keyword such as
+/// "at" or "of", so that both sides can be parsed separately.
+fn split_keyword<'a>(s: &'a str, keyword: &str) -> Option<(&'a str, &'a str)> {
+    for word in s.split_whitespace() {
+        if word.eq_ignore_ascii_case(keyword) {
+            let start = word.as_ptr() as usize - s.as_ptr() as usize;
+            let end = start + word.len();
+            return Some((s[..start].trim(), s[end..].trim()));
         }
-        let unit = (&self.buf[start_pos..self.pos]).to_ascii_uppercase();
-        for (i, candidate) in UNITS.iter().enumerate() {
-            if candidate.as_bytes() == unit {
-                return Ok(f64::powf(1000.0, i as f64));
-            }
-        }
-        return Err(ParseError::InvalidUnit);
     }
+    return None;
+}
 
-    fn parse_period(&mut self) -> Result<f64, ParseError> {
-        let start_pos = self.pos;
-        while !self.eof() && self.peek().is_ascii_alphabetic() {
-            self.advance();
+/// Parses `part`, a slice of the input `input` of a command, so that an
+/// error is shown under its position in `input`.
+fn parse_input<T>(
+    input: &str,
+    part: &str,
+    f: impl FnOnce(&str) -> Result<T, ParseError>,
+) -> Result<T, Error> {
+    let offset = part.as_ptr() as usize - input.as_ptr() as usize;
+    return f(part).map_err(|e| Error::Input(input.to_string(), e.shift(offset)));
+}
+
+/// Joins the arguments that pico_args did not consume into a single
+/// expression, rejecting anything that looks like an unknown flag.
+fn remaining_args(pargs: pico_args::Arguments) -> Result<String, Error> {
+    let mut s = String::new();
+    let mut sep: &str = "";
+    for a in pargs.finish() {
+        let a = a.to_string_lossy();
+        if a.starts_with('-') {
+            return Err(Error::Usage(messages::text(
+                messages::Message::UnknownOption,
+                &[&format!("{:?}", a)],
+            )));
         }
-        let period = unsafe { std::str::from_utf8_unchecked(&self.buf[start_pos..self.pos]) };
-        let period = period.to_ascii_lowercase();
-        let seconds = period_to_seconds(&period)? as f64;
-        return Ok(seconds);
+        s.push_str(sep);
+        s.push_str(&a);
+        sep = " ";
     }
+    return Ok(s);
 }
 
-#[test]
-fn test_parse_whitespaces() {
-    // See that we can put whitespaces pretty much everywhere
-    assert!(parse("1B/s").is_ok());
-    assert!(parse("1 B/s").is_ok());
-    assert!(parse("1B /s").is_ok());
-    assert!(parse("1B/ s").is_ok());
-    assert!(parse("1B / s").is_ok());
-    assert!(parse("1 B/ s").is_ok());
-    assert!(parse("1 B / s").is_ok());
-    assert!(parse(" 1 B / s ").is_ok());
+/// Returns the argument of a subcommand that takes at most one, e.g. a
+/// file, once its options are taken out. Other arguments and options
+/// left over are errors.
+fn optional_arg(pargs: pico_args::Arguments) -> Result<Option<String>, Error> {
+    let mut args: Vec<String> = Vec::new();
+    for a in pargs.finish() {
+        let a = a.to_string_lossy();
+        if a.starts_with('-') {
+            return Err(Error::Usage(messages::text(
+                messages::Message::UnknownOption,
+                &[&format!("{:?}", a)],
+            )));
+        }
+        args.push(a.into_owned());
+    }
+    if args.len() > 1 {
+        let extra = args[1..].join(" ");
+        return Err(Error::Usage(messages::text(
+            messages::Message::TooManyArguments,
+            &[&extra],
+        )));
+    }
+    return Ok(args.pop());
 }
 
-#[test]
-fn test_parse_units() {
-    // Try the different units and with different casing.
-    assert!(parse("1 b / s").is_ok());
-    assert!(parse("1 B / s").is_ok());
-    assert!(parse("1 kB / s").is_ok());
-    assert!(parse("1 Kb / s").is_ok());
-    assert!(parse("1 KB / s").is_ok());
-    assert!(parse("1 MB / s").is_ok());
-    assert!(parse("1 GB / s").is_ok());
-    assert!(parse("1 TB / s").is_ok());
-    assert!(parse("1 PB / s").is_ok());
-    assert!(parse("1 EB / s").is_ok());
-    assert!(parse("1 ZB / s").is_ok());
-    assert!(parse("1 YB / s").is_ok());
+/// Returns the inputs of a subcommand that works on lists: one per
+/// remaining argument, or one per line of stdin if there are none.
+/// Blank lines and lines starting with '#' are skipped; the line
+/// numbers are kept for error messages.
+fn input_lines(pargs: pico_args::Arguments) -> Result<Vec<(usize, String)>, Error> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let args = pargs.finish();
+    if !args.is_empty() {
+        for (i, a) in args.iter().enumerate() {
+            let a = a.to_string_lossy();
+            if a.starts_with('-') {
+                return Err(Error::Usage(messages::text(
+                    messages::Message::UnknownOption,
+                    &[&format!("{:?}", a)],
+                )));
+            }
+            lines.push((i + 1, a.into_owned()));
+        }
+        return Ok(lines);
+    }
+    for (i, line) in std::io::stdin().lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        lines.push((i + 1, trimmed.to_string()));
+    }
+    return Ok(lines);
 }
 
 #[test]
-fn test_parse_periods() {
-    // Try the different period spellings and with different casing.
-    assert!(parse("1 B / s").is_ok());
-    assert!(parse("1 B / S").is_ok());
-    assert!(parse("1 B / sec").is_ok());
-    assert!(parse("1 B / SEC").is_ok());
-    assert!(parse("1 B / SeC").is_ok());
-    assert!(parse("1 B / SEC").is_ok());
-    assert!(parse("1 B / second").is_ok());
-    assert!(parse("1 B / m").is_ok());
-    assert!(parse("1 B / min").is_ok());
-    assert!(parse("1 B / minute").is_ok());
-    assert!(parse("1 B / h").is_ok());
-    assert!(parse("1 B / hr").is_ok());
-    assert!(parse("1 B / hour").is_ok());
-    assert!(parse("1 B / d").is_ok());
-    assert!(parse("1 B / day").is_ok());
-    assert!(parse("1 B / w").is_ok());
-    assert!(parse("1 B / wk").is_ok());
-    assert!(parse("1 B / week").is_ok());
-    assert!(parse("1 B / mon").is_ok());
-    assert!(parse("1 B / month").is_ok());
-    assert!(parse("1 B / y").is_ok());
-    assert!(parse("1 B / yr").is_ok());
-    assert!(parse("1 B / year").is_ok());
+fn test_parse_compression() {
+    assert_eq!(parse_compression("2.5:1"), Ok(2.5));
+    assert_eq!(parse_compression("4:2"), Ok(2.0));
+    assert_eq!(parse_compression("50%"), Ok(2.0));
+    assert!(parse_compression("0%").is_err());
+    assert!(parse_compression("2.3").is_err());
+    assert!(parse_compression("1:0").is_err());
 }
 
 #[test]
-fn test_parse_invalid_inputs() {
-    // All sorts of invalid input. I probably cannot think of
-    // all the weird-ass ways to get an error though.
-    assert!(parse("").is_err());
-    assert!(parse("1").is_err());
-    assert!(parse("1B").is_err());
-    assert!(parse("1B/").is_err());
-    assert!(parse("1Bps").is_err());
-    assert!(parse("1Bs").is_err());
-    assert!(parse("1B:s").is_err());
-
-    assert!(parse("x MB/s").is_err());
-    assert!(parse("1e7 MB/s").is_err());
-    assert!(parse("-33 MB/s").is_err());
-    assert!(parse("192.168.1.1 MB/s").is_err());
-    assert!(parse("４ MB/s").is_err()); // wide digits
-
-    assert!(parse("4 XB/s").is_err());
-    assert!(parse("4 ML/s").is_err());
-    assert!(parse("4 MMMB/s").is_err());
+fn test_copy_text() {
+    let config = config::Config::default();
+    let rate = Rate::new(1_000_000, 1);
+    assert_eq!(
+        copy_text(&config, Some("value"), rate).unwrap(),
+        "1.000 MB / sec"
+    );
+    let table = copy_text(&config, None, rate).unwrap();
+    assert!(table.starts_with("  1.000 MB / sec\n 60.000 MB / min\n"));
+    let csv = copy_text(&config, Some("csv"), rate).unwrap();
+    assert!(csv.starts_with("period,seconds,scaled_value,unit,raw_bytes\n"));
 }
 
-#[rustfmt::skip]
 #[test]
-fn test_parse_number() {
-    let mut p = Parser { buf: b"", pos: 0 };
-    assert!(p.parse_number().is_err());
-
-    let mut p = Parser { buf: b"x", pos: 0 };
-    assert!(p.parse_number().is_err());
-
-    let mut p = Parser { buf: b"1", pos: 0 };
-    assert_eq!(p.parse_number(), Ok(1.0));
-
-    let mut p = Parser { buf: b"123", pos: 0 };
-    assert_eq!(p.parse_number(), Ok(123.0));
-
-    let mut p = Parser { buf: b"1.", pos: 0 };
-    assert!(p.parse_number().is_err());
-
-    let mut p = Parser { buf: b"1.25", pos: 0 };
-    assert_eq!(p.parse_number(), Ok(1.25));
+fn test_plain() {
+    assert_eq!(plain(162037037.037037), "162037037.037");
+    assert_eq!(plain(125000.0), "125000");
+    assert_eq!(plain(2.5), "2.5");
 }
 
 #[test]
-fn test_parse_unit() {
-    let mut p = Parser { buf: b"B", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1.0));
-
-    let mut p = Parser { buf: b"KB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e3));
-
-    let mut p = Parser { buf: b"MB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e6));
-
-    let mut p = Parser { buf: b"GB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e9));
-
-    let mut p = Parser { buf: b"TB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e12));
-
-    let mut p = Parser { buf: b"PB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e15));
-
-    let mut p = Parser { buf: b"EB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e18));
-
-    let mut p = Parser { buf: b"ZB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e21));
-
-    let mut p = Parser { buf: b"YB", pos: 0 };
-    assert_eq!(p.parse_bytes(), Ok(1e24));
+fn test_split_keyword() {
+    assert_eq!(
+        split_keyword("800GB at 95MB/s", "at"),
+        Some(("800GB", "95MB/s"))
+    );
+    assert_eq!(
+        split_keyword("8 TB AT 1 GB / s", "at"),
+        Some(("8 TB", "1 GB / s"))
+    );
+    assert_eq!(split_keyword("800GB 95MB/s", "at"), None);
 }
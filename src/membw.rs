@@ -0,0 +1,103 @@
+//! `rate membw`: theoretical memory bandwidth.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_rate, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " membw <type>-<MT/s> [x<channels>] [--bus-width <bits>]\n",
+    "       e.g. membw ddr5-5600 x2\n",
+    "       Multiplies the transfer rate by the width of a channel (64 bits\n",
+    "       for DDR, 16 for LPDDR, unless --bus-width says otherwise) and the\n",
+    "       number of channels (1 by default)."
+);
+
+/// Memory types and the data width of one of their channels, in bits.
+const TYPES: &[(&str, u32)] = &[
+    ("ddr", 64),
+    ("ddr2", 64),
+    ("ddr3", 64),
+    ("ddr4", 64),
+    ("ddr5", 64),
+    ("lpddr4", 16),
+    ("lpddr4x", 16),
+    ("lpddr5", 16),
+    ("lpddr5x", 16),
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let bus_width: Option<u32> = pargs.opt_value_from_str("--bus-width")?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?.to_ascii_lowercase();
+    let mut words = args.split_whitespace();
+    let (Some(module), channels, None) = (words.next(), words.next(), words.next()) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<type>-<MT/s> [x<channels>]"],
+        )));
+    };
+    let invalid = || {
+        Error::Usage(messages::text(
+            Message::InvalidMemory,
+            &[&format!("{:?}", module)],
+        ))
+    };
+    let (kind, speed) = module.split_once('-').ok_or_else(invalid)?;
+    let speed: f64 = speed.parse().map_err(|_| invalid())?;
+    let Some((_, width)) = TYPES.iter().find(|(name, _)| *name == kind) else {
+        let names: Vec<&str> = TYPES.iter().map(|(n, _)| *n).collect();
+        return Err(Error::Usage(messages::text(
+            Message::UnknownMemory,
+            &[&format!("{:?}", kind), &names.join(" ")],
+        )));
+    };
+    let channels: u32 = match channels {
+        Some(c) => c.trim_start_matches('x').parse().map_err(|_| {
+            Error::Usage(messages::text(
+                Message::InvalidChannels,
+                &[&format!("{:?}", c)],
+            ))
+        })?,
+        None => 1,
+    };
+    let width = bus_width.unwrap_or(*width);
+
+    let bandwidth = bandwidth(speed, width, channels);
+    let kind = kind.to_ascii_uppercase();
+    let text = format!(
+        "memory    : {} at {} MT/s, {} x {}-bit\nbandwidth : {}",
+        kind,
+        speed,
+        channels,
+        width,
+        format_rate(bandwidth)
+    );
+    let bandwidth = Rate::from_decimal(bandwidth);
+    out.line(
+        &text,
+        &[
+            ("memory", Value::Text(&kind)),
+            ("transfers", Value::Number(speed * 1e6)),
+            ("channels", Value::Number(channels as f64)),
+            ("width", Value::Number(width as f64)),
+            ("bandwidth", Value::Rate(bandwidth)),
+        ],
+    )?;
+    out.table(bandwidth)?;
+    return Ok(());
+}
+
+fn bandwidth(megatransfers: f64, width: u32, channels: u32) -> f64 {
+    return megatransfers * 1e6 * (width / 8) as f64 * channels as f64;
+}
+
+#[test]
+fn test_bandwidth() {
+    // DDR4-3200, dual channel: 51.2 GB/s.
+    assert_eq!(bandwidth(3200.0, 64, 2), 51.2e9);
+    assert_eq!(bandwidth(5600.0, 64, 1), 44.8e9);
+}
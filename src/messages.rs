@@ -0,0 +1,922 @@
+//! The catalog of the messages of the command line in each language,
+//! chosen with `--lang` or from the locale (`LC_ALL`, `LC_MESSAGES`,
+//! `LANG`). The errors and notes of the subcommands are in the catalog
+//! too, but their help and their output are in English only.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use rate::{ParseErrorKind, PERIODS, UNITS};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Fr,
+    Es,
+}
+
+/// The languages of the catalog, with their codes.
+const LANGS: &[(&str, Lang)] = &[("en", Lang::En), ("fr", Lang::Fr), ("es", Lang::Es)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    InvalidNumber,
+    InvalidUnit,
+    InvalidPeriod,
+    UnexpectedCharacter,
+    TrailingInput,
+    DidYouMean,
+    Line,
+    AssertionFailed,
+    UnknownOption,
+    Usage,
+    HelpNumber,
+    HelpBits,
+    HelpCompress,
+    HelpOutput,
+    HelpPrecision,
+    HelpUnits,
+    HelpPeriods,
+    HelpBitsOption,
+    HelpNice,
+    HelpExplain,
+    HelpNormalize,
+    HelpCopy,
+    HelpCopyFormat,
+    HelpMonthDays,
+    HelpProfile,
+    HelpColor,
+    HelpLang,
+    HelpDefaults,
+    HelpEnvironment,
+    HelpCommand,
+    HelpExitStatus,
+    TooManyArguments,
+    // Raised on the systems without Unix sockets only.
+    #[allow(dead_code)]
+    UnixOnly,
+    Expected,
+    ExpectedEither,
+    Between,
+    Exclusive,
+    OptionPositive,
+    IntervalPositive,
+    IntervalTooLong,
+    DurationTooLong,
+    InvalidRefresh,
+    InvalidSpeed,
+    InvalidTime,
+    InvalidStart,
+    SizePositive,
+    RatePositive,
+    RatesPositive,
+    SizeRatePositive,
+    BlockSizePositive,
+    ChunkPositive,
+    FrameSizePositive,
+    LimitPositive,
+    StageRatePositive,
+    StageRatesPositive,
+    LinkRatePositive,
+    DaysHeightPositive,
+    WindowColumnsPositive,
+    RepoPositive,
+    S3Positive,
+    TcpPositive,
+    AssertBounds,
+    LessThan,
+    MoreThan,
+    TwoStages,
+    UnknownFormat,
+    BurstRate,
+    PriceFinite,
+    NoSamplesToBill,
+    UnknownPreset,
+    DutyCycle,
+    HitRatio,
+    NotACgroup,
+    ContainerNotV2,
+    NoConfigDir,
+    NotSet,
+    UnknownAction,
+    UnknownProfile,
+    NoSource,
+    ListeningPort,
+    Listening,
+    LiveNoFiles,
+    IntervalForStdin,
+    AfterOlder,
+    BeforeFuture,
+    SnapshotKind,
+    UnknownProvider,
+    NulByte,
+    NoRenditions,
+    SharesZero,
+    SharesScaled,
+    LineNoTimestamp,
+    LineJsonFields,
+    NoSamplesInLog,
+    TooManyWindows,
+    TooLarge,
+    InvalidMemory,
+    UnknownMemory,
+    InvalidChannels,
+    Overlap,
+    NoRatesToCompare,
+    NoRatesToSummarize,
+    NoToolSpeed,
+    UnknownGeneration,
+    InvalidLanes,
+    NotStateFile,
+    DayRange,
+    WindowSecond,
+    NoSeries,
+    LoadUnder,
+    UnknownTool,
+    NoThroughputColumns,
+    S3Parts,
+    NeedWindow,
+    NeedSlot,
+    NeverSends,
+    TooManyDays,
+    UnknownInterface,
+    UnknownDrive,
+    LossRange,
+    TopicAtLeastOne,
+    ReplicationBrokers,
+    WarningThreshold,
+    UnknownWifi,
+    ChannelWidth,
+    Streams,
+    Clipboard,
+    // Raised on the systems without interface counters only.
+    #[allow(dead_code)]
+    CountersUnsupported,
+}
+
+/// The message in English, French and Spanish. `{}` stands for the
+/// arguments, in order.
+#[rustfmt::skip]
+fn translations(message: Message) -> [&'static str; 3] {
+    match message {
+        Message::InvalidNumber => [
+            "not a valid number",
+            "nombre invalide",
+            "número no válido",
+        ],
+        Message::InvalidUnit => [
+            "not a recognized unit ({})",
+            "unité inconnue ({})",
+            "unidad desconocida ({})",
+        ],
+        Message::InvalidPeriod => [
+            "not a recognized time period ({})",
+            "période inconnue ({})",
+            "período desconocido ({})",
+        ],
+        Message::UnexpectedCharacter => [
+            "expected {}, found {}",
+            "{} attendu, {} trouvé",
+            "se esperaba {}, se encontró {}",
+        ],
+        Message::TrailingInput => [
+            "unexpected input after the expression",
+            "texte inattendu après l'expression",
+            "texto inesperado después de la expresión",
+        ],
+        Message::DidYouMean => [
+            "did you mean {}?",
+            "vouliez-vous dire {} ?",
+            "¿quiso decir {}?",
+        ],
+        Message::Line => [
+            "line {}",
+            "ligne {}",
+            "línea {}",
+        ],
+        Message::AssertionFailed => [
+            "assertion failed: {}",
+            "assertion non vérifiée : {}",
+            "aserción fallida: {}",
+        ],
+        Message::UnknownOption => [
+            "unknown option {}",
+            "option inconnue {}",
+            "opción desconocida {}",
+        ],
+        Message::Usage => [
+            "Usage",
+            "Utilisation",
+            "Uso",
+        ],
+        Message::HelpNumber => [
+            "integer or float (no scientific notation)",
+            "entier ou décimal (sans notation scientifique)",
+            "entero o decimal (sin notación científica)",
+        ],
+        Message::HelpBits => [
+            "(b after an uppercase prefix means bits: Kb Mb Gb ...)",
+            "(b après un préfixe majuscule désigne des bits : Kb Mb Gb ...)",
+            "(b tras un prefijo en mayúscula indica bits: Kb Mb Gb ...)",
+        ],
+        Message::HelpCompress => [
+            "scale by a compression ratio (2.3:1 or 57%)",
+            "appliquer un taux de compression (2.3:1 ou 57%)",
+            "aplicar una tasa de compresión (2.3:1 o 57%)",
+        ],
+        Message::HelpOutput => [
+            "text (default), json, csv or prometheus, for every command",
+            "text (par défaut), json, csv ou prometheus, pour toute commande",
+            "text (por defecto), json, csv o prometheus, para todo comando",
+        ],
+        Message::HelpPrecision => [
+            "the number of decimals (default 3)",
+            "le nombre de décimales (3 par défaut)",
+            "el número de decimales (3 por defecto)",
+        ],
+        Message::HelpUnits => [
+            "si (KB MB ..., default) or iec (KiB MiB ...)",
+            "si (KB MB ..., par défaut) ou iec (KiB MiB ...)",
+            "si (KB MB ..., por defecto) o iec (KiB MiB ...)",
+        ],
+        Message::HelpPeriods => [
+            "the periods of the table, e.g. sec,hour,day",
+            "les périodes du tableau, p. ex. sec,hour,day",
+            "los períodos de la tabla, p. ej. sec,hour,day",
+        ],
+        Message::HelpBitsOption => [
+            "show amounts in bits (Mb) rather than bytes (MB)",
+            "afficher les quantités en bits (Mb) plutôt qu'en octets (MB)",
+            "mostrar las cantidades en bits (Mb) en lugar de bytes (MB)",
+        ],
+        Message::HelpNice => [
+            "round to figures like 85 or 2.5, for slides",
+            "arrondir à des valeurs comme 85 ou 2.5, pour des présentations",
+            "redondear a cifras como 85 o 2.5, para presentaciones",
+        ],
+        Message::HelpExplain => [
+            "show how each figure is computed",
+            "montrer comment chaque valeur est calculée",
+            "mostrar cómo se calcula cada cifra",
+        ],
+        Message::HelpNormalize => [
+            "print the rate in canonical form only",
+            "afficher seulement le débit sous sa forme canonique",
+            "mostrar solo la tasa en su forma canónica",
+        ],
+        Message::HelpCopy => [
+            "copy the table to the clipboard as well",
+            "copier aussi le tableau dans le presse-papiers",
+            "copiar también la tabla al portapapeles",
+        ],
+        Message::HelpCopyFormat => [
+            "copy in another output format, or \"value\"",
+            "copier dans un autre format de sortie, ou \"value\"",
+            "copiar en otro formato de salida, o \"value\"",
+        ],
+        Message::HelpMonthDays => [
+            "the length of a month in days, 30 by default",
+            "la durée d'un mois en jours, 30 par défaut",
+            "la duración de un mes en días, 30 por defecto",
+        ],
+        Message::HelpProfile => [
+            "the options of a profile of the configuration",
+            "les options d'un profil de la configuration",
+            "las opciones de un perfil de la configuración",
+        ],
+        Message::HelpColor => [
+            "auto (default), always or never",
+            "auto (par défaut), always ou never",
+            "auto (por defecto), always o never",
+        ],
+        Message::HelpLang => [
+            "the language of the messages: en, fr or es",
+            "la langue des messages : en, fr ou es",
+            "el idioma de los mensajes: en, fr o es",
+        ],
+        Message::HelpDefaults => [
+            "Defaults for these options are read from {}",
+            "Les valeurs par défaut de ces options sont lues dans {}",
+            "Los valores por defecto de estas opciones se leen de {}",
+        ],
+        Message::HelpEnvironment => [
+            "and the RATE_PRECISION, RATE_UNITS and RATE_FORMAT variables",
+            "et les variables RATE_PRECISION, RATE_UNITS et RATE_FORMAT",
+            "y las variables RATE_PRECISION, RATE_UNITS y RATE_FORMAT",
+        ],
+        Message::HelpCommand => [
+            "{} (see {} <command> -h)",
+            "{} (voir {} <command> -h)",
+            "{} (ver {} <command> -h)",
+        ],
+        Message::HelpExitStatus => [
+            "Exit status: 0 success, 1 failed assertion, 2 usage error,\n\
+             3 invalid number, 4 invalid unit, 5 invalid period,\n\
+             6 unexpected character, 7 trailing input, 8 I/O error",
+            "Code de sortie : 0 succès, 1 assertion non vérifiée, 2 erreur\n\
+             d'utilisation, 3 nombre invalide, 4 unité inconnue, 5 période\n\
+             inconnue, 6 caractère inattendu, 7 texte en trop, 8 erreur d'E/S",
+            "Código de salida: 0 éxito, 1 aserción fallida, 2 error de uso,\n\
+             3 número no válido, 4 unidad desconocida, 5 período desconocido,\n\
+             6 carácter inesperado, 7 texto sobrante, 8 error de E/S",
+        ],
+        Message::TooManyArguments => [
+            "too many arguments: {}",
+            "trop d'arguments : {}",
+            "demasiados argumentos: {}",
+        ],
+        Message::UnixOnly => [
+            "{} is only supported on Unix systems",
+            "{} n'est disponible que sur les systèmes Unix",
+            "{} solo está disponible en sistemas Unix",
+        ],
+        Message::Expected => [
+            "expected {}",
+            "{} attendu",
+            "se esperaba {}",
+        ],
+        Message::ExpectedEither => [
+            "expected either {} or {}",
+            "{} ou {} attendu",
+            "se esperaba {} o {}",
+        ],
+        Message::Between => [
+            "{} must be between {} and {}",
+            "{} doit être compris entre {} et {}",
+            "{} debe estar entre {} y {}",
+        ],
+        Message::Exclusive => [
+            "{} and {} are exclusive",
+            "{} et {} sont incompatibles",
+            "{} y {} son incompatibles",
+        ],
+        Message::OptionPositive => [
+            "{} must be positive",
+            "{} doit être positif",
+            "{} debe ser positivo",
+        ],
+        Message::IntervalPositive => [
+            "the interval must be positive",
+            "l'intervalle doit être positif",
+            "el intervalo debe ser positivo",
+        ],
+        Message::IntervalTooLong => [
+            "the interval is too long",
+            "l'intervalle est trop long",
+            "el intervalo es demasiado largo",
+        ],
+        Message::DurationTooLong => [
+            "the duration is too long",
+            "la durée est trop longue",
+            "la duración es demasiado larga",
+        ],
+        Message::InvalidRefresh => [
+            "invalid refresh interval {}",
+            "intervalle de rafraîchissement invalide {}",
+            "intervalo de actualización no válido {}",
+        ],
+        Message::InvalidSpeed => [
+            "invalid speed {}",
+            "vitesse invalide {}",
+            "velocidad no válida {}",
+        ],
+        Message::InvalidTime => [
+            "invalid time {}",
+            "heure invalide {}",
+            "hora no válida {}",
+        ],
+        Message::InvalidStart => [
+            "invalid start time {}",
+            "heure de début invalide {}",
+            "hora de inicio no válida {}",
+        ],
+        Message::SizePositive => [
+            "the size must be positive",
+            "la taille doit être positive",
+            "el tamaño debe ser positivo",
+        ],
+        Message::RatePositive => [
+            "the rate must be positive",
+            "le débit doit être positif",
+            "la tasa debe ser positiva",
+        ],
+        Message::RatesPositive => [
+            "the rates must be positive",
+            "les débits doivent être positifs",
+            "las tasas deben ser positivas",
+        ],
+        Message::SizeRatePositive => [
+            "the size and the rate must be positive",
+            "la taille et le débit doivent être positifs",
+            "el tamaño y la tasa deben ser positivos",
+        ],
+        Message::BlockSizePositive => [
+            "the block size must be positive",
+            "la taille de bloc doit être positive",
+            "el tamaño de bloque debe ser positivo",
+        ],
+        Message::ChunkPositive => [
+            "the chunk size and the rate must be positive",
+            "la taille des blocs et le débit doivent être positifs",
+            "el tamaño de bloque y la tasa deben ser positivos",
+        ],
+        Message::FrameSizePositive => [
+            "the frame size must be positive",
+            "la taille de trame doit être positive",
+            "el tamaño de trama debe ser positivo",
+        ],
+        Message::LimitPositive => [
+            "the limit must be positive",
+            "la limite doit être positive",
+            "el límite debe ser positivo",
+        ],
+        Message::StageRatePositive => [
+            "the rate of {} must be positive",
+            "le débit de {} doit être positif",
+            "la tasa de {} debe ser positiva",
+        ],
+        Message::StageRatesPositive => [
+            "the rates of the stages must be positive",
+            "les débits des étapes doivent être positifs",
+            "las tasas de las etapas deben ser positivas",
+        ],
+        Message::LinkRatePositive => [
+            "the rate of the link must be positive",
+            "le débit du lien doit être positif",
+            "la tasa del enlace debe ser positiva",
+        ],
+        Message::DaysHeightPositive => [
+            "--days and --height must be positive",
+            "--days et --height doivent être positifs",
+            "--days y --height deben ser positivos",
+        ],
+        Message::WindowColumnsPositive => [
+            "the window and column numbers must be positive",
+            "la fenêtre et les numéros de colonne doivent être positifs",
+            "la ventana y los números de columna deben ser positivos",
+        ],
+        Message::RepoPositive => [
+            "the count, the rate and --parallel must be positive",
+            "le nombre, le débit et --parallel doivent être positifs",
+            "el número, la tasa y --parallel deben ser positivos",
+        ],
+        Message::S3Positive => [
+            "the part size, concurrency and rate must be positive",
+            "la taille des parties, la concurrence et le débit doivent être positifs",
+            "el tamaño de las partes, la concurrencia y la tasa deben ser positivos",
+        ],
+        Message::TcpPositive => [
+            "the round trip and the segment size must be positive",
+            "l'aller-retour et la taille de segment doivent être positifs",
+            "el tiempo de ida y vuelta y el tamaño de segmento deben ser positivos",
+        ],
+        Message::AssertBounds => [
+            "assert needs --at-least and/or --at-most",
+            "assert demande --at-least et/ou --at-most",
+            "assert necesita --at-least y/o --at-most",
+        ],
+        Message::LessThan => [
+            "{} is less than {}",
+            "{} est inférieur à {}",
+            "{} es menor que {}",
+        ],
+        Message::MoreThan => [
+            "{} is more than {}",
+            "{} est supérieur à {}",
+            "{} es mayor que {}",
+        ],
+        Message::TwoStages => [
+            "expected at least two stages separated by ->",
+            "au moins deux étapes séparées par -> attendues",
+            "se esperaban al menos dos etapas separadas por ->",
+        ],
+        Message::UnknownFormat => [
+            "unknown format {}",
+            "format inconnu {}",
+            "formato desconocido {}",
+        ],
+        Message::BurstRate => [
+            "the burst rate must be at least the (positive) sustained rate",
+            "le débit de rafale doit être au moins le débit soutenu (positif)",
+            "la tasa de ráfaga debe ser al menos la tasa sostenida (positiva)",
+        ],
+        Message::PriceFinite => [
+            "--price must be a finite amount, not negative",
+            "--price doit être un montant fini, non négatif",
+            "--price debe ser un importe finito, no negativo",
+        ],
+        Message::NoSamplesToBill => [
+            "no samples to bill",
+            "aucun échantillon à facturer",
+            "ninguna muestra que facturar",
+        ],
+        Message::UnknownPreset => [
+            "unknown preset {} ({})",
+            "préréglage inconnu {} ({})",
+            "preajuste desconocido {} ({})",
+        ],
+        Message::DutyCycle => [
+            "the duty cycle is at most 100%",
+            "le rapport cyclique est au plus de 100%",
+            "el ciclo de trabajo es como máximo del 100%",
+        ],
+        Message::HitRatio => [
+            "the cache hit ratio is at most 100%",
+            "le taux de succès du cache est au plus de 100%",
+            "la tasa de aciertos de la caché es como máximo del 100%",
+        ],
+        Message::NotACgroup => [
+            "{} is neither a cgroup v2 nor a running docker or podman container",
+            "{} n'est ni un cgroup v2 ni un conteneur docker ou podman en cours d'exécution",
+            "{} no es un cgroup v2 ni un contenedor docker o podman en ejecución",
+        ],
+        Message::ContainerNotV2 => [
+            "container {} is not in a cgroup v2",
+            "le conteneur {} n'est pas dans un cgroup v2",
+            "el contenedor {} no está en un cgroup v2",
+        ],
+        Message::NoConfigDir => [
+            "no configuration directory (neither XDG_CONFIG_HOME nor HOME is set)",
+            "pas de répertoire de configuration (ni XDG_CONFIG_HOME ni HOME n'est défini)",
+            "no hay directorio de configuración (ni XDG_CONFIG_HOME ni HOME están definidos)",
+        ],
+        Message::NotSet => [
+            "{} is not set",
+            "{} n'est pas défini",
+            "{} no está definido",
+        ],
+        Message::UnknownAction => [
+            "unknown action {} ({})",
+            "action inconnue {} ({})",
+            "acción desconocida {} ({})",
+        ],
+        Message::UnknownProfile => [
+            "unknown profile {} ({})",
+            "profil inconnu {} ({})",
+            "perfil desconocido {} ({})",
+        ],
+        Message::NoSource => [
+            "expected at least one source",
+            "au moins une source attendue",
+            "se esperaba al menos una fuente",
+        ],
+        Message::ListeningPort => [
+            "listening on port {}",
+            "à l'écoute sur le port {}",
+            "escuchando en el puerto {}",
+        ],
+        Message::Listening => [
+            "listening on {}",
+            "à l'écoute sur {}",
+            "escuchando en {}",
+        ],
+        Message::LiveNoFiles => [
+            "--live takes no files",
+            "--live ne prend pas de fichiers",
+            "--live no admite archivos",
+        ],
+        Message::IntervalForStdin => [
+            "pass --interval with snapshots on stdin",
+            "passez --interval avec des relevés sur l'entrée standard",
+            "indique --interval con instantáneas en la entrada estándar",
+        ],
+        Message::AfterOlder => [
+            "<after> is older than <before>; pass --interval",
+            "<after> est plus ancien que <before> ; passez --interval",
+            "<after> es más antiguo que <before>; indique --interval",
+        ],
+        Message::BeforeFuture => [
+            "<before> is in the future; pass --interval",
+            "<before> est dans le futur ; passez --interval",
+            "<before> está en el futuro; indique --interval",
+        ],
+        Message::SnapshotKind => [
+            "only snapshots of /proc/net/dev or `ip -s link` can be compared with the current counters",
+            "seuls les relevés de /proc/net/dev ou de `ip -s link` peuvent être comparés aux compteurs actuels",
+            "solo las instantáneas de /proc/net/dev o de `ip -s link` pueden compararse con los contadores actuales",
+        ],
+        Message::UnknownProvider => [
+            "unknown provider {} ({})",
+            "fournisseur inconnu {} ({})",
+            "proveedor desconocido {} ({})",
+        ],
+        Message::NulByte => [
+            "path contains a NUL byte",
+            "le chemin contient un octet NUL",
+            "la ruta contiene un byte NUL",
+        ],
+        Message::NoRenditions => [
+            "no renditions given",
+            "aucun rendu donné",
+            "no se indicó ninguna variante",
+        ],
+        Message::SharesZero => [
+            "the shares add up to zero",
+            "la somme des parts est nulle",
+            "las proporciones suman cero",
+        ],
+        Message::SharesScaled => [
+            "note: shares add up to {}%, scaling them to 100%",
+            "note : la somme des parts est de {}%, elles sont ramenées à 100%",
+            "nota: las proporciones suman {}%, se escalan al 100%",
+        ],
+        Message::LineNoTimestamp => [
+            "line {}: no valid timestamp",
+            "ligne {} : pas d'horodatage valide",
+            "línea {}: ninguna marca de tiempo válida",
+        ],
+        Message::LineJsonFields => [
+            "line {}: expected \"timestamp\" and \"bytes\" numbers",
+            "ligne {} : nombres \"timestamp\" et \"bytes\" attendus",
+            "línea {}: se esperaban los números \"timestamp\" y \"bytes\"",
+        ],
+        Message::NoSamplesInLog => [
+            "no samples in the log",
+            "aucun échantillon dans le journal",
+            "ninguna muestra en el registro",
+        ],
+        Message::TooManyWindows => [
+            "the log spans {} to {}: more than {} windows, use a longer --window",
+            "le journal va de {} à {} : plus de {} fenêtres, utilisez une --window plus longue",
+            "el registro abarca de {} a {}: más de {} ventanas, use una --window más larga",
+        ],
+        Message::TooLarge => [
+            "{} over a {} exceeds the largest amount that can be represented (2^127 bytes)",
+            "{} sur la période {} dépasse la plus grande quantité représentable (2^127 octets)",
+            "{} en el período {} supera la mayor cantidad representable (2^127 bytes)",
+        ],
+        Message::InvalidMemory => [
+            "invalid memory {}, e.g. ddr5-5600",
+            "mémoire invalide {}, p. ex. ddr5-5600",
+            "memoria no válida {}, p. ej. ddr5-5600",
+        ],
+        Message::UnknownMemory => [
+            "unknown memory type {} ({})",
+            "type de mémoire inconnu {} ({})",
+            "tipo de memoria desconocido {} ({})",
+        ],
+        Message::InvalidChannels => [
+            "invalid channel count {}",
+            "nombre de canaux invalide {}",
+            "número de canales no válido {}",
+        ],
+        Message::Overlap => [
+            "the overlap is at most 100%",
+            "le chevauchement est au plus de 100%",
+            "el solapamiento es como máximo del 100%",
+        ],
+        Message::NoRatesToCompare => [
+            "no rates to compare",
+            "aucun débit à comparer",
+            "ninguna tasa que comparar",
+        ],
+        Message::NoRatesToSummarize => [
+            "no rates to summarize",
+            "aucun débit à résumer",
+            "ninguna tasa que resumir",
+        ],
+        Message::NoToolSpeed => [
+            "no rsync, curl or wget speed found in the input",
+            "aucune vitesse de rsync, curl ou wget trouvée dans l'entrée",
+            "no se encontró ninguna velocidad de rsync, curl o wget en la entrada",
+        ],
+        Message::UnknownGeneration => [
+            "unknown generation {} ({})",
+            "génération inconnue {} ({})",
+            "generación desconocida {} ({})",
+        ],
+        Message::InvalidLanes => [
+            "invalid lane count {} ({})",
+            "nombre de voies invalide {} ({})",
+            "número de carriles no válido {} ({})",
+        ],
+        Message::NotStateFile => [
+            "{}: not a state file of rate pipe",
+            "{} : pas un fichier d'état de rate pipe",
+            "{}: no es un archivo de estado de rate pipe",
+        ],
+        Message::DayRange => [
+            "--day must be between 0 and {} for this period",
+            "--day doit être compris entre 0 et {} pour cette période",
+            "--day debe estar entre 0 y {} para este período",
+        ],
+        Message::WindowSecond => [
+            "the window must be at least a second",
+            "la fenêtre doit durer au moins une seconde",
+            "la ventana debe durar al menos un segundo",
+        ],
+        Message::NoSeries => [
+            "no series match {}",
+            "aucune série ne correspond à {}",
+            "ninguna serie coincide con {}",
+        ],
+        Message::LoadUnder => [
+            "--load must be under 100%",
+            "--load doit être inférieur à 100%",
+            "--load debe ser inferior al 100%",
+        ],
+        Message::UnknownTool => [
+            "unknown tool {} ({})",
+            "outil inconnu {} ({})",
+            "herramienta desconocida {} ({})",
+        ],
+        Message::NoThroughputColumns => [
+            "no {} throughput columns found in the input",
+            "aucune colonne de débit de {} trouvée dans l'entrée",
+            "no se encontraron columnas de rendimiento de {} en la entrada",
+        ],
+        Message::S3Parts => [
+            "warning: S3 allows at most {} parts, use larger parts",
+            "avertissement : S3 accepte au plus {} parties, utilisez des parties plus grandes",
+            "aviso: S3 admite como máximo {} partes, use partes más grandes",
+        ],
+        Message::NeedWindow => [
+            "at least one --window is needed",
+            "au moins une --window est nécessaire",
+            "se necesita al menos una --window",
+        ],
+        Message::NeedSlot => [
+            "at least one --slot is needed",
+            "au moins un --slot est nécessaire",
+            "se necesita al menos un --slot",
+        ],
+        Message::NeverSends => [
+            "the schedule never sends anything",
+            "le planning n'envoie jamais rien",
+            "el calendario nunca envía nada",
+        ],
+        Message::TooManyDays => [
+            "{} at {} a day takes more than {} days",
+            "{} à {} par jour prend plus de {} jours",
+            "{} a {} por día tarda más de {} días",
+        ],
+        Message::UnknownInterface => [
+            "unknown interface {} (see \"standard list\")",
+            "interface inconnue {} (voir \"standard list\")",
+            "interfaz desconocida {} (ver \"standard list\")",
+        ],
+        Message::UnknownDrive => [
+            "unknown drive {} ({})",
+            "lecteur inconnu {} ({})",
+            "unidad desconocida {} ({})",
+        ],
+        Message::LossRange => [
+            "the loss must be between 0% and 100%",
+            "la perte doit être comprise entre 0% et 100%",
+            "la pérdida debe estar entre 0% y 100%",
+        ],
+        Message::TopicAtLeastOne => [
+            "replication, partitions and brokers must be at least 1",
+            "la réplication, les partitions et les brokers doivent valoir au moins 1",
+            "la replicación, las particiones y los brokers deben ser al menos 1",
+        ],
+        Message::ReplicationBrokers => [
+            "the replication factor cannot exceed the number of brokers",
+            "le facteur de réplication ne peut pas dépasser le nombre de brokers",
+            "el factor de replicación no puede superar el número de brokers",
+        ],
+        Message::WarningThreshold => [
+            "the warning threshold is at most 100%",
+            "le seuil d'avertissement est au plus de 100%",
+            "el umbral de aviso es como máximo del 100%",
+        ],
+        Message::UnknownWifi => [
+            "unknown Wi-Fi standard {} ({})",
+            "norme Wi-Fi inconnue {} ({})",
+            "estándar Wi-Fi desconocido {} ({})",
+        ],
+        Message::ChannelWidth => [
+            "{} does not support {}MHz channels",
+            "{} ne prend pas en charge les canaux de {}MHz",
+            "{} no admite canales de {}MHz",
+        ],
+        Message::Streams => [
+            "{} supports 1 to {} spatial streams",
+            "{} prend en charge de 1 à {} flux spatiaux",
+            "{} admite de 1 a {} flujos espaciales",
+        ],
+        Message::Clipboard => [
+            "could not copy to the clipboard with {}",
+            "impossible de copier dans le presse-papiers avec {}",
+            "no se pudo copiar al portapapeles con {}",
+        ],
+        Message::CountersUnsupported => [
+            "reading the interface counters is not supported on this system",
+            "la lecture des compteurs d'interface n'est pas prise en charge sur ce système",
+            "la lectura de los contadores de interfaz no es compatible con este sistema",
+        ],
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Chooses the language: `code` if given (from `--lang`), or that of the
+/// locale. Languages not in the catalog fall back to English.
+pub fn init(code: Option<&str>) -> Result<(), String> {
+    let lang = match code {
+        Some(code) => match parse_lang(code) {
+            Some(lang) => lang,
+            None => {
+                let codes: Vec<&str> = LANGS.iter().map(|(c, _)| *c).collect();
+                return Err(format!("unknown language {:?} ({})", code, codes.join(" ")));
+            }
+        },
+        None => locale_lang(|name| std::env::var(name).ok()),
+    };
+    _ = LANG.set(lang);
+    return Ok(());
+}
+
+/// The language of a code such as "fr" or a locale such as "fr_CA.UTF-8".
+fn parse_lang(code: &str) -> Option<Lang> {
+    let code = code.split(['_', '-', '.', '@']).next()?;
+    let lang = LANGS.iter().find(|(c, _)| c.eq_ignore_ascii_case(code));
+    return lang.map(|(_, lang)| *lang);
+}
+
+/// The language of the locale: the first of `LC_ALL`, `LC_MESSAGES` and
+/// `LANG` that is set decides, as in POSIX.
+fn locale_lang(var: impl Fn(&str) -> Option<String>) -> Lang {
+    for name in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Some(locale) = var(name).filter(|v| !v.is_empty()) {
+            return parse_lang(&locale).unwrap_or(Lang::En);
+        }
+    }
+    return Lang::En;
+}
+
+fn lang() -> Lang {
+    return *LANG.get().unwrap_or(&Lang::En);
+}
+
+/// The message in the chosen language, with its `{}` replaced by `args`.
+pub fn text(message: Message, args: &[&dyn Display]) -> String {
+    return fill(translations(message)[lang() as usize], args);
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    out.push_str(parts.next().unwrap_or(""));
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    return out;
+}
+
+/// The description of a parse error, as `ParseErrorKind` displays it in
+/// English.
+pub fn parse_error(kind: ParseErrorKind) -> String {
+    match kind {
+        ParseErrorKind::InvalidNumber => return text(Message::InvalidNumber, &[]),
+        ParseErrorKind::InvalidUnit => {
+            let symbols: Vec<&str> = UNITS.iter().map(|u| u.symbol).collect();
+            return text(Message::InvalidUnit, &[&symbols.join(" ")]);
+        }
+        ParseErrorKind::InvalidPeriod => {
+            let names: Vec<&str> = PERIODS.iter().map(|p| p.name).collect();
+            return text(Message::InvalidPeriod, &[&names.join(" ")]);
+        }
+        ParseErrorKind::UnexpectedCharacter { expected, actual } => {
+            let expected = format!("{:?}", expected as char);
+            let actual = format!("{:?}", actual as char);
+            return text(Message::UnexpectedCharacter, &[&expected, &actual]);
+        }
+        ParseErrorKind::TrailingInput => return text(Message::TrailingInput, &[]),
+    }
+}
+
+#[test]
+fn test_messages() {
+    assert_eq!(parse_lang("fr_CA.UTF-8"), Some(Lang::Fr));
+    assert_eq!(parse_lang("ES"), Some(Lang::Es));
+    assert_eq!(parse_lang("C"), None);
+    let env = |name: &str| match name {
+        "LC_MESSAGES" => Some(String::from("es_MX.UTF-8")),
+        "LANG" => Some(String::from("fr_FR.UTF-8")),
+        _ => None,
+    };
+    assert_eq!(locale_lang(env), Lang::Es);
+    assert_eq!(locale_lang(|_| Some(String::from("C.UTF-8"))), Lang::En);
+    assert_eq!(
+        fill("expected {}, found {}", &[&1, &"x"]),
+        "expected 1, found x"
+    );
+    // The English catalog matches the library's messages.
+    for kind in [
+        ParseErrorKind::InvalidNumber,
+        ParseErrorKind::InvalidUnit,
+        ParseErrorKind::InvalidPeriod,
+        ParseErrorKind::UnexpectedCharacter {
+            expected: b'/',
+            actual: b's',
+        },
+        ParseErrorKind::TrailingInput,
+    ] {
+        assert_eq!(parse_error(kind), kind.to_string());
+    }
+}
@@ -0,0 +1,115 @@
+//! `rate migrate`: a dump, transfer and restore, and whether it fits.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, parse, parse_duration, parse_input, parse_percent, parse_size,
+    remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " migrate <size> --dump <rate> --transfer <rate> --restore <rate>\n",
+    "       [--overlap <ratio>] [--window <duration>]\n",
+    "       Plans a migration that dumps the data, sends it and restores it.\n",
+    "       --overlap is how much the stages run at the same time, from 0%\n",
+    "       (one after the other, the default) to 100% (fully pipelined,\n",
+    "       as fast as the slowest stage). --window is the maintenance\n",
+    "       window it has to fit in."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let dump: f64 = pargs.value_from_fn("--dump", parse)?;
+    let transfer: f64 = pargs.value_from_fn("--transfer", parse)?;
+    let restore: f64 = pargs.value_from_fn("--restore", parse)?;
+    let overlap: f64 = pargs
+        .opt_value_from_fn("--overlap", parse_percent)?
+        .unwrap_or(0.0);
+    let window: Option<f64> = pargs.opt_value_from_fn("--window", parse_duration)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let size = parse_input(&args, &args, parse_size)?;
+    if dump <= 0.0 || transfer <= 0.0 || restore <= 0.0 {
+        return Err(Error::Usage(messages::text(
+            Message::StageRatesPositive,
+            &[],
+        )));
+    }
+    if overlap > 1.0 {
+        return Err(Error::Usage(messages::text(Message::Overlap, &[])));
+    }
+
+    let stages = [("dump", dump), ("transfer", transfer), ("restore", restore)];
+    let times: Vec<f64> = stages.iter().map(|(_, rate)| size / rate).collect();
+    let slowest = (0..stages.len())
+        .max_by(|a, b| times[*a].total_cmp(&times[*b]))
+        .unwrap_or(0);
+    for (i, (name, rate)) in stages.iter().enumerate() {
+        let text = format!(
+            "{:<10} : {} at {}{}",
+            name,
+            format_duration(times[i]),
+            format_rate(*rate),
+            if i == slowest { " (bottleneck)" } else { "" }
+        );
+        out.line(
+            &text,
+            &[
+                ("stage", Value::Text(name)),
+                ("time", Value::Duration(times[i])),
+                ("rate", Value::Rate(Rate::from_decimal(*rate))),
+            ],
+        )?;
+    }
+    let wall = wall_time(&times, overlap);
+    let sequential: f64 = times.iter().sum();
+    let mut text = vec![
+        format!("sequential : {}", format_duration(sequential)),
+        format!(
+            "wall time  : {} ({:.0}% overlap)",
+            format_duration(wall),
+            overlap * 100.0
+        ),
+    ];
+    let mut fields = vec![
+        ("sequential", Value::Duration(sequential)),
+        ("wall_time", Value::Duration(wall)),
+        ("overlap", Value::Number(overlap)),
+    ];
+    if let Some(window) = window {
+        match wall <= window {
+            true => text.push(format!(
+                "window     : fits in {}, {} to spare",
+                format_duration(window),
+                format_duration(window - wall)
+            )),
+            false => text.push(format!(
+                "window     : does not fit in {}, {} over",
+                format_duration(window),
+                format_duration(wall - window)
+            )),
+        }
+        fields.push(("window", Value::Duration(window)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// The time the stages take together: their sum when they run one after
+/// the other, the longest when they fully overlap, and in between.
+fn wall_time(times: &[f64], overlap: f64) -> f64 {
+    let sum: f64 = times.iter().sum();
+    let longest = times.iter().copied().fold(0.0, f64::max);
+    return sum - overlap * (sum - longest);
+}
+
+#[test]
+fn test_wall_time() {
+    let times = [2.0, 5.0, 3.0];
+    assert_eq!(wall_time(&times, 0.0), 10.0);
+    assert_eq!(wall_time(&times, 1.0), 5.0);
+    assert_eq!(wall_time(&times, 0.5), 7.5);
+}
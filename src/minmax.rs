@@ -0,0 +1,90 @@
+//! `rate minmax`: the slowest and fastest of a list of rates.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_rate, input_lines, parse, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " minmax [<rate>...]\n",
+    "       Reads rates from the arguments, or one per line from stdin.\n",
+    "       A line may start with a label, e.g. \"db2: 80 MB/s\"."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let mut slowest: Option<(f64, &str)> = None;
+    let mut fastest: Option<(f64, &str)> = None;
+    let lines = input_lines(pargs)?;
+    for (n, line) in &lines {
+        let (label, expr) = split_label(line);
+        let rate = parse(expr).map_err(|e| Error::Line(*n, expr.to_string(), e))?;
+        if slowest.is_none_or(|(r, _)| rate < r) {
+            slowest = Some((rate, label));
+        }
+        if fastest.is_none_or(|(r, _)| rate > r) {
+            fastest = Some((rate, label));
+        }
+    }
+    let (Some(slowest), Some(fastest)) = (slowest, fastest) else {
+        return Err(Error::Usage(messages::text(Message::NoRatesToCompare, &[])));
+    };
+    let ratio = fastest.0 / slowest.0;
+    let text = [
+        format!(
+            "slowest: {}{}",
+            format_rate(slowest.0),
+            label_suffix(slowest.1)
+        ),
+        format!(
+            "fastest: {}{}",
+            format_rate(fastest.0),
+            label_suffix(fastest.1)
+        ),
+        match slowest.0 > 0.0 {
+            true => format!("ratio  : {:.2}x", ratio),
+            false => String::from("ratio  : inf"),
+        },
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("slowest", Value::Rate(Rate::from_decimal(slowest.0))),
+            ("slowest_label", Value::Text(slowest.1)),
+            ("fastest", Value::Rate(Rate::from_decimal(fastest.0))),
+            ("fastest_label", Value::Text(fastest.1)),
+            ("ratio", Value::Number(ratio)),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// Splits an optional leading label from a rate expression: rates always
+/// start with a digit, so anything else up to the first space is a label.
+fn split_label(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    if line.starts_with(|c: char| c.is_ascii_digit()) {
+        return ("", line);
+    }
+    match line.split_once(char::is_whitespace) {
+        Some((label, rest)) => return (label.trim_end_matches(':'), rest),
+        None => return ("", line),
+    }
+}
+
+fn label_suffix(label: &str) -> String {
+    if label.is_empty() {
+        return String::new();
+    }
+    return format!(" ({})", label);
+}
+
+#[test]
+fn test_split_label() {
+    assert_eq!(split_label("10 MB/s"), ("", "10 MB/s"));
+    assert_eq!(split_label("db2: 10 MB/s"), ("db2", "10 MB/s"));
+    assert_eq!(split_label("db2 10MB/s"), ("db2", "10MB/s"));
+}
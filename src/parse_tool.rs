@@ -0,0 +1,126 @@
+//! `rate parse-tool`: re-express the speed in rsync, curl or wget progress.
+
+use std::io::Read;
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_duration, parse_size, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " parse-tool [<progress line>] [--remaining <size>]\n",
+    "       Reads a progress line of rsync --progress, curl or wget (from the\n",
+    "       arguments, or the last one on stdin), and prints the speed it\n",
+    "       reports as a table. --remaining also gives the time left. These\n",
+    "       tools count in powers of 1024, e.g. \"12.3MB/s\" is 12.3 MiB/s."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let remaining: Option<f64> = pargs.opt_value_from_fn("--remaining", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let args: Vec<String> = pargs
+        .finish()
+        .iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    let mut text = args.join(" ");
+    if text.is_empty() {
+        std::io::stdin().read_to_string(&mut text)?;
+    }
+    // Progress meters redraw the same line with carriage returns.
+    let Some((native, speed)) = text.split(['\n', '\r']).rev().find_map(extract_speed) else {
+        return Err(Error::Usage(messages::text(Message::NoToolSpeed, &[])));
+    };
+    out.line(
+        &format!("reported : {}", native),
+        &[("reported", Value::Text(&native))],
+    )?;
+    out.table(Rate::from_decimal(speed))?;
+    if let Some(remaining) = remaining {
+        out.line(
+            &format!("eta      : {}", format_duration(remaining / speed)),
+            &[("eta", Value::Duration(remaining / speed))],
+        )?;
+    }
+    return Ok(());
+}
+
+/// Finds the speed in a progress line and returns it as written and in
+/// bytes per second. rsync and wget write e.g. "12.34MB/s" (wget may
+/// put a space before the unit); curl writes bare sizes like "98.5M",
+/// with the current speed in the last column of its meter.
+fn extract_speed(line: &str) -> Option<(String, f64)> {
+    let tokens: Vec<&str> = line
+        .split_whitespace()
+        .map(|t| t.trim_matches(|c| c == '(' || c == ')'))
+        .collect();
+    for (i, token) in tokens.iter().enumerate().rev() {
+        if let Some(unit) = token.strip_suffix("/s") {
+            let native = if unit.starts_with(|c: char| c.is_ascii_digit()) {
+                token.to_string()
+            } else if i > 0 {
+                format!("{} {}", tokens[i - 1], token)
+            } else {
+                continue;
+            };
+            let speed = parse_binary(native.trim_end_matches("/s"))?;
+            return Some((native, speed));
+        }
+    }
+    // curl: "100 1024M 100 1024M 0 0 98.5M 0 0:00:10 0:00:10 --:--:-- 100M"
+    if tokens.len() == 12 && tokens[8].contains(':') {
+        let speed = parse_binary(tokens[11])?;
+        return Some((tokens[11].to_string(), speed));
+    }
+    return None;
+}
+
+/// Parses sizes like "12.34MB", "12.3 MB", "850kB" or "98.5M" using
+/// powers of 1024, as these tools do.
+fn parse_binary(s: &str) -> Option<f64> {
+    let s = s.replace(',', "");
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.trim().trim_end_matches('B').trim_end_matches('i');
+    let power = match unit.to_ascii_uppercase().as_str() {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return None,
+    };
+    return Some(number * 1024f64.powi(power));
+}
+
+#[test]
+fn test_extract_speed() {
+    let mib = 1024.0 * 1024.0;
+    let rsync = "    524,288,000  48%   12.34MB/s    0:00:42";
+    assert_eq!(
+        extract_speed(rsync),
+        Some((String::from("12.34MB/s"), 12.34 * mib))
+    );
+    let wget = "big.iso  45%[=======>      ] 450.00M  12.5MB/s    eta 40s";
+    assert_eq!(
+        extract_speed(wget),
+        Some((String::from("12.5MB/s"), 12.5 * mib))
+    );
+    let wget_old = "2024-01-02 03:04:05 (850 KB/s) - 'file' saved";
+    assert_eq!(
+        extract_speed(wget_old),
+        Some((String::from("850 KB/s"), 850.0 * 1024.0))
+    );
+    let curl = "100 1024M  100 1024M    0     0  98.5M      0  0:00:10  0:00:10 --:--:--  100M";
+    assert_eq!(
+        extract_speed(curl),
+        Some((String::from("100M"), 100.0 * mib))
+    );
+    assert_eq!(extract_speed("nothing to see"), None);
+}
@@ -0,0 +1,134 @@
+//! `rate pcie`: bandwidth of a PCI Express link.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_bitrate, format_rate, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " pcie <gen1..gen7> x<lanes>\n",
+    "       Prints the raw signaling rate of the link, its bandwidth after\n",
+    "       line encoding (per direction), and the usual table."
+);
+
+struct Generation {
+    name: &'static str,
+    /// Gigatransfers per second per lane.
+    gts: f64,
+    encoding: &'static str,
+    efficiency: f64,
+}
+
+const GENERATIONS: &[Generation] = &[
+    Generation {
+        name: "gen1",
+        gts: 2.5,
+        encoding: "8b/10b",
+        efficiency: 8.0 / 10.0,
+    },
+    Generation {
+        name: "gen2",
+        gts: 5.0,
+        encoding: "8b/10b",
+        efficiency: 8.0 / 10.0,
+    },
+    Generation {
+        name: "gen3",
+        gts: 8.0,
+        encoding: "128b/130b",
+        efficiency: 128.0 / 130.0,
+    },
+    Generation {
+        name: "gen4",
+        gts: 16.0,
+        encoding: "128b/130b",
+        efficiency: 128.0 / 130.0,
+    },
+    Generation {
+        name: "gen5",
+        gts: 32.0,
+        encoding: "128b/130b",
+        efficiency: 128.0 / 130.0,
+    },
+    // From 6.0 on, data moves in 256-byte FLITs of which 242 bytes are
+    // TLP and DLLP payload, the rest being CRC and FEC.
+    Generation {
+        name: "gen6",
+        gts: 64.0,
+        encoding: "FLIT",
+        efficiency: 242.0 / 256.0,
+    },
+    Generation {
+        name: "gen7",
+        gts: 128.0,
+        encoding: "FLIT",
+        efficiency: 242.0 / 256.0,
+    },
+];
+
+const LANES: &[u32] = &[1, 2, 4, 8, 16, 32];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?.to_ascii_lowercase();
+    let mut words = args.split_whitespace();
+    let (Some(generation), Some(lanes), None) = (words.next(), words.next(), words.next()) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<generation> x<lanes>"],
+        )));
+    };
+    let generation = generation.trim_start_matches("pcie");
+    let generation = if generation.starts_with("gen") {
+        generation.to_string()
+    } else {
+        format!("gen{}", generation)
+    };
+    let Some(g) = GENERATIONS.iter().find(|g| g.name == generation) else {
+        let names: Vec<&str> = GENERATIONS.iter().map(|g| g.name).collect();
+        return Err(Error::Usage(messages::text(
+            Message::UnknownGeneration,
+            &[&format!("{:?}", generation), &names.join(" ")],
+        )));
+    };
+    let lanes: u32 = match lanes.trim_start_matches('x').parse() {
+        Ok(n) if LANES.contains(&n) => n,
+        _ => {
+            let lanes = format!("{:?}", lanes);
+            return Err(Error::Usage(messages::text(
+                Message::InvalidLanes,
+                &[&lanes, &"x1 x2 x4 x8 x16 x32"],
+            )));
+        }
+    };
+
+    let raw = g.gts * 1e9 / 8.0 * lanes as f64;
+    let effective = raw * g.efficiency;
+    let text = [
+        format!(
+            "link      : PCIe {} x{} ({} GT/s per lane, {})",
+            g.name, lanes, g.gts, g.encoding
+        ),
+        format!("raw       : {}", format_bitrate(raw)),
+        format!(
+            "effective : {} ({}) per direction",
+            format_rate(effective),
+            format_bitrate(effective)
+        ),
+    ];
+    let effective = Rate::from_decimal(effective);
+    out.line(
+        &text.join("\n"),
+        &[
+            ("generation", Value::Text(g.name)),
+            ("lanes", Value::Number(lanes as f64)),
+            ("raw", Value::Bitrate(Rate::from_decimal(raw))),
+            ("effective", Value::Rate(effective)),
+        ],
+    )?;
+    out.table(effective)?;
+    return Ok(());
+}
@@ -0,0 +1,249 @@
+//! `rate pipe`: copy stdin to stdout while reporting the throughput.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::diff::parse_snapshot;
+use crate::messages::{self, Message};
+use crate::report::{Reports, SampleLog};
+use crate::{
+    format_duration, format_size, parse, parse_duration, parse_size, remaining_args, Error, Rate,
+    RateEstimator, TokenBucket,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " pipe [--interval <duration>] [--limit <rate>] [--burst <size>]\n",
+    "       [--log <file>] [--summary-only] [--output <format>] [--state <file>]\n",
+    "       [--size <size>]\n",
+    "       Copies stdin to stdout unchanged, printing the amount copied and\n",
+    "       the current rate to stderr every --interval (1s by default), and\n",
+    "       the average rate at the end, e.g. tar c dir | rate pipe | ssh ...\n",
+    "       --limit holds the copy to a rate, allowing bursts of --burst\n",
+    "       (64KB by default). --log appends each sample to <file> as a\n",
+    "       line of JSON, which `rate log` reads. --summary-only prints the\n",
+    "       average only. --output json or csv prints a record per interval\n",
+    "       and one for the average. SIGUSR1 (or SIGINFO, ^T, where there\n",
+    "       is one) prints the status right away, as in dd. --state keeps\n",
+    "       the amount copied and the time spent in <file>, and starts from\n",
+    "       them, so that a copy that is restarted counts as one. --size\n",
+    "       shows a progress bar, the percentage and the time left towards\n",
+    "       <size>, as pv does."
+);
+
+/// Set when the status is asked for with a signal.
+static STATUS: AtomicBool = AtomicBool::new(false);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let interval: f64 = pargs
+        .opt_value_from_fn("--interval", parse_duration)?
+        .unwrap_or(1.0);
+    let limit: Option<f64> = pargs.opt_value_from_fn("--limit", parse)?;
+    let mut log = SampleLog::option(&mut pargs)?;
+    let mut reports = Reports::options(&mut pargs, Box::new(std::io::stderr()))?;
+    let state: Option<PathBuf> = pargs.opt_value_from_str("--state")?;
+    let burst: f64 = pargs
+        .opt_value_from_fn("--burst", parse_size)?
+        .unwrap_or(64e3);
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if interval <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+    if limit.is_some_and(|limit| limit <= 0.0) {
+        return Err(Error::Usage(messages::text(Message::LimitPositive, &[])));
+    }
+    let interval = Duration::try_from_secs_f64(interval)
+        .map_err(|_| Error::Usage(messages::text(Message::IntervalTooLong, &[])))?;
+    let mut bucket =
+        limit.map(|limit| TokenBucket::new(burst as u64, Rate::from_bytes_per_second(limit)));
+    let before = match &state {
+        Some(path) => State::load(path)?,
+        None => State::default(),
+    };
+
+    on_status_signal();
+    let mut input = std::io::stdin().lock();
+    let mut output = std::io::stdout().lock();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut total: u64 = 0;
+    let mut estimator = RateEstimator::new(interval.as_secs_f64(), interval.as_secs_f64());
+    estimator.push(0.0, 0);
+    let start = Instant::now();
+    let mut last = start;
+    loop {
+        if STATUS.swap(false, Ordering::Relaxed) {
+            let seconds = start.elapsed().as_secs_f64();
+            let rate = estimator
+                .instantaneous()
+                .unwrap_or(Rate::from_bytes_per_second(total as f64 / seconds));
+            eprintln!(
+                "{:>12} : {} copied, {}",
+                format_duration(before.seconds + seconds),
+                format_size((before.bytes + total) as f64),
+                rate
+            );
+        }
+        let n = match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::Io(e)),
+        };
+        if let Some(bucket) = &mut bucket {
+            bucket.acquire(n as u64);
+        }
+        output.write_all(&buf[..n])?;
+        total += n as u64;
+        let now = Instant::now();
+        if now - last >= interval {
+            output.flush()?;
+            estimator.push((now - start).as_secs_f64(), total);
+            if let Some(log) = &mut log {
+                log.write(total, &estimator)?;
+            }
+            let now = before.after(total, (now - start).as_secs_f64());
+            if let Some(path) = &state {
+                now.save(path)?;
+            }
+            let rate = estimator.instantaneous().unwrap();
+            let line = format!(
+                "{:>12} : {} copied, {}",
+                format_duration(now.seconds),
+                format_size(now.bytes as f64),
+                rate
+            );
+            reports.sample(now.seconds, now.bytes, rate, &line)?;
+            last = Instant::now();
+        }
+    }
+    output.flush()?;
+    let seconds = start.elapsed().as_secs_f64();
+    estimator.push(seconds, total);
+    if let Some(log) = &mut log {
+        log.write(total, &estimator)?;
+    }
+    let now = before.after(total, seconds);
+    if let Some(path) = &state {
+        now.save(path)?;
+    }
+    let average = match now.seconds > 0.0 {
+        true => Rate::from_bytes_per_second(now.bytes as f64 / now.seconds),
+        false => Rate::new(0, 1),
+    };
+    let line = format!(
+        "{:>12} : {} copied, {} on average",
+        format_duration(now.seconds),
+        format_size(now.bytes as f64),
+        average
+    );
+    reports.summary(now.seconds, now.bytes, average, &line)?;
+    return Ok(());
+}
+
+/// What `--state` keeps across runs: the bytes copied and the seconds
+/// spent copying them. The time between runs is not counted, so that the
+/// average is that of the copy.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct State {
+    bytes: u64,
+    seconds: f64,
+}
+
+impl State {
+    /// Reads a state file written by `save`; there is none on the first
+    /// run.
+    fn load(path: &Path) -> Result<State, Error> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(State::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let counters = parse_snapshot(&text);
+        let field = |name: &str| counters.iter().find(|(k, _)| k == name).map(|(_, v)| *v);
+        let (Some(bytes), Some(seconds)) = (field("bytes"), field("seconds")) else {
+            return Err(Error::Usage(messages::text(
+                Message::NotStateFile,
+                &[&path.display()],
+            )));
+        };
+        return Ok(State {
+            bytes: bytes as u64,
+            seconds,
+        });
+    }
+
+    /// The state once `bytes` more have been copied in `seconds`.
+    fn after(&self, bytes: u64, seconds: f64) -> State {
+        return State {
+            bytes: self.bytes + bytes,
+            seconds: self.seconds + seconds,
+        };
+    }
+
+    /// Writes the state to a temporary file renamed over `path`, so that
+    /// it is never left half written.
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let text = format!("bytes {}\nseconds {}\n", self.bytes, self.seconds);
+        std::fs::write(&temporary, text)?;
+        std::fs::rename(&temporary, path)?;
+        return Ok(());
+    }
+}
+
+/// Sets `STATUS` on SIGUSR1, and on SIGINFO where there is one. Reads
+/// are not restarted after the signal, so that the status is printed
+/// even while waiting for input.
+#[cfg(unix)]
+fn on_status_signal() {
+    extern "C" fn handler(_: libc::c_int) {
+        STATUS.store(true, Ordering::Relaxed);
+    }
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction =
+            handler as extern "C" fn(libc::c_int) as *const () as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        ))]
+        libc::sigaction(libc::SIGINFO, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+fn on_status_signal() {}
+
+#[test]
+fn test_state() {
+    let path = std::env::temp_dir().join(format!("rate-test-state-{}", std::process::id()));
+    assert_eq!(State::load(&path).unwrap(), State::default());
+    let state = State::default().after(1000, 2.5).after(500, 0.5);
+    assert_eq!(
+        state,
+        State {
+            bytes: 1500,
+            seconds: 3.0
+        }
+    );
+    state.save(&path).unwrap();
+    assert_eq!(State::load(&path).unwrap(), state);
+    std::fs::write(&path, "bytes 12\n").unwrap();
+    assert!(State::load(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,95 @@
+//! `rate plan`: staying within a metered data plan.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_rate, format_size, parse_size, period_to_seconds, remaining_args, Error,
+    ParseError, ParseErrorKind, Rate, DAY,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " plan <size>/<period> --day <n> --used <size>\n",
+    "       e.g. plan 50GB/month --day 12 --used 23GB\n",
+    "       Given the allowance of a plan, the days elapsed in the current\n",
+    "       period and the data used so far, prints the daily budget left\n",
+    "       and the sustained rate that stays within the plan."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let elapsed: f64 = pargs.value_from_str("--day")?;
+    let used: f64 = pargs.value_from_fn("--used", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let (allowance, period) = parse_allowance(&remaining_args(pargs)?)?;
+    let days = period / DAY as f64;
+    if !(0.0..days).contains(&elapsed) {
+        return Err(Error::Usage(messages::text(Message::DayRange, &[&days])));
+    }
+
+    let left = (allowance - used).max(0.0);
+    let days_left = days - elapsed;
+    let sustained = left / (days_left * DAY as f64);
+    let mut text = vec![
+        format!("allowance : {} per {} days", format_size(allowance), days),
+        format!(
+            "used      : {} ({:.1}%) after {} days",
+            format_size(used),
+            100.0 * used / allowance,
+            elapsed
+        ),
+        format!("left      : {} for {} days", format_size(left), days_left),
+        format!("daily     : {} / day", format_size(left / days_left)),
+        format!(
+            "sustained : {} ({})",
+            format_rate(sustained),
+            format_bitrate(sustained)
+        ),
+    ];
+    let mut fields = vec![
+        ("allowance", Value::Size(allowance)),
+        ("days", Value::Number(days)),
+        ("used", Value::Size(used)),
+        ("elapsed", Value::Number(elapsed)),
+        ("left", Value::Size(left)),
+        ("daily", Value::Size(left / days_left)),
+        ("sustained", Value::Rate(Rate::from_decimal(sustained))),
+    ];
+    if elapsed > 0.0 {
+        let projected = used / elapsed * days;
+        if projected > allowance {
+            text.push(format!(
+                "pace      : {} by the end, {} over",
+                format_size(projected),
+                format_size(projected - allowance)
+            ));
+        } else {
+            text.push(format!("pace      : {} by the end", format_size(projected)));
+        }
+        fields.push(("pace", Value::Size(projected)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Parses "<size>/<period>" into the size in bytes and the length of
+/// the period in seconds.
+pub fn parse_allowance(s: &str) -> Result<(f64, f64), ParseError> {
+    let (size, period) = s
+        .split_once('/')
+        .ok_or(ParseErrorKind::UnexpectedCharacter {
+            expected: b'/',
+            actual: 0,
+        })?;
+    let seconds = period_to_seconds(&period.trim().to_ascii_lowercase())?;
+    return Ok((parse_size(size)?, seconds as f64));
+}
+
+#[test]
+fn test_parse_allowance() {
+    assert_eq!(parse_allowance("50GB/month"), Ok((50e9, 30.0 * DAY as f64)));
+    assert_eq!(parse_allowance("1 TB / week"), Ok((1e12, 7.0 * DAY as f64)));
+    assert!(parse_allowance("50GB").is_err());
+}
@@ -0,0 +1,63 @@
+//! `rate pps`: packets per second to bandwidth and back.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_count, parse, parse_quantity, parse_size, remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " pps <packets>pps|<rate> [--frame <size>] [--overhead <size>]\n",
+    "       Converts a packet rate (e.g., 1.2Mpps) to bandwidth, or a link\n",
+    "       rate to the packets per second it can carry. --frame defaults to\n",
+    "       64B; --overhead is the per-frame cost on the wire, by default the\n",
+    "       20B of Ethernet preamble, start delimiter and inter-frame gap."
+);
+
+const ETHERNET_OVERHEAD: f64 = 20.0;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let frame: f64 = pargs
+        .opt_value_from_fn("--frame", parse_size)?
+        .unwrap_or(64.0);
+    let overhead: f64 = pargs
+        .opt_value_from_fn("--overhead", parse_size)?
+        .unwrap_or(ETHERNET_OVERHEAD);
+    let mut out = output(&mut pargs)?;
+    let arg = remaining_args(pargs)?;
+    if frame <= 0.0 {
+        return Err(Error::Usage(messages::text(
+            Message::FrameSizePositive,
+            &[],
+        )));
+    }
+    let wire = frame + overhead;
+
+    let lower = arg.to_ascii_lowercase();
+    let pps = match lower.strip_suffix("pps") {
+        Some(count) => parse_quantity(count)?,
+        // A link rate is what goes on the wire, overhead included.
+        None => parse(&arg)? / wire,
+    };
+    let text = [
+        format!("packets   : {}pps", format_count(pps)),
+        format!("frame     : {} B (+{} B on the wire)", frame, overhead),
+        format!("frames    : {}", format_bitrate(pps * frame)),
+        format!("line rate : {}", format_bitrate(pps * wire)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("packets", Value::Number(pps)),
+            ("frame", Value::Size(frame)),
+            ("overhead", Value::Size(overhead)),
+            ("frames", Value::Bitrate(Rate::from_decimal(pps * frame))),
+            ("line_rate", Value::Bitrate(Rate::from_decimal(pps * wire))),
+        ],
+    )?;
+    return Ok(());
+}
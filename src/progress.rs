@@ -0,0 +1,129 @@
+//! `rate progress`: what the progress bar of a transfer will look like.
+
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use rate::sink::{OutputSink, Value};
+
+use crate::messages::{self, Message};
+use crate::report::{output_format, progress_line};
+use crate::time::{now, parse_timestamp};
+use crate::{
+    config, parse, parse_duration, parse_input, parse_size, remaining_args, split_keyword, Error,
+    Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " progress <size> at <rate> [--start <time>] [--at <time>]\n",
+    "       [--speed <factor>] [--refresh <duration>]\n",
+    "       Renders the progress bar of a transfer that started at --start\n",
+    "       (Unix seconds or ISO 8601, now by default), advancing in real\n",
+    "       time until it is done. With --at, prints the state at that time\n",
+    "       instead. --speed plays the transfer faster, e.g. 60 to watch an\n",
+    "       hour in a minute."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let start: Option<String> = pargs.opt_value_from_str("--start")?;
+    let at: Option<String> = pargs.opt_value_from_str("--at")?;
+    let speed: f64 = pargs.opt_value_from_str("--speed")?.unwrap_or(1.0);
+    let refresh: f64 = pargs
+        .opt_value_from_fn("--refresh", parse_duration)?
+        .unwrap_or(1.0);
+    let format = output_format(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((size, rate)) = split_keyword(&args, "at") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<size> at <rate>"],
+        )));
+    };
+    let size = parse_input(&args, size, parse_size)?;
+    let rate = parse_input(&args, rate, parse)?;
+    if size <= 0.0 || rate <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::SizeRatePositive, &[])));
+    }
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(Error::Usage(messages::text(
+            Message::InvalidSpeed,
+            &[&speed],
+        )));
+    }
+    let refresh = Duration::try_from_secs_f64(refresh)
+        .map_err(|_| Error::Usage(messages::text(Message::InvalidRefresh, &[&refresh])))?;
+    let time = |s: &str| {
+        return parse_timestamp(s).ok_or_else(|| {
+            Error::Usage(messages::text(Message::InvalidTime, &[&format!("{:?}", s)]))
+        });
+    };
+    let started = now();
+    let start = match &start {
+        Some(s) => time(s)?,
+        None => started,
+    };
+
+    let mut out = config::get().sink_to(format.as_deref(), std::io::stdout());
+    if let Some(at) = at {
+        write(out.as_mut(), size, rate, time(&at)? - start)?;
+        return Ok(());
+    }
+    let live = format.is_none() && std::io::stdout().is_terminal();
+    loop {
+        let elapsed = started - start + (now() - started) * speed;
+        if live {
+            print!("\r{}\x1b[K", state(size, rate, elapsed));
+            std::io::stdout().flush()?;
+        } else {
+            write(out.as_mut(), size, rate, elapsed)?;
+        }
+        let left = (size / rate - elapsed) / speed;
+        if left <= 0.0 {
+            break;
+        }
+        let left = Duration::try_from_secs_f64(left).unwrap_or(Duration::MAX);
+        std::thread::sleep(refresh.min(left));
+    }
+    if live {
+        println!();
+    }
+    return Ok(());
+}
+
+/// The progress line of a transfer of `size` bytes at `rate` bytes per
+/// second, `elapsed` seconds after it started.
+fn state(size: f64, rate: f64, elapsed: f64) -> String {
+    let elapsed = elapsed.clamp(0.0, size / rate);
+    let done = (rate * elapsed).round().min(size);
+    return progress_line(elapsed, done as u64, Rate::from_decimal(rate), size);
+}
+
+/// Writes the state of the transfer `elapsed` seconds after it started,
+/// its progress line in text.
+fn write(out: &mut dyn OutputSink, size: f64, rate: f64, elapsed: f64) -> std::io::Result<()> {
+    let line = state(size, rate, elapsed);
+    let elapsed = elapsed.clamp(0.0, size / rate);
+    let done = (rate * elapsed).round().min(size);
+    return out.line(
+        &line,
+        &[
+            ("elapsed", Value::Duration(elapsed)),
+            ("bytes", Value::Size(done)),
+            ("rate", Value::Rate(Rate::from_decimal(rate))),
+            ("percent", Value::Number(done / size * 100.0)),
+            ("eta", Value::Duration(size / rate - elapsed)),
+        ],
+    );
+}
+
+#[test]
+fn test_state() {
+    let rate = 1e6;
+    assert_eq!(
+        state(1e9, rate, 300.0),
+        "300.000 MB 0:05:00 [1.000 MB / sec] [======>             ]  30% ETA 0:11:40"
+    );
+    assert!(state(1e9, rate, -5.0).starts_with("   0.000 B 0:00:00"));
+    assert!(state(1e9, rate, 5000.0).ends_with("] 100% ETA 0:00:00"));
+}
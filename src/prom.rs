@@ -0,0 +1,184 @@
+//! `rate prom`: the rate of a Prometheus counter over a window.
+
+use std::io::Read;
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_duration, format_size, parse_duration, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " prom <counter> [--server <url>] [--window <duration>]\n",
+    "       Asks the Prometheus server (http://localhost:9090 by default)\n",
+    "       how much a byte counter such as\n",
+    "       'node_network_transmit_bytes_total{device=\"eth0\"}' increased\n",
+    "       over the last --window (5m by default), and prints the rate of\n",
+    "       each series it matches as a table."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let server: String = pargs
+        .opt_value_from_str("--server")?
+        .unwrap_or_else(|| String::from("http://localhost:9090"));
+    let window: f64 = pargs
+        .opt_value_from_fn("--window", parse_duration)?
+        .unwrap_or(300.0);
+    let mut out = output(&mut pargs)?;
+    let counter: String = pargs.free_from_str()?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if window < 1.0 {
+        return Err(Error::Usage(messages::text(Message::WindowSecond, &[])));
+    }
+
+    // increase() extrapolates to the whole window and handles counter
+    // resets, as the rate() of a dashboard would.
+    let query = format!("increase({}[{}s])", counter, window.round());
+    let url = format!("{}/api/v1/query", server.trim_end_matches('/'));
+    let body = match ureq::get(&url).query("query", &query).call() {
+        Ok(response) => response.into_string()?,
+        // Prometheus explains bad queries in the body of a 400.
+        Err(ureq::Error::Status(_, response)) => {
+            let mut body = String::new();
+            response.into_reader().read_to_string(&mut body)?;
+            match json_string_field(&body, "error") {
+                Some(e) => return Err(Error::Usage(format!("prometheus: {}", e))),
+                None => return Err(Error::Usage(format!("prometheus: {}", body.trim()))),
+            }
+        }
+        Err(e) => return Err(Error::Io(std::io::Error::other(e))),
+    };
+    let series = parse_vector(&body).map_err(|e| Error::Usage(format!("prometheus: {}", e)))?;
+    if series.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NoSeries, &[&counter])));
+    }
+    for (i, (labels, increase)) in series.iter().enumerate() {
+        if i > 0 {
+            out.line("", &[])?;
+        }
+        let text = format!(
+            "{} : {} in {}",
+            labels,
+            format_size(*increase),
+            format_duration(window)
+        );
+        out.line(
+            &text,
+            &[
+                ("series", Value::Text(labels)),
+                ("increase", Value::Size(*increase)),
+                ("window", Value::Duration(window)),
+            ],
+        )?;
+        out.table(Rate::from_decimal(increase / window))?;
+    }
+    return Ok(());
+}
+
+/// Reads the series of the answer to an instant query: their labels as
+/// `{device="eth0"}` and their values.
+fn parse_vector(body: &str) -> Result<Vec<(String, f64)>, String> {
+    if json_string_field(body, "status").as_deref() != Some("success") {
+        let error = json_string_field(body, "error");
+        return Err(error.unwrap_or_else(|| String::from("unexpected answer")));
+    }
+    let mut series = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("\"metric\":") {
+        rest = rest[start + "\"metric\":".len()..].trim_start();
+        let Some(mut object) = rest.strip_prefix('{') else {
+            return Err(String::from("expected the labels of a series"));
+        };
+        let mut labels = Vec::new();
+        loop {
+            object = object.trim_start().trim_start_matches(',').trim_start();
+            if let Some(after) = object.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+            let (name, after) = json_string(object).ok_or("expected a label name")?;
+            let after = after.trim_start().strip_prefix(':').ok_or("expected ':'")?;
+            let (value, after) = json_string(after.trim_start()).ok_or("expected a label value")?;
+            if name != "__name__" {
+                labels.push(format!("{}={:?}", name, value));
+            }
+            object = after;
+        }
+        let start = rest
+            .find("\"value\":")
+            .ok_or("expected the value of a series")?;
+        rest = &rest[start..];
+        // "value":[<timestamp>,"<value>"]
+        let value = rest
+            .find(',')
+            .and_then(|comma| json_string(rest[comma + 1..].trim_start()));
+        let Some((value, after)) = value else {
+            return Err(String::from("expected the value of a series"));
+        };
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("invalid value {:?}", value))?;
+        series.push((format!("{{{}}}", labels.join(", ")), value));
+        rest = after;
+    }
+    return Ok(series);
+}
+
+/// The value of the first string field `name` in a JSON document.
+fn json_string_field(body: &str, name: &str) -> Option<String> {
+    let key = format!("\"{}\":", name);
+    let start = body.find(&key)? + key.len();
+    return json_string(body[start..].trim_start()).map(|(s, _)| s);
+}
+
+/// Reads the JSON string at the start of `s`, and returns it with what
+/// follows it.
+fn json_string(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                c => out.push(c),
+            },
+            c => out.push(c),
+        }
+    }
+    return None;
+}
+
+#[test]
+fn test_parse_vector() {
+    let body = r#"{"status":"success","data":{"resultType":"vector","result":[
+        {"metric":{"__name__":"node_network_transmit_bytes_total","device":"eth0","instance":"web:9100"},"value":[1700000000.123,"36000000"]},
+        {"metric":{},"value":[1700000000.123,"1.5e3"]}]}}"#;
+    assert_eq!(
+        parse_vector(body),
+        Ok(vec![
+            (
+                String::from("{device=\"eth0\", instance=\"web:9100\"}"),
+                36000000.0
+            ),
+            (String::from("{}"), 1500.0),
+        ])
+    );
+    let body = r#"{"status":"success","data":{"resultType":"vector","result":[]}}"#;
+    assert_eq!(parse_vector(body), Ok(vec![]));
+    let body =
+        r#"{"status":"error","errorType":"bad_data","error":"parse error: unexpected \"}\""}"#;
+    assert_eq!(
+        parse_vector(body),
+        Err(String::from("parse error: unexpected \"}\""))
+    );
+}
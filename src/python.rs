@@ -0,0 +1,92 @@
+//! Python bindings, with the `python` feature.
+//!
+//! Build the extension module with
+//! `cargo rustc --lib --release --no-default-features
+//! --features python,pyo3/extension-module --crate-type cdylib`
+//! and copy target/release/librate.so to `rate.so` (`rate.pyd` on
+//! Windows) on the Python path, or build it with maturin.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{format_table, period_to_seconds, ParseError, Rate};
+
+fn value_error(s: &str, e: ParseError) -> PyErr {
+    return PyValueError::new_err(format!("{:?}: {}", s, e));
+}
+
+/// A data rate, e.g. `rate.Rate("12 MB/s")`.
+#[pyclass(name = "Rate", frozen)]
+struct PyRate(Rate);
+
+#[pymethods]
+impl PyRate {
+    #[new]
+    fn new(s: &str) -> PyResult<Self> {
+        return crate::parse(s).map(PyRate).map_err(|e| value_error(s, e));
+    }
+
+    #[staticmethod]
+    fn from_bytes_per_second(bytes_per_second: f64) -> Self {
+        return PyRate(Rate::from_bytes_per_second(bytes_per_second));
+    }
+
+    #[getter]
+    fn bytes_per_second(&self) -> f64 {
+        return self.0.bytes_per_second();
+    }
+
+    #[getter]
+    fn bits_per_second(&self) -> f64 {
+        return self.0.bits_per_second();
+    }
+
+    /// The number of bytes transferred over a period, e.g. "day".
+    fn per(&self, period: &str) -> PyResult<f64> {
+        let seconds = period_to_seconds(period).map_err(|e| value_error(period, e))?;
+        return Ok(self.0.bytes_per_second() * seconds as f64);
+    }
+
+    /// The conversion table printed by the command-line tool.
+    fn table(&self) -> String {
+        return format_table(self.0);
+    }
+
+    fn __str__(&self) -> String {
+        return self.0.to_string();
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "Rate.from_bytes_per_second({:?})",
+            self.0.bytes_per_second()
+        );
+    }
+}
+
+/// Parses a rate expression, e.g. "14TB/day".
+#[pyfunction]
+fn parse(s: &str) -> PyResult<PyRate> {
+    return PyRate::new(s);
+}
+
+/// Parses a data size, e.g. "800 GB", and returns it in bytes.
+#[pyfunction]
+fn parse_size(s: &str) -> PyResult<f64> {
+    return crate::parse_size(s).map_err(|e| value_error(s, e));
+}
+
+/// Parses a duration, e.g. "1h30m", and returns it in seconds.
+#[pyfunction]
+fn parse_duration(s: &str) -> PyResult<f64> {
+    return crate::parse_duration(s).map_err(|e| value_error(s, e));
+}
+
+#[pymodule]
+fn rate(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRate>()?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_size, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_duration, m)?)?;
+    return Ok(());
+}
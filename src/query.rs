@@ -0,0 +1,62 @@
+//! `rate query`: read a rate from a running `rate daemon`.
+
+use std::path::PathBuf;
+
+use rate::sink::Value;
+
+use crate::daemon::default_socket;
+use crate::report::output;
+use crate::{remaining_args, Error};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " query [<source>] [--socket <path>]\n",
+    "       Prints the latest rates of a source sampled by `rate daemon`,\n",
+    "       or of all its sources."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let socket: PathBuf = pargs
+        .opt_value_from_str("--socket")?
+        .unwrap_or_else(default_socket);
+    let mut out = output(&mut pargs)?;
+    let name = remaining_args(pargs)?;
+    let reply = ask(&socket, &name)?;
+    if let Some(message) = reply.strip_prefix("error: ") {
+        return Err(Error::Usage(message.trim_end().to_string()));
+    }
+    // The daemon replies with a "<source> : <rate>" line per rate.
+    for line in reply.lines() {
+        let Some((key, rate)) = line.split_once(" : ") else {
+            out.line(line, &[])?;
+            continue;
+        };
+        match rate::parse(rate) {
+            Ok(rate) => out.line(
+                line,
+                &[("source", Value::Text(key)), ("rate", Value::Rate(rate))],
+            )?,
+            Err(_) => out.line(line, &[])?,
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(unix)]
+fn ask(socket: &std::path::Path, name: &str) -> Result<String, Error> {
+    use std::io::{Read, Write};
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    stream.write_all(format!("{}\n", name).as_bytes())?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    return Ok(reply);
+}
+
+#[cfg(not(unix))]
+fn ask(_socket: &std::path::Path, _name: &str) -> Result<String, Error> {
+    return Err(Error::Usage(crate::messages::text(
+        crate::messages::Message::UnixOnly,
+        &[&"query"],
+    )));
+}
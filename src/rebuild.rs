@@ -0,0 +1,114 @@
+//! `rate rebuild`: how long an array stays degraded while a disk rebuilds.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_percent, parse_size, remaining_args,
+    Error, Rate, YEAR,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " rebuild <disk capacity> [--speed <rate> | --preset <name>] [--load <ratio>]\n",
+    "       [--disks <n>] [--afr <ratio>]\n",
+    "       Estimates the rebuild of one disk, during which the array runs\n",
+    "       with reduced redundancy. --load is the share of the rebuild speed\n",
+    "       lost to the regular workload (e.g., 40%). Presets: hdd5400 hdd\n",
+    "       sata-ssd nvme (hdd by default). With --disks, the size of the\n",
+    "       array, also estimates the odds that another disk fails during\n",
+    "       the rebuild, from an annualized failure rate (--afr, 2% default)."
+);
+
+/// Typical sustained sequential write speeds.
+const PRESETS: &[(&str, f64)] = &[
+    ("hdd5400", 100e6),
+    ("hdd", 180e6),
+    ("sata-ssd", 450e6),
+    ("nvme", 2000e6),
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let speed: Option<f64> = pargs.opt_value_from_fn("--speed", parse)?;
+    let preset: Option<String> = pargs.opt_value_from_str("--preset")?;
+    let load: f64 = pargs
+        .opt_value_from_fn("--load", parse_percent)?
+        .unwrap_or(0.0);
+    let disks: Option<u32> = pargs.opt_value_from_str("--disks")?;
+    let afr: f64 = pargs
+        .opt_value_from_fn("--afr", parse_percent)?
+        .unwrap_or(0.02);
+    let mut out = output(&mut pargs)?;
+    let capacity = parse_size(&remaining_args(pargs)?)?;
+    let speed = match (speed, preset) {
+        (Some(_), Some(_)) => {
+            return Err(Error::Usage(messages::text(
+                Message::Exclusive,
+                &[&"--speed", &"--preset"],
+            )))
+        }
+        (Some(speed), None) => speed,
+        (None, preset) => {
+            let name = preset.unwrap_or_else(|| String::from("hdd"));
+            let Some((_, speed)) = PRESETS.iter().find(|(n, _)| *n == name) else {
+                let names: Vec<&str> = PRESETS.iter().map(|(n, _)| *n).collect();
+                return Err(Error::Usage(messages::text(
+                    Message::UnknownPreset,
+                    &[&format!("{:?}", name), &names.join(" ")],
+                )));
+            };
+            *speed
+        }
+    };
+    if load >= 1.0 {
+        return Err(Error::Usage(messages::text(Message::LoadUnder, &[])));
+    }
+
+    let effective = speed * (1.0 - load);
+    let mut text = vec![
+        format!("disk      : {}", format_size(capacity)),
+        format!("speed     : {}", format_rate(speed)),
+    ];
+    if load > 0.0 {
+        text.push(format!(
+            "effective : {} ({:.0}% lost to load)",
+            format_rate(effective),
+            load * 100.0
+        ));
+    }
+    let window = capacity / effective;
+    text.push(format!("rebuild   : {}", format_duration(window)));
+    let mut fields = vec![
+        ("disk", Value::Size(capacity)),
+        ("speed", Value::Rate(Rate::from_decimal(speed))),
+        ("effective", Value::Rate(Rate::from_decimal(effective))),
+        ("rebuild", Value::Duration(window)),
+    ];
+    if let Some(disks) = disks {
+        let risk = second_failure(window, disks.saturating_sub(1), afr);
+        text.push(format!(
+            "risk      : {:.3}% that another of the {} disks fails meanwhile",
+            100.0 * risk,
+            disks.saturating_sub(1)
+        ));
+        fields.push(("risk", Value::Number(risk)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// The probability that at least one of `disks` disks fails within
+/// `seconds`, for independent failures at an annualized rate of `afr`.
+fn second_failure(seconds: f64, disks: u32, afr: f64) -> f64 {
+    let survive_one = (1.0 - afr).powf(seconds / YEAR as f64);
+    return 1.0 - survive_one.powi(disks as i32);
+}
+
+#[test]
+fn test_second_failure() {
+    assert!((second_failure(YEAR as f64, 1, 0.02) - 0.02).abs() < 1e-12);
+    assert_eq!(second_failure(YEAR as f64, 0, 0.02), 0.0);
+    assert!((second_failure(YEAR as f64, 2, 0.5) - 0.75).abs() < 1e-12);
+}
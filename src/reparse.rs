@@ -0,0 +1,262 @@
+//! `rate reparse`: the throughput columns of iostat, vmstat or sar output,
+//! re-expressed in the units and output formats of rate.
+
+use std::io::Read;
+
+use rate::sink::Value;
+use rate::Rate;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_rate, optional_arg, Error};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " reparse --from <tool> [<file>] [--output <format>]\n",
+    "       Reads the output of iostat (-d, -x, -m), vmstat or sar (-d, -n DEV)\n",
+    "       from <file> or stdin, and prints the rates of its throughput\n",
+    "       columns. These tools count in powers of 1024 (vmstat in 1 KiB\n",
+    "       blocks, old sar in 512-byte sectors), which is converted."
+);
+
+/// How a tool lays out its reports: the header of the column that names
+/// the device or interface (if any), and its throughput columns with the
+/// name of the field they go to and the bytes in their unit.
+struct Dialect {
+    tool: &'static str,
+    names: &'static [&'static str],
+    columns: &'static [(&'static str, &'static str, f64)],
+}
+
+const KIB: f64 = 1024.0;
+const MIB: f64 = 1024.0 * 1024.0;
+
+const DIALECTS: &[Dialect] = &[
+    Dialect {
+        tool: "iostat",
+        names: &["Device", "Device:"],
+        columns: &[
+            ("kB_read/s", "read", KIB),
+            ("kB_wrtn/s", "write", KIB),
+            ("MB_read/s", "read", MIB),
+            ("MB_wrtn/s", "write", MIB),
+            ("rkB/s", "read", KIB),
+            ("wkB/s", "write", KIB),
+            ("rMB/s", "read", MIB),
+            ("wMB/s", "write", MIB),
+        ],
+    },
+    Dialect {
+        tool: "vmstat",
+        names: &[],
+        columns: &[
+            ("bi", "read", KIB),
+            ("bo", "write", KIB),
+            ("si", "swap in", KIB),
+            ("so", "swap out", KIB),
+        ],
+    },
+    Dialect {
+        tool: "sar",
+        names: &["DEV", "IFACE"],
+        columns: &[
+            ("rkB/s", "read", KIB),
+            ("wkB/s", "write", KIB),
+            ("rd_sec/s", "read", 512.0),
+            ("wr_sec/s", "write", 512.0),
+            ("rxkB/s", "rx", KIB),
+            ("txkB/s", "tx", KIB),
+        ],
+    },
+];
+
+/// A row of a report: the device or interface, if the tool names one,
+/// and its rates in bytes per second.
+#[derive(Debug, PartialEq)]
+struct Sample {
+    source: Option<String>,
+    rates: Vec<(&'static str, f64)>,
+}
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let tool: String = pargs.value_from_str("--from")?;
+    let mut out = output(&mut pargs)?;
+    let path = optional_arg(pargs)?;
+    let Some(dialect) = DIALECTS.iter().find(|d| d.tool == tool) else {
+        let tools: Vec<&str> = DIALECTS.iter().map(|d| d.tool).collect();
+        return Err(Error::Usage(messages::text(
+            Message::UnknownTool,
+            &[&format!("{:?}", tool), &tools.join(" ")],
+        )));
+    };
+
+    let mut text = String::new();
+    match path {
+        Some(path) => text = std::fs::read_to_string(path)?,
+        None => _ = std::io::stdin().read_to_string(&mut text)?,
+    }
+    let samples = parse_report(dialect, &text);
+    if samples.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::NoThroughputColumns,
+            &[&dialect.tool],
+        )));
+    }
+
+    let label = |s: &Sample, field: &str| match &s.source {
+        Some(source) => format!("{} {}", source, field),
+        None => field.to_string(),
+    };
+    let width = samples
+        .iter()
+        .flat_map(|s| s.rates.iter().map(|(f, _)| label(s, f).len()))
+        .max()
+        .unwrap_or(0);
+    for sample in &samples {
+        let mut text: Vec<String> = Vec::new();
+        let mut fields: Vec<(&str, Value)> = Vec::new();
+        if let Some(source) = &sample.source {
+            fields.push(("source", Value::Text(source)));
+        }
+        for (field, rate) in &sample.rates {
+            let label = label(sample, field);
+            text.push(format!(
+                "{:<width$} : {}",
+                label,
+                format_rate(*rate),
+                width = width
+            ));
+            fields.push((field, Value::Rate(Rate::from_bytes_per_second(*rate))));
+        }
+        out.line(&text.join("\n"), &fields)?;
+    }
+    return Ok(());
+}
+
+/// Reads the rows of each report of the tool. A header line (one with a
+/// throughput column) starts a report and a blank line ends it. Rows are
+/// matched to the header from the right, since sar writes "Average:"
+/// where its reports have a time and "AM"/"PM".
+fn parse_report(dialect: &Dialect, text: &str) -> Vec<Sample> {
+    let mut samples = Vec::new();
+    let mut header: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            header = None;
+            continue;
+        }
+        if tokens
+            .iter()
+            .any(|t| dialect.columns.iter().any(|(c, _, _)| c == t))
+        {
+            header = Some(tokens);
+            continue;
+        }
+        let Some(header) = &header else {
+            continue;
+        };
+        let Some(offset) = header.len().checked_sub(tokens.len()) else {
+            continue;
+        };
+        let column = |name: &str| {
+            let i = header.iter().position(|h| *h == name)?;
+            return tokens.get(i.checked_sub(offset)?).copied();
+        };
+        let mut rates = Vec::new();
+        for (name, field, unit) in dialect.columns {
+            if let Some(value) = column(name) {
+                let Ok(value) = value.replace(',', ".").parse::<f64>() else {
+                    continue;
+                };
+                rates.push((*field, value * unit));
+            }
+        }
+        if rates.is_empty() {
+            continue;
+        }
+        let mut source = dialect
+            .names
+            .iter()
+            .find_map(|n| column(n))
+            .map(String::from);
+        if tokens[0] == "Average:" {
+            source = source.map(|s| format!("{} (average)", s));
+        }
+        samples.push(Sample { source, rates });
+    }
+    return samples;
+}
+
+#[test]
+fn test_parse_iostat() {
+    let text = "\
+Linux 6.1.0 (host) \t01/02/2024 \t_x86_64_\t(8 CPU)
+
+avg-cpu:  %user   %nice %system %iowait  %steal   %idle
+           1.20    0.00    0.50    0.10    0.00   98.20
+
+Device             tps    kB_read/s    kB_wrtn/s    kB_dscd/s    kB_read    kB_wrtn    kB_dscd
+nvme0n1          12.00       100.00      2048.00         0.00     500000    9000000          0
+";
+    let iostat = &DIALECTS[0];
+    assert_eq!(
+        parse_report(iostat, text),
+        vec![Sample {
+            source: Some(String::from("nvme0n1")),
+            rates: vec![("read", 102400.0), ("write", 2097152.0)],
+        }]
+    );
+    let text = "\
+Device            r/s     rMB/s   rrqm/s  %rrqm r_await rareq-sz     w/s     wMB/s
+sda              1.00      0.50     0.00   0.00    0.10   512.00    2.00      1.50
+";
+    assert_eq!(
+        parse_report(iostat, text)[0].rates,
+        vec![("read", 0.5 * MIB), ("write", 1.5 * MIB)]
+    );
+}
+
+#[test]
+fn test_parse_vmstat() {
+    let text = "\
+procs -----------memory---------- ---swap-- -----io---- -system-- ------cpu-----
+ r  b   swpd   free   buff  cache   si   so    bi    bo   in   cs us sy id wa st
+ 1  0      0 800000  50000 900000    0    4   100   200  300  400  2  1 97  0  0
+";
+    assert_eq!(
+        parse_report(&DIALECTS[1], text),
+        vec![Sample {
+            source: None,
+            rates: vec![
+                ("read", 102400.0),
+                ("write", 204800.0),
+                ("swap in", 0.0),
+                ("swap out", 4096.0),
+            ],
+        }]
+    );
+}
+
+#[test]
+fn test_parse_sar() {
+    let text = "\
+12:00:01 AM     IFACE   rxpck/s   txpck/s    rxkB/s    txkB/s   rxcmp/s   txcmp/s  rxmcst/s   %ifutil
+12:10:01 AM      eth0    100.00     50.00   1000.00     20.00      0.00      0.00      0.00      0.82
+Average:         eth0    100.00     50.00    500.00     10.00      0.00      0.00      0.00      0.41
+";
+    let samples = parse_report(&DIALECTS[2], text);
+    assert_eq!(samples[0].source.as_deref(), Some("eth0"));
+    assert_eq!(samples[0].rates, vec![("rx", 1024000.0), ("tx", 20480.0)]);
+    assert_eq!(samples[1].source.as_deref(), Some("eth0 (average)"));
+    assert_eq!(samples[1].rates, vec![("rx", 512000.0), ("tx", 10240.0)]);
+    let text = "\
+12:00:01        DEV       tps  rd_sec/s  wr_sec/s
+12:10:01     dev8-0      5.00    200.00    400.00
+";
+    assert_eq!(
+        parse_report(&DIALECTS[2], text)[0].rates,
+        vec![("read", 102400.0), ("write", 204800.0)]
+    );
+}
@@ -0,0 +1,87 @@
+//! `rate repo`: transfers of many small files.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_count, format_duration, format_rate, format_size, parse, parse_duration, parse_input,
+    parse_quantity, parse_size, remaining_args, split_keyword, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " repo <count> files of <size> at <rate> --latency <duration>\n",
+    "       [--parallel <n>]\n",
+    "       Estimates copying many files of the given average size, each\n",
+    "       paying --latency of protocol round trips (open, create, close)\n",
+    "       on top of its bytes at <rate>. --parallel files are in flight at\n",
+    "       once, sharing the rate (1 by default)."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let latency: f64 = pargs.value_from_fn("--latency", parse_duration)?;
+    let parallel: usize = pargs.opt_value_from_str("--parallel")?.unwrap_or(1);
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let usage = || {
+        Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<count> files of <size> at <rate>"],
+        ))
+    };
+    let (files, rate) = split_keyword(&args, "at").ok_or_else(usage)?;
+    let (count, size) = split_keyword(files, "of").ok_or_else(usage)?;
+    let count = count.strip_suffix("files").unwrap_or(count).trim_end();
+    let count = parse_input(&args, count, parse_quantity)?.round();
+    let size = parse_input(&args, size, parse_size)?;
+    let rate = parse_input(&args, rate, parse)?;
+    if count < 1.0 || rate <= 0.0 || parallel == 0 {
+        return Err(Error::Usage(messages::text(Message::RepoPositive, &[])));
+    }
+
+    let (transfer, waiting) = estimate(count, size, latency, rate, parallel);
+    let seconds = transfer + waiting;
+    let throughput = count * size / seconds;
+    let text = [
+        format!("files      : {}", format_count(count)),
+        format!("total      : {}", format_size(count * size)),
+        format!("duration   : {}", format_duration(seconds)),
+        format!("throughput : {}", format_rate(throughput)),
+        format!(
+            "latency    : {} ({:.0}% of the time)",
+            format_duration(waiting),
+            waiting / seconds * 100.0
+        ),
+        format!("naive      : {} (size / rate)", format_duration(transfer)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("files", Value::Number(count)),
+            ("total", Value::Size(count * size)),
+            ("duration", Value::Duration(seconds)),
+            ("throughput", Value::Rate(Rate::from_decimal(throughput))),
+            ("latency", Value::Duration(waiting)),
+            ("naive", Value::Duration(transfer)),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// The seconds spent moving the bytes and those spent waiting on the
+/// files' round trips, `parallel` files at a time.
+fn estimate(count: f64, size: f64, latency: f64, rate: f64, parallel: usize) -> (f64, f64) {
+    let transfer = count * size / rate;
+    let waves = (count / parallel as f64).ceil();
+    return (transfer, waves * latency);
+}
+
+#[test]
+fn test_estimate() {
+    // 1000 files of 1 KB at 1 MB/s: a second of bytes, 5 of latency.
+    assert_eq!(estimate(1000.0, 1e3, 0.005, 1e6, 1), (1.0, 5.0));
+    // 8 at a time wait in 125 waves.
+    assert_eq!(estimate(1000.0, 1e3, 0.005, 1e6, 8), (1.0, 0.625));
+}
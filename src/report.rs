@@ -0,0 +1,223 @@
+//! Reporting shared by the monitoring subcommands: the lines or records
+//! of each interval, progress bars in the manner of pv, and the samples
+//! they log for `rate log` to read back.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use rate::sink::{JsonSink, OutputSink, Value};
+
+use crate::messages::{self, Message};
+use crate::{config, format_size, parse_size, Error, Rate, RateEstimator};
+
+/// The `--output <format>` option of a subcommand: the sink that its
+/// results are written to, on stdout.
+pub fn output(pargs: &mut pico_args::Arguments) -> Result<Box<dyn OutputSink>, Error> {
+    let format = output_format(pargs)?;
+    return Ok(config::get().sink_to(format.as_deref(), std::io::stdout()));
+}
+
+/// The format of the `--output <format>` option, the configured one by
+/// default, or `None` for text.
+pub fn output_format(pargs: &mut pico_args::Arguments) -> Result<Option<String>, Error> {
+    let format = match pargs.opt_value_from_str::<_, String>("--output")? {
+        Some(format) => Some(config::output_format(&format).map_err(Error::Usage)?),
+        None => config::get().output.clone(),
+    };
+    return Ok(format.filter(|f| f != "text"));
+}
+
+/// The `--log <file>` option of the monitoring subcommands: appends one
+/// JSON object per sample to the file, for `rate log` to read back.
+pub struct SampleLog {
+    sink: JsonSink<File>,
+    total: u64,
+}
+
+impl SampleLog {
+    pub fn option(pargs: &mut pico_args::Arguments) -> Result<Option<SampleLog>, Error> {
+        let Some(path) = pargs.opt_value_from_str::<_, PathBuf>("--log")? else {
+            return Ok(None);
+        };
+        let file = File::options().create(true).append(true).open(path)?;
+        return Ok(Some(SampleLog {
+            sink: JsonSink::new(file),
+            total: 0,
+        }));
+    }
+
+    /// Logs the sample just pushed to `estimator`, at which point `total`
+    /// bytes had been transferred.
+    pub fn write(&mut self, total: u64, estimator: &RateEstimator) -> Result<(), Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let zero = rate::Rate::new(0, 1);
+        self.sink.record(&[
+            ("timestamp", Value::Number(now.as_secs_f64())),
+            ("bytes", Value::Size((total - self.total) as f64)),
+            ("total", Value::Size(total as f64)),
+            (
+                "instantaneous",
+                Value::Rate(estimator.instantaneous().unwrap_or(zero)),
+            ),
+            ("average", Value::Rate(estimator.mean().unwrap_or(zero))),
+        ])?;
+        self.total = total;
+        return Ok(());
+    }
+}
+
+/// The `--summary-only`, `--output <format>` and `--size <size>` options
+/// of the streaming subcommands: whether a line is printed every
+/// interval, whether the lines are text or records of an output format,
+/// one per interval and one for the average at the end, and whether they
+/// show the progress towards a total size, as pv does.
+pub struct Reports {
+    summary_only: bool,
+    size: Option<f64>,
+    text: bool,
+    sink: Box<dyn OutputSink>,
+}
+
+impl Reports {
+    pub fn options(
+        pargs: &mut pico_args::Arguments,
+        out: Box<dyn Write>,
+    ) -> Result<Reports, Error> {
+        let summary_only = pargs.contains("--summary-only");
+        let size: Option<f64> = pargs.opt_value_from_fn("--size", parse_size)?;
+        if size.is_some_and(|size| size <= 0.0) {
+            return Err(Error::Usage(messages::text(Message::SizePositive, &[])));
+        }
+        // The configured output format is for results: progress stays
+        // text unless asked otherwise.
+        let format = match pargs.opt_value_from_str::<_, String>("--output")? {
+            Some(format) if format != "text" => {
+                Some(config::output_format(&format).map_err(Error::Usage)?)
+            }
+            _ => None,
+        };
+        return Ok(Reports {
+            summary_only,
+            size,
+            text: format.is_none(),
+            sink: config::get().sink_to(format.as_deref(), out),
+        });
+    }
+
+    /// Reports the sample of an interval, `line` in text unless there is
+    /// a `--size`, in which case it is a progress bar.
+    pub fn sample(
+        &mut self,
+        seconds: f64,
+        total: u64,
+        rate: Rate,
+        line: &str,
+    ) -> Result<(), Error> {
+        if self.summary_only {
+            return Ok(());
+        }
+        if let Some(size) = self.size {
+            let line = progress_line(seconds, total, rate, size);
+            return self.write(seconds, total, rate, &line);
+        }
+        return self.write(seconds, total, rate, line);
+    }
+
+    /// Reports the average at the end, `line` in text. With an output
+    /// format, returns false, and the caller prints nothing more.
+    pub fn summary(
+        &mut self,
+        seconds: f64,
+        total: u64,
+        rate: Rate,
+        line: &str,
+    ) -> Result<bool, Error> {
+        self.write(seconds, total, rate, line)?;
+        return Ok(self.text);
+    }
+
+    /// Writes the table of `rate`, after the summary in text.
+    pub fn table(&mut self, rate: Rate) -> Result<(), Error> {
+        self.sink.table(rate)?;
+        return Ok(());
+    }
+
+    fn write(&mut self, seconds: f64, total: u64, rate: Rate, line: &str) -> Result<(), Error> {
+        let mut fields = vec![
+            ("elapsed", Value::Duration(seconds)),
+            ("bytes", Value::Size(total as f64)),
+            ("rate", Value::Rate(rate)),
+        ];
+        if let Some(size) = self.size {
+            let (percent, eta) = progress(total, rate, size);
+            fields.push(("percent", Value::Number(percent)));
+            fields.push(("eta", Value::Duration(eta)));
+        }
+        self.sink.line(line, &fields)?;
+        return Ok(());
+    }
+}
+
+/// The percentage of `size` done and the seconds left at `rate`.
+fn progress(total: u64, rate: Rate, size: f64) -> (f64, f64) {
+    let percent = (total as f64 / size * 100.0).min(100.0);
+    let left = (size - total as f64).max(0.0);
+    let eta = match rate.bytes_per_second() > 0.0 {
+        true => left / rate.bytes_per_second(),
+        false => f64::INFINITY,
+    };
+    return (percent, eta);
+}
+
+/// A line in the manner of pv: the amount, the time, the rate, a bar,
+/// the percentage and the time left, e.g.
+/// `  1.200 GB 0:00:30 [40.000 MB / sec] [======>             ]  30% ETA 0:01:10`
+pub fn progress_line(seconds: f64, total: u64, rate: Rate, size: f64) -> String {
+    const WIDTH: usize = 20;
+    let (percent, eta) = progress(total, rate, size);
+    let done = (percent / 100.0 * WIDTH as f64) as usize;
+    let bar = match done {
+        WIDTH => "=".repeat(WIDTH),
+        _ => format!("{}>{}", "=".repeat(done), " ".repeat(WIDTH - done - 1)),
+    };
+    return format!(
+        "{:>10} {} [{}] [{}] {:>3.0}% ETA {}",
+        format_size(total as f64),
+        clock(seconds),
+        rate,
+        bar,
+        percent.floor(),
+        clock(eta)
+    );
+}
+
+/// Seconds as H:MM:SS, or "-:--:--" when they are not known.
+fn clock(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return String::from("-:--:--");
+    }
+    let seconds = seconds.round() as u64;
+    return format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+}
+
+#[test]
+fn test_progress_line() {
+    let rate = Rate::from_bytes_per_second(40e6);
+    assert_eq!(
+        progress_line(30.0, 1_200_000_000, rate, 4e9),
+        "  1.200 GB 0:00:30 [40.000 MB / sec] [======>             ]  30% ETA 0:01:10"
+    );
+    assert_eq!(
+        progress_line(3725.0, 5_000_000_000, rate, 4e9),
+        "  5.000 GB 1:02:05 [40.000 MB / sec] [====================] 100% ETA 0:00:00"
+    );
+    assert!(progress_line(1.0, 0, Rate::new(0, 1), 4e9).ends_with("  0% ETA -:--:--"));
+}
@@ -0,0 +1,135 @@
+//! `rate s3`: how long a parallel multipart upload takes.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_duration, parse_size, remaining_args,
+    Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " s3 <size> --per-connection <rate> [--part <size>] [--concurrency <n>]\n",
+    "       [--latency <duration>] [--link <rate>]\n",
+    "       Estimates a multipart upload where each part costs a request\n",
+    "       round trip on top of its transfer. Defaults match the AWS CLI:\n",
+    "       8MB parts, 10 at a time, with 50ms of latency per request.\n",
+    "       --link caps the combined rate of all the connections."
+);
+
+/// S3 refuses uploads of more parts than this.
+const MAX_PARTS: f64 = 10_000.0;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let connection: f64 = pargs.value_from_fn("--per-connection", parse)?;
+    let part: f64 = pargs
+        .opt_value_from_fn("--part", parse_size)?
+        .unwrap_or(8e6);
+    let concurrency: usize = pargs.opt_value_from_str("--concurrency")?.unwrap_or(10);
+    let latency: f64 = pargs
+        .opt_value_from_fn("--latency", parse_duration)?
+        .unwrap_or(0.05);
+    let link: Option<f64> = pargs.opt_value_from_fn("--link", parse)?;
+    let mut out = output(&mut pargs)?;
+    let size = parse_size(&remaining_args(pargs)?)?;
+    if part <= 0.0 || concurrency == 0 || connection <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::S3Positive, &[])));
+    }
+
+    let upload = estimate(size, part, concurrency, connection, latency, link);
+    if upload.parts as f64 > MAX_PARTS {
+        eprintln!("{}", messages::text(Message::S3Parts, &[&MAX_PARTS]));
+    }
+    let text = [
+        format!("parts      : {} of {}", upload.parts, format_size(part)),
+        format!(
+            "waves      : {} of up to {} parts",
+            upload.waves, concurrency
+        ),
+        format!("duration   : {}", format_duration(upload.seconds)),
+        format!("throughput : {}", format_rate(size / upload.seconds)),
+        format!(
+            "naive      : {} (size / bandwidth)",
+            format_duration(size / upload.bandwidth)
+        ),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("parts", Value::Number(upload.parts as f64)),
+            ("part", Value::Size(part)),
+            ("waves", Value::Number(upload.waves as f64)),
+            ("duration", Value::Duration(upload.seconds)),
+            (
+                "throughput",
+                Value::Rate(Rate::from_decimal(size / upload.seconds)),
+            ),
+            ("naive", Value::Duration(size / upload.bandwidth)),
+        ],
+    )?;
+    return Ok(());
+}
+
+struct Upload {
+    parts: usize,
+    waves: usize,
+    seconds: f64,
+    bandwidth: f64,
+}
+
+/// Uploads the parts in waves of `concurrency`, each part paying one
+/// request latency, plus a round trip to create and one to complete
+/// the upload.
+fn estimate(
+    size: f64,
+    part: f64,
+    concurrency: usize,
+    connection: f64,
+    latency: f64,
+    link: Option<f64>,
+) -> Upload {
+    let parts = ((size / part).ceil() as usize).max(1);
+    let active = concurrency.min(parts);
+    let mut per_connection = connection;
+    if let Some(link) = link {
+        per_connection = per_connection.min(link / active as f64);
+    }
+    let waves = parts.div_ceil(concurrency);
+    let last_part = size - (parts - 1) as f64 * part;
+    // All waves but the last move full parts; the last wave is as slow
+    // as its biggest part, which is a full one unless it is alone.
+    let slowest_last = if parts % concurrency == 1 || concurrency == 1 {
+        last_part
+    } else {
+        part.min(size)
+    };
+    let seconds = (waves - 1) as f64 * (latency + part / per_connection)
+        + latency
+        + slowest_last / per_connection
+        + 2.0 * latency;
+    return Upload {
+        parts,
+        waves,
+        seconds,
+        bandwidth: per_connection * active as f64,
+    };
+}
+
+#[test]
+fn test_estimate() {
+    // 4 parts of 10 bytes at 1 B/s, 2 at a time, no latency: 2 waves.
+    let u = estimate(40.0, 10.0, 2, 1.0, 0.0, None);
+    assert_eq!((u.parts, u.waves, u.seconds), (4, 2, 20.0));
+    // Each wave pays a second of latency, plus two for create/complete.
+    let u = estimate(40.0, 10.0, 2, 1.0, 1.0, None);
+    assert_eq!(u.seconds, 24.0);
+    // A lone, smaller last part.
+    let u = estimate(25.0, 10.0, 2, 1.0, 0.0, None);
+    assert_eq!((u.parts, u.waves, u.seconds), (3, 2, 15.0));
+    // The link is shared between the connections.
+    let u = estimate(40.0, 10.0, 2, 1.0, 0.0, Some(1.0));
+    assert_eq!(u.seconds, 40.0);
+}
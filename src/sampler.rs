@@ -0,0 +1,150 @@
+//! The counters of the system that the monitoring commands turn into
+//! rates. Each platform has its own backend, in a `platform` module.
+
+use std::io;
+
+/// A source of cumulative byte counters.
+pub trait Sampler {
+    /// Reads every counter, keyed by name, in a stable order.
+    fn sample(&mut self) -> io::Result<Vec<(String, f64)>>;
+}
+
+/// The bytes received and sent by each network interface, keyed
+/// "<interface> rx" and "<interface> tx" as in the snapshots of
+/// `rate diff`.
+pub struct Interfaces;
+
+impl Sampler for Interfaces {
+    fn sample(&mut self) -> io::Result<Vec<(String, f64)>> {
+        return platform::interfaces();
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::diff::parse_snapshot;
+
+    pub fn interfaces() -> std::io::Result<Vec<(String, f64)>> {
+        return Ok(parse_snapshot(&std::fs::read_to_string("/proc/net/dev")?));
+    }
+}
+
+/// The interface table of the IP Helper API: GetIfTable2 fills a table
+/// of MIB_IF_ROW2, which is declared here with only the fields used.
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct MibIfRow2 {
+        _identifiers: [u32; 7],
+        alias: [u16; 257],
+        _description: [u16; 257],
+        _addresses: [u32; 17],
+        _types: [u32; 7],
+        flags: u8,
+        _status: [u32; 3],
+        _network: [u32; 5],
+        _speeds: [u64; 2],
+        in_octets: u64,
+        _in: [u64; 8],
+        out_octets: u64,
+        _out: [u64; 8],
+    }
+
+    const _: () = assert!(std::mem::size_of::<MibIfRow2>() == 1352);
+
+    #[repr(C)]
+    struct MibIfTable2 {
+        entries: u32,
+        table: [MibIfRow2; 1],
+    }
+
+    /// The bit of `flags` set on the filter drivers that Windows lists
+    /// next to each adapter, with the same counters.
+    const FILTER_INTERFACE: u8 = 0b10;
+
+    #[link(name = "iphlpapi")]
+    extern "system" {
+        fn GetIfTable2(table: *mut *mut MibIfTable2) -> u32;
+        fn FreeMibTable(memory: *const c_void);
+    }
+
+    pub fn interfaces() -> std::io::Result<Vec<(String, f64)>> {
+        let mut table: *mut MibIfTable2 = std::ptr::null_mut();
+        let status = unsafe { GetIfTable2(&mut table) };
+        if status != 0 {
+            return Err(std::io::Error::from_raw_os_error(status as i32));
+        }
+        let mut counters = Vec::new();
+        unsafe {
+            let rows = std::ptr::addr_of!((*table).table) as *const MibIfRow2;
+            for i in 0..(*table).entries as usize {
+                let row = &*rows.add(i);
+                if row.flags & FILTER_INTERFACE != 0 {
+                    continue;
+                }
+                let len = row.alias.iter().position(|&c| c == 0).unwrap_or(256);
+                let name = String::from_utf16_lossy(&row.alias[..len]);
+                counters.push((format!("{} rx", name), row.in_octets as f64));
+                counters.push((format!("{} tx", name), row.out_octets as f64));
+            }
+            FreeMibTable(table as *const c_void);
+        }
+        return Ok(counters);
+    }
+}
+
+/// The interface list of the routing sysctl: NET_RT_IFLIST2 returns an
+/// if_msghdr2, with 64-bit counters, per interface.
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::CStr;
+
+    pub fn interfaces() -> std::io::Result<Vec<(String, f64)>> {
+        let mut mib = [libc::CTL_NET, libc::PF_ROUTE, 0, 0, libc::NET_RT_IFLIST2, 0];
+        let mut len: libc::size_t = 0;
+        let null = std::ptr::null_mut();
+        if unsafe { libc::sysctl(mib.as_mut_ptr(), 6, null, &mut len, null, 0) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut buf = vec![0u8; len];
+        let data = buf.as_mut_ptr() as *mut libc::c_void;
+        if unsafe { libc::sysctl(mib.as_mut_ptr(), 6, data, &mut len, null, 0) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut counters = Vec::new();
+        let mut offset = 0;
+        while offset + std::mem::size_of::<libc::if_msghdr2>() <= len {
+            let message = buf[offset..].as_ptr() as *const libc::if_msghdr2;
+            let message = unsafe { std::ptr::read_unaligned(message) };
+            if message.ifm_msglen == 0 {
+                break;
+            }
+            offset += message.ifm_msglen as usize;
+            if message.ifm_type as libc::c_int != libc::RTM_IFINFO2 {
+                continue;
+            }
+            let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+            let index = message.ifm_index as libc::c_uint;
+            if unsafe { libc::if_indextoname(index, name.as_mut_ptr()) }.is_null() {
+                continue;
+            }
+            let name = unsafe { CStr::from_ptr(name.as_ptr()) }.to_string_lossy();
+            let (rx, tx) = (message.ifm_data.ifi_ibytes, message.ifm_data.ifi_obytes);
+            counters.push((format!("{} rx", name), rx as f64));
+            counters.push((format!("{} tx", name), tx as f64));
+        }
+        return Ok(counters);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    pub fn interfaces() -> std::io::Result<Vec<(String, f64)>> {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            crate::messages::text(crate::messages::Message::CountersUnsupported, &[]),
+        ));
+    }
+}
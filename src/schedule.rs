@@ -0,0 +1,149 @@
+//! `rate schedule`: transfers that may only run during daily windows.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::time::{format_timestamp, now, parse_timestamp};
+use crate::{
+    compression_option, format_duration, format_size, parse, parse_input, parse_size,
+    remaining_args, split_keyword, Error, DAY,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " schedule <size> at <rate> --window <HH:MM-HH:MM>... [--start <time>]\n",
+    "       [--compress <ratio>]\n",
+    "       Plans a transfer that only runs during the given daily windows\n",
+    "       (UTC; a window may wrap past midnight). --start is Unix seconds\n",
+    "       or ISO 8601 and defaults to now. --compress (e.g., 2.3:1 or 57%)\n",
+    "       scales the rate by a compression ratio."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let windows: Vec<(f64, f64)> = pargs.values_from_fn("--window", parse_window)?;
+    let start: Option<String> = pargs.opt_value_from_str("--start")?;
+    let compression = compression_option(&mut pargs)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((size, rate)) = split_keyword(&args, "at") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<size> at <rate>"],
+        )));
+    };
+    let size = parse_input(&args, size, parse_size)?;
+    let rate = parse_input(&args, rate, parse)? * compression;
+    if windows.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NeedWindow, &[])));
+    }
+    if rate <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::RatePositive, &[])));
+    }
+    let start = match start {
+        Some(s) => parse_timestamp(&s).ok_or_else(|| {
+            Error::Usage(messages::text(
+                Message::InvalidStart,
+                &[&format!("{:?}", s)],
+            ))
+        })?,
+        None => now(),
+    };
+
+    let per_day: f64 = windows.iter().map(|(s, e)| e - s).sum();
+    let (finish, used) = plan(size, rate, &windows, start);
+    let finish_at = format_timestamp(finish);
+    let text = [
+        format!(
+            "per day  : {} in {}",
+            format_size(per_day * rate),
+            format_duration(per_day)
+        ),
+        format!("windows  : {}", used),
+        format!("elapsed  : {}", format_duration(finish - start)),
+        format!("finish   : {} UTC", finish_at),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("per_day", Value::Size(per_day * rate)),
+            ("window", Value::Duration(per_day)),
+            ("windows", Value::Number(used as f64)),
+            ("elapsed", Value::Duration(finish - start)),
+            ("finish", Value::Text(&finish_at)),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// Parses "HH:MM-HH:MM" into start and end offsets from midnight, in
+/// seconds; the end is pushed to the next day if the window wraps.
+pub fn parse_window(s: &str) -> Result<(f64, f64), String> {
+    let invalid = || format!("invalid window {:?}, expected HH:MM-HH:MM", s);
+    let (from, to) = s.split_once('-').ok_or_else(invalid)?;
+    let from = parse_clock(from).ok_or_else(invalid)?;
+    let mut to = parse_clock(to).ok_or_else(invalid)?;
+    if to <= from {
+        to += DAY as f64;
+    }
+    return Ok((from, to));
+}
+
+fn parse_clock(s: &str) -> Option<f64> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u64, u64) = (h.parse().ok()?, m.parse().ok()?);
+    if h > 24 || m > 59 || (h == 24 && m != 0) {
+        return None;
+    }
+    return Some((h * 3600 + m * 60) as f64);
+}
+
+/// Walks the windows day by day from `start` until `size` bytes have
+/// been sent. Returns the finish time and the number of windows used.
+fn plan(size: f64, rate: f64, windows: &[(f64, f64)], start: f64) -> (f64, usize) {
+    let day = DAY as f64;
+    let mut left = size;
+    let mut used = 0;
+    // Start a day early to catch windows that wrap into the first day.
+    let mut midnight = (start / day).floor() * day - day;
+    loop {
+        let mut today: Vec<(f64, f64)> = windows
+            .iter()
+            .map(|(s, e)| (midnight + s, midnight + e))
+            .filter(|(_, e)| *e > start)
+            .collect();
+        today.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (from, to) in today {
+            let from = from.max(start);
+            let capacity = (to - from) * rate;
+            used += 1;
+            if left <= capacity {
+                return (from + left / rate, used);
+            }
+            left -= capacity;
+        }
+        midnight += day;
+    }
+}
+
+#[test]
+fn test_parse_window() {
+    assert_eq!(parse_window("01:00-05:00"), Ok((3600.0, 18000.0)));
+    assert_eq!(parse_window("22:00-02:00"), Ok((79200.0, 93600.0)));
+    assert!(parse_window("25:00-02:00").is_err());
+    assert!(parse_window("01:00").is_err());
+}
+
+#[test]
+fn test_plan() {
+    // 1 byte/s from 01:00 to 05:00: 14400 bytes per night.
+    let windows = [(3600.0, 18000.0)];
+    assert_eq!(plan(14400.0, 1.0, &windows, 0.0), (18000.0, 1));
+    assert_eq!(plan(20000.0, 1.0, &windows, 0.0), (86400.0 + 9200.0, 2));
+    // Starting mid-window only uses what is left of it.
+    assert_eq!(plan(1000.0, 1.0, &windows, 17500.0), (86400.0 + 4100.0, 2));
+    // A window wrapping past midnight, starting right after midnight.
+    let windows = [(79200.0, 93600.0)];
+    assert_eq!(plan(100.0, 1.0, &windows, 86400.0), (86500.0, 1));
+}
@@ -0,0 +1,149 @@
+//! Serde support: rates, units and periods are (de)serialized as the
+//! strings the parser reads, e.g. "12 MB/sec", "MB" and "sec".
+
+use alloc::format;
+use alloc::string::String;
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::{parse, Period, Rate, RateRow, Unit, PERIODS, UNITS};
+
+impl Serialize for Rate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&exact_string(*self));
+    }
+}
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        return parse(&s).map_err(|e| D::Error::custom(format!("{:?}: {}", s, e)));
+    }
+}
+
+impl Serialize for Unit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(self.symbol);
+    }
+}
+
+impl<'de> Deserialize<'de> for Unit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        match UNITS.iter().find(|u| u.symbol.eq_ignore_ascii_case(&s)) {
+            Some(unit) => return Ok(*unit),
+            None => return Err(D::Error::custom(format!("unknown unit {:?}", s))),
+        }
+    }
+}
+
+impl Serialize for Period {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(self.name);
+    }
+}
+
+impl<'de> Deserialize<'de> for Period {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        return s
+            .parse()
+            .map_err(|e| D::Error::custom(format!("{:?}: {}", s, e)));
+    }
+}
+
+impl Serialize for RateRow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut row = serializer.serialize_struct("RateRow", 5)?;
+        row.serialize_field("period", &self.period)?;
+        row.serialize_field("seconds", &self.seconds)?;
+        row.serialize_field("scaled_value", &self.scaled_value)?;
+        row.serialize_field("unit", &self.unit)?;
+        row.serialize_field("raw_bytes", &self.raw_bytes)?;
+        return row.end();
+    }
+}
+
+/// Writes a rate as a decimal number in the shortest period and the
+/// largest unit that represent it exactly, so that parsing the string
+/// gives back the same rate (e.g. "1 TB/day" rather than "11.574... MB/s").
+fn exact_string(rate: Rate) -> String {
+    for period in PERIODS {
+        let (bytes, seconds) = rate.over(period.seconds);
+        for unit in UNITS.iter().rev() {
+            let Some(denominator) = seconds.checked_mul(unit.bytes) else {
+                continue;
+            };
+            if bytes < denominator && unit.bytes > 1 {
+                continue;
+            }
+            if let Some(amount) = decimal(bytes, denominator) {
+                return format!("{} {}/{}", amount, unit.symbol, period.name);
+            }
+            break;
+        }
+    }
+    return format!("{} B/s", rate.bytes_per_second());
+}
+
+/// Writes the fraction `numerator / denominator` in decimal, if it has a
+/// finite expansion that fits in a u128.
+fn decimal(numerator: u128, denominator: u128) -> Option<String> {
+    let rate = Rate::new(numerator, denominator);
+    let (numerator, denominator) = (rate.bytes, rate.seconds);
+    let mut rest = denominator;
+    let (mut twos, mut fives) = (0, 0);
+    while rest > 0 && rest % 2 == 0 {
+        rest /= 2;
+        twos += 1;
+    }
+    while rest > 0 && rest % 5 == 0 {
+        rest /= 5;
+        fives += 1;
+    }
+    if rest != 1 {
+        return None;
+    }
+    let places = u32::max(twos, fives);
+    let scale = 10u128.checked_pow(places)?;
+    let digits = numerator.checked_mul(scale / denominator)?;
+    if places == 0 {
+        return Some(format!("{}", digits));
+    }
+    return Some(format!(
+        "{}.{:0width$}",
+        digits / scale,
+        digits % scale,
+        width = places as usize
+    ));
+}
+
+#[test]
+fn test_exact_string() {
+    assert_eq!(exact_string(parse("12 MB/s").unwrap()), "12 MB/sec");
+    assert_eq!(exact_string(parse("1 Gb/s").unwrap()), "125 MB/sec");
+    assert_eq!(exact_string(parse("1 TB/day").unwrap()), "1 TB/day");
+    assert_eq!(exact_string(parse("0.5 B/s").unwrap()), "0.5 B/sec");
+    for s in [
+        "1 TB/day",
+        "0.5 B/s",
+        "3.3 KB/min",
+        "7 YB/year",
+        "0.001 b/year",
+    ] {
+        let rate = parse(s).unwrap();
+        assert_eq!(parse(&exact_string(rate)), Ok(rate));
+    }
+}
+
+#[test]
+fn test_deserialize() {
+    use serde::de::value::{Error, StrDeserializer};
+    let rate = Rate::deserialize(StrDeserializer::<Error>::new("12 MB/s"));
+    assert_eq!(rate, Ok(Rate::from_bytes_per_second(12e6)));
+    let unit = Unit::deserialize(StrDeserializer::<Error>::new("GB"));
+    assert_eq!(unit.map(|u| u.bytes), Ok(1_000_000_000));
+    let period = Period::deserialize(StrDeserializer::<Error>::new("hr"));
+    assert_eq!(period.map(|p| p.name), Ok("hour"));
+    assert!(Rate::deserialize(StrDeserializer::<Error>::new("12 MB")).is_err());
+}
@@ -0,0 +1,204 @@
+//! `rate simulate`: transfers whose rate changes over the day.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::schedule::parse_window;
+use crate::time::{format_timestamp, now, parse_timestamp};
+use crate::{
+    format_duration, format_rate, format_size, parse, parse_input, parse_size, remaining_args,
+    Error, Rate, DAY,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " simulate <size> --slot <HH:MM-HH:MM=rate>... [--otherwise <rate>]\n",
+    "       [--start <time>]\n",
+    "       Runs a transfer through a daily schedule of rates (UTC; a slot\n",
+    "       may wrap past midnight), e.g. --slot 09:00-17:00=100Mb/s to\n",
+    "       throttle it in office hours. The rest of the day runs at\n",
+    "       --otherwise, or not at all. --start is Unix seconds or ISO 8601\n",
+    "       and defaults to now. Prints when it completes and how much is\n",
+    "       sent each day."
+);
+
+/// The most days a transfer is simulated over, one line each.
+const MAX_DAYS: f64 = 10000.0;
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let slots: Vec<(f64, f64, f64)> = pargs.values_from_fn("--slot", parse_slot)?;
+    let otherwise: f64 = pargs
+        .opt_value_from_fn("--otherwise", parse)?
+        .unwrap_or(0.0);
+    let start: Option<String> = pargs.opt_value_from_str("--start")?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let size = parse_input(&args, &args, parse_size)?;
+    if size <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::SizePositive, &[])));
+    }
+    if slots.is_empty() {
+        return Err(Error::Usage(messages::text(Message::NeedSlot, &[])));
+    }
+    let segments = segments(&slots, otherwise).map_err(Error::Usage)?;
+    if segments.iter().all(|(_, _, rate)| *rate <= 0.0) {
+        return Err(Error::Usage(messages::text(Message::NeverSends, &[])));
+    }
+    let start = match start {
+        Some(s) => parse_timestamp(&s).ok_or_else(|| {
+            Error::Usage(messages::text(
+                Message::InvalidStart,
+                &[&format!("{:?}", s)],
+            ))
+        })?,
+        None => now(),
+    };
+
+    let per_day: f64 = segments.iter().map(|(s, e, rate)| (e - s) * rate).sum();
+    // A day more for a start partway through the first one.
+    if size / per_day + 1.0 > MAX_DAYS {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyDays,
+            &[&format_size(size), &format_size(per_day), &MAX_DAYS],
+        )));
+    }
+    let (finish, days) = simulate(size, &segments, start);
+    let finish_at = format_timestamp(finish);
+    let text = [
+        format!("per day  : {}", format_size(per_day)),
+        format!("average  : {}", format_rate(per_day / DAY as f64)),
+        format!("elapsed  : {}", format_duration(finish - start)),
+        format!("finish   : {} UTC", finish_at),
+        String::new(),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("per_day", Value::Size(per_day)),
+            (
+                "average",
+                Value::Rate(Rate::from_decimal(per_day / DAY as f64)),
+            ),
+            ("elapsed", Value::Duration(finish - start)),
+            ("finish", Value::Text(&finish_at)),
+        ],
+    )?;
+    let mut sent = 0.0;
+    for (midnight, bytes) in days {
+        sent += bytes;
+        let day = &format_timestamp(midnight)[..10];
+        let text = format!(
+            "{} : {:>10} {:>4.0}%",
+            day,
+            format_size(bytes),
+            (sent / size * 100.0).floor()
+        );
+        out.line(
+            &text,
+            &[
+                ("day", Value::Text(day)),
+                ("bytes", Value::Size(bytes)),
+                ("sent", Value::Size(sent)),
+            ],
+        )?;
+    }
+    return Ok(());
+}
+
+/// Parses "HH:MM-HH:MM=<rate>" into the window of the slot, as offsets
+/// from midnight, and its rate in bytes per second.
+fn parse_slot(s: &str) -> Result<(f64, f64, f64), String> {
+    let Some((window, rate)) = s.split_once('=') else {
+        return Err(format!("invalid slot {:?}, expected HH:MM-HH:MM=<rate>", s));
+    };
+    let (from, to) = parse_window(window)?;
+    let rate = parse(rate.trim()).map_err(|e| format!("{}: {:?}", e, rate))?;
+    return Ok((from, to, rate));
+}
+
+/// The slots as consecutive segments of a day, from midnight to
+/// midnight, the gaps between them at the rate `otherwise`.
+fn segments(slots: &[(f64, f64, f64)], otherwise: f64) -> Result<Vec<(f64, f64, f64)>, String> {
+    let day = DAY as f64;
+    let mut slots: Vec<(f64, f64, f64)> = slots
+        .iter()
+        .flat_map(|&(from, to, rate)| match to > day {
+            true => vec![(from, day, rate), (0.0, to - day, rate)],
+            false => vec![(from, to, rate)],
+        })
+        .collect();
+    slots.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut segments = Vec::new();
+    let mut end = 0.0;
+    for (from, to, rate) in slots {
+        if from < end {
+            return Err(String::from("the slots overlap"));
+        }
+        if from > end {
+            segments.push((end, from, otherwise));
+        }
+        segments.push((from, to, rate));
+        end = to;
+    }
+    if end < day {
+        segments.push((end, day, otherwise));
+    }
+    return Ok(segments);
+}
+
+/// Sends `size` bytes through the segments day by day from `start`.
+/// Returns the finish time and the bytes sent on each day, by midnight.
+fn simulate(size: f64, segments: &[(f64, f64, f64)], start: f64) -> (f64, Vec<(f64, f64)>) {
+    let day = DAY as f64;
+    let mut left = size;
+    let mut days = Vec::new();
+    let mut midnight = (start / day).floor() * day;
+    loop {
+        let mut sent = 0.0;
+        for (from, to, rate) in segments {
+            let (from, to) = ((midnight + from).max(start), midnight + to);
+            if to <= from || *rate <= 0.0 {
+                continue;
+            }
+            let capacity = (to - from) * rate;
+            if left <= capacity {
+                days.push((midnight, sent + left));
+                return (from + left / rate, days);
+            }
+            left -= capacity;
+            sent += capacity;
+        }
+        days.push((midnight, sent));
+        midnight += day;
+    }
+}
+
+#[test]
+fn test_segments() {
+    let slots = [(9.0 * 3600.0, 17.0 * 3600.0, 1.0), (79200.0, 93600.0, 4.0)];
+    assert_eq!(
+        segments(&slots, 2.0),
+        Ok(vec![
+            (0.0, 7200.0, 4.0),
+            (7200.0, 32400.0, 2.0),
+            (32400.0, 61200.0, 1.0),
+            (61200.0, 79200.0, 2.0),
+            (79200.0, 86400.0, 4.0),
+        ])
+    );
+    assert!(segments(&[(0.0, 7200.0, 1.0), (3600.0, 9000.0, 1.0)], 0.0).is_err());
+    assert!(parse_slot("09:00-17:00").is_err());
+}
+
+#[test]
+fn test_simulate() {
+    // 1 byte/s in the first half of the day, 3 bytes/s in the second.
+    let segments = [(0.0, 43200.0, 1.0), (43200.0, 86400.0, 3.0)];
+    let (finish, days) = simulate(43200.0 + 3.0 * 43200.0 + 100.0, &segments, 0.0);
+    assert_eq!(finish, 86500.0);
+    assert_eq!(days, [(0.0, 172800.0), (86400.0, 100.0)]);
+    // Starting in the afternoon only counts what is left of it.
+    assert_eq!(simulate(400.0, &segments, 86300.0).0, 86400.0 + 100.0);
+}
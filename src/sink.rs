@@ -0,0 +1,414 @@
+//! Output formats: the same results written as text, JSON, CSV or
+//! Prometheus metrics, through one trait.
+
+use std::io::{self, Write};
+
+use crate::{format_bitrate, format_duration, format_size, Rate, RateFormatter};
+
+/// A value in a record, typed so that each sink can render it its own
+/// way: `TextSink` as "12.000 MB / sec", `JsonSink` as a plain number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value<'a> {
+    Rate(Rate),
+    /// A rate shown in bits, e.g. "1.000 Gb / sec" for a link. Other
+    /// formats than text have it in bytes per second like any rate.
+    Bitrate(Rate),
+    /// A size in bytes.
+    Size(f64),
+    /// A duration in seconds.
+    Duration(f64),
+    Number(f64),
+    Text(&'a str),
+}
+
+/// Where results are written. Implement it to add an output format.
+pub trait OutputSink {
+    /// Writes the table of how much data `rate` amounts to over each
+    /// period.
+    fn table(&mut self, rate: Rate) -> io::Result<()>;
+
+    /// Writes a set of named values, e.g. one measurement.
+    fn record(&mut self, fields: &[(&str, Value)]) -> io::Result<()>;
+
+    /// Writes a line laid out for people, such as a row of a table, a
+    /// chart or a blank line, whose data is `fields`: text sinks write
+    /// `text`, the others a record of `fields`, if there are any.
+    fn line(&mut self, text: &str, fields: &[(&str, Value)]) -> io::Result<()> {
+        let _ = text;
+        if fields.is_empty() {
+            return Ok(());
+        }
+        return self.record(fields);
+    }
+}
+
+/// The human-readable output of the command line.
+pub struct TextSink<W: Write> {
+    out: W,
+    formatter: RateFormatter,
+}
+
+impl<W: Write> TextSink<W> {
+    /// A sink that writes tables as `format_table` does.
+    pub fn new(out: W) -> TextSink<W> {
+        return TextSink {
+            out,
+            formatter: RateFormatter::new(),
+        };
+    }
+
+    /// Writes tables with `formatter` instead.
+    pub fn formatter(mut self, formatter: RateFormatter) -> TextSink<W> {
+        self.formatter = formatter;
+        return self;
+    }
+}
+
+impl<W: Write> OutputSink for TextSink<W> {
+    fn table(&mut self, rate: Rate) -> io::Result<()> {
+        return write!(self.out, "{}", self.formatter.format_table(rate));
+    }
+
+    fn record(&mut self, fields: &[(&str, Value)]) -> io::Result<()> {
+        let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, value) in fields {
+            let value = match value {
+                Value::Rate(rate) => rate.to_string(),
+                Value::Bitrate(rate) => format_bitrate(rate.bytes_per_second()),
+                Value::Size(bytes) => format_size(*bytes),
+                Value::Duration(seconds) => format_duration(*seconds),
+                Value::Number(n) => n.to_string(),
+                Value::Text(s) => s.to_string(),
+            };
+            writeln!(self.out, "{:<width$} : {}", name, value)?;
+        }
+        return Ok(());
+    }
+
+    fn line(&mut self, text: &str, _: &[(&str, Value)]) -> io::Result<()> {
+        return writeln!(self.out, "{}", text);
+    }
+}
+
+/// One JSON document per table or record, on a line of its own. Rates
+/// are in bytes per second, sizes in bytes and durations in seconds.
+pub struct JsonSink<W: Write> {
+    out: W,
+    formatter: RateFormatter,
+}
+
+impl<W: Write> JsonSink<W> {
+    /// A sink that writes the rows of `format_table`.
+    pub fn new(out: W) -> JsonSink<W> {
+        return JsonSink {
+            out,
+            formatter: RateFormatter::new(),
+        };
+    }
+
+    /// Writes the periods and units of `formatter` in tables instead.
+    pub fn formatter(mut self, formatter: RateFormatter) -> JsonSink<W> {
+        self.formatter = formatter;
+        return self;
+    }
+}
+
+impl<W: Write> OutputSink for JsonSink<W> {
+    fn table(&mut self, rate: Rate) -> io::Result<()> {
+        write!(self.out, "[")?;
+        for (i, (period, amount, unit)) in self.formatter.rows(rate).iter().enumerate() {
+            write!(
+                self.out,
+                "{}{{\"period\":{},\"seconds\":{},\"scaled_value\":{},\"unit\":{},\"raw_bytes\":{}}}",
+                if i == 0 { "" } else { "," },
+                json_string(period.name),
+                period.seconds,
+                json_number(*amount),
+                json_string(unit),
+                json_number(rate.bytes_per(*period))
+            )?;
+        }
+        return writeln!(self.out, "]");
+    }
+
+    fn record(&mut self, fields: &[(&str, Value)]) -> io::Result<()> {
+        write!(self.out, "{{")?;
+        for (i, (name, value)) in fields.iter().enumerate() {
+            let value = match value {
+                Value::Rate(rate) | Value::Bitrate(rate) => json_number(rate.bytes_per_second()),
+                Value::Size(n) | Value::Duration(n) | Value::Number(n) => json_number(*n),
+                Value::Text(s) => json_string(s),
+            };
+            let sep = if i == 0 { "" } else { "," };
+            write!(self.out, "{}{}:{}", sep, json_string(name), value)?;
+        }
+        return writeln!(self.out, "}}");
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+/// JSON has no infinity or NaN.
+fn json_number(n: f64) -> String {
+    if n.is_finite() {
+        return n.to_string();
+    }
+    return String::from("null");
+}
+
+/// Comma-separated values, with a header line before the first record
+/// and whenever the fields change. Rates are in bytes per second, sizes
+/// in bytes and durations in seconds.
+pub struct CsvSink<W: Write> {
+    out: W,
+    formatter: RateFormatter,
+    /// The header of the records written last.
+    header: Option<String>,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(out: W) -> CsvSink<W> {
+        return CsvSink {
+            out,
+            formatter: RateFormatter::new(),
+            header: None,
+        };
+    }
+
+    /// Writes the periods and units of `formatter` in tables instead.
+    pub fn formatter(mut self, formatter: RateFormatter) -> CsvSink<W> {
+        self.formatter = formatter;
+        return self;
+    }
+}
+
+impl<W: Write> OutputSink for CsvSink<W> {
+    fn table(&mut self, rate: Rate) -> io::Result<()> {
+        writeln!(self.out, "period,seconds,scaled_value,unit,raw_bytes")?;
+        self.header = None;
+        for (period, amount, unit) in self.formatter.rows(rate) {
+            writeln!(
+                self.out,
+                "{},{},{},{},{}",
+                period.name,
+                period.seconds,
+                amount,
+                unit,
+                rate.bytes_per(period)
+            )?;
+        }
+        return Ok(());
+    }
+
+    fn record(&mut self, fields: &[(&str, Value)]) -> io::Result<()> {
+        let names: Vec<String> = fields.iter().map(|(name, _)| csv_field(name)).collect();
+        let header = names.join(",");
+        if self.header.as_ref() != Some(&header) {
+            writeln!(self.out, "{}", header)?;
+            self.header = Some(header);
+        }
+        let values: Vec<String> = fields
+            .iter()
+            .map(|(_, value)| match value {
+                Value::Rate(rate) | Value::Bitrate(rate) => rate.bytes_per_second().to_string(),
+                Value::Size(n) | Value::Duration(n) | Value::Number(n) => n.to_string(),
+                Value::Text(s) => csv_field(s),
+            })
+            .collect();
+        return writeln!(self.out, "{}", values.join(","));
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        return format!("\"{}\"", s.replace('"', "\"\""));
+    }
+    return s.to_string();
+}
+
+/// Prometheus text exposition: one gauge per numeric value, named
+/// `rate_<name>` with a suffix for its unit. Text values are skipped.
+/// Tables are always in bytes, over the periods of the formatter.
+pub struct PrometheusSink<W: Write> {
+    out: W,
+    formatter: RateFormatter,
+    /// The metrics whose type is written: once each, as the format asks.
+    typed: Vec<String>,
+}
+
+impl<W: Write> PrometheusSink<W> {
+    pub fn new(out: W) -> PrometheusSink<W> {
+        return PrometheusSink {
+            out,
+            formatter: RateFormatter::new(),
+            typed: Vec::new(),
+        };
+    }
+
+    /// Writes the periods of `formatter` in tables instead.
+    pub fn formatter(mut self, formatter: RateFormatter) -> PrometheusSink<W> {
+        self.formatter = formatter;
+        return self;
+    }
+
+    /// Writes the type of the gauge `name` before its first sample.
+    fn gauge(&mut self, name: &str) -> io::Result<()> {
+        if self.typed.iter().any(|typed| typed == name) {
+            return Ok(());
+        }
+        self.typed.push(name.to_string());
+        return writeln!(self.out, "# TYPE {} gauge", name);
+    }
+}
+
+impl<W: Write> OutputSink for PrometheusSink<W> {
+    fn table(&mut self, rate: Rate) -> io::Result<()> {
+        self.gauge("rate_bytes_per_period")?;
+        for (period, _, _) in self.formatter.rows(rate) {
+            writeln!(
+                self.out,
+                "rate_bytes_per_period{{period=\"{}\"}} {}",
+                period.name,
+                rate.bytes_per(period)
+            )?;
+        }
+        return Ok(());
+    }
+
+    fn record(&mut self, fields: &[(&str, Value)]) -> io::Result<()> {
+        for (name, value) in fields {
+            let (suffix, n) = match value {
+                Value::Rate(rate) | Value::Bitrate(rate) => {
+                    ("_bytes_per_second", rate.bytes_per_second())
+                }
+                Value::Size(n) => ("_bytes", *n),
+                Value::Duration(n) => ("_seconds", *n),
+                Value::Number(n) => ("", *n),
+                Value::Text(_) => continue,
+            };
+            let name: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            let name = format!("rate_{}{}", name, suffix);
+            self.gauge(&name)?;
+            writeln!(self.out, "{} {}", name, n)?;
+        }
+        return Ok(());
+    }
+}
+
+#[test]
+fn test_sinks() {
+    let rate = crate::parse("1 MB/s").unwrap();
+    let fields = [
+        ("copied", Value::Size(5e6)),
+        ("rate", Value::Rate(rate)),
+        ("file", Value::Text("a,b")),
+    ];
+
+    let mut text = TextSink::new(Vec::new());
+    text.record(&fields).unwrap();
+    text.line("", &[]).unwrap();
+    text.line("link 8 Mb", &[("link", Value::Bitrate(rate))])
+        .unwrap();
+    text.record(&[("link", Value::Bitrate(rate))]).unwrap();
+    let text = String::from_utf8(text.out).unwrap();
+    assert_eq!(
+        text,
+        "copied : 5.000 MB\nrate   : 1.000 MB / sec\nfile   : a,b\n\nlink 8 Mb\nlink : 8.000 Mb / sec\n"
+    );
+    let formatter = RateFormatter::new()
+        .precision(1)
+        .periods(&crate::PERIODS[..1]);
+    let mut text = TextSink::new(Vec::new()).formatter(formatter);
+    text.table(rate).unwrap();
+    assert_eq!(String::from_utf8(text.out).unwrap(), "  1.0 MB / sec\n");
+
+    let mut json = JsonSink::new(Vec::new());
+    json.line("", &[]).unwrap();
+    json.record(&fields).unwrap();
+    json.table(rate).unwrap();
+    json.line("link 8 Mb", &[("link", Value::Bitrate(rate))])
+        .unwrap();
+    let json = String::from_utf8(json.out).unwrap();
+    let mut lines = json.lines();
+    assert_eq!(
+        lines.next(),
+        Some(r#"{"copied":5000000,"rate":1000000,"file":"a,b"}"#)
+    );
+    assert!(lines.next().unwrap().starts_with(
+        r#"[{"period":"sec","seconds":1,"scaled_value":1,"unit":"MB","raw_bytes":1000000},"#
+    ));
+    assert_eq!(lines.last(), Some(r#"{"link":1000000}"#));
+
+    let mut csv = CsvSink::new(Vec::new());
+    csv.record(&fields).unwrap();
+    csv.record(&fields).unwrap();
+    csv.record(&fields[..1]).unwrap();
+    let csv = String::from_utf8(csv.out).unwrap();
+    assert_eq!(
+        csv,
+        "copied,rate,file\n5000000,1000000,\"a,b\"\n5000000,1000000,\"a,b\"\ncopied\n5000000\n"
+    );
+
+    let mut prometheus = PrometheusSink::new(Vec::new());
+    prometheus.record(&fields).unwrap();
+    prometheus.record(&fields[..1]).unwrap();
+    let prometheus = String::from_utf8(prometheus.out).unwrap();
+    assert_eq!(
+        prometheus,
+        "# TYPE rate_copied_bytes gauge\nrate_copied_bytes 5000000\n\
+         # TYPE rate_rate_bytes_per_second gauge\nrate_rate_bytes_per_second 1000000\n\
+         rate_copied_bytes 5000000\n"
+    );
+}
+
+#[test]
+fn test_sink_tables() {
+    use crate::{Period, UnitSystem, HOUR};
+
+    let rate = crate::parse("1 MB/s").unwrap();
+    let shift = Period {
+        name: "shift",
+        seconds: 8 * HOUR,
+    };
+    let formatter = RateFormatter::new()
+        .unit_system(UnitSystem::Binary)
+        .bits(true)
+        .periods(&[shift]);
+
+    let mut json = JsonSink::new(Vec::new()).formatter(formatter.clone());
+    json.table(rate).unwrap();
+    assert_eq!(
+        String::from_utf8(json.out).unwrap(),
+        "[{\"period\":\"shift\",\"seconds\":28800,\"scaled_value\":214.57672119140625,\
+         \"unit\":\"Gib\",\"raw_bytes\":28800000000}]\n"
+    );
+
+    let mut csv = CsvSink::new(Vec::new()).formatter(formatter.clone());
+    csv.table(rate).unwrap();
+    assert_eq!(
+        String::from_utf8(csv.out).unwrap(),
+        "period,seconds,scaled_value,unit,raw_bytes\nshift,28800,214.57672119140625,Gib,28800000000\n"
+    );
+
+    let mut prometheus = PrometheusSink::new(Vec::new()).formatter(formatter.nice(true));
+    prometheus.table(rate).unwrap();
+    assert_eq!(
+        String::from_utf8(prometheus.out).unwrap(),
+        "# TYPE rate_bytes_per_period gauge\nrate_bytes_per_period{period=\"shift\"} 28800000000\n"
+    );
+}
@@ -0,0 +1,192 @@
+//! `rate standard`: the speed of common interfaces.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_duration, format_rate, parse_size, remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " standard <name> [--size <size>]\n",
+    "       Prints the nominal speed of an interface, its data rate after\n",
+    "       line encoding, the usual table, and how long --size takes.\n",
+    "       Run with \"list\" to see the known interfaces."
+);
+
+struct Standard {
+    name: &'static str,
+    /// Nominal signaling rate, in bits per second.
+    nominal: f64,
+    /// Share of the signaling rate left for data after line encoding.
+    efficiency: f64,
+}
+
+const STANDARDS: &[Standard] = &[
+    Standard {
+        name: "usb2",
+        nominal: 480e6,
+        efficiency: 1.0,
+    },
+    Standard {
+        name: "usb3.2-gen1",
+        nominal: 5e9,
+        efficiency: 8.0 / 10.0,
+    },
+    Standard {
+        name: "usb3.2-gen2",
+        nominal: 10e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "usb3.2-gen2x2",
+        nominal: 20e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "usb4",
+        nominal: 40e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "usb4-v2",
+        nominal: 80e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "thunderbolt3",
+        nominal: 40e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "thunderbolt4",
+        nominal: 40e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "thunderbolt5",
+        nominal: 80e9,
+        efficiency: 128.0 / 132.0,
+    },
+    Standard {
+        name: "sata1",
+        nominal: 1.5e9,
+        efficiency: 8.0 / 10.0,
+    },
+    Standard {
+        name: "sata2",
+        nominal: 3e9,
+        efficiency: 8.0 / 10.0,
+    },
+    Standard {
+        name: "sata3",
+        nominal: 6e9,
+        efficiency: 8.0 / 10.0,
+    },
+    Standard {
+        name: "sas3",
+        nominal: 12e9,
+        efficiency: 8.0 / 10.0,
+    },
+    Standard {
+        name: "sas4",
+        nominal: 22.5e9,
+        efficiency: 128.0 / 150.0,
+    },
+    Standard {
+        name: "nvme-gen3x4",
+        nominal: 32e9,
+        efficiency: 128.0 / 130.0,
+    },
+    Standard {
+        name: "nvme-gen4x4",
+        nominal: 64e9,
+        efficiency: 128.0 / 130.0,
+    },
+    Standard {
+        name: "nvme-gen5x4",
+        nominal: 128e9,
+        efficiency: 128.0 / 130.0,
+    },
+    Standard {
+        name: "1gbe",
+        nominal: 1e9,
+        efficiency: 1.0,
+    },
+    Standard {
+        name: "10gbe",
+        nominal: 10e9,
+        efficiency: 1.0,
+    },
+    Standard {
+        name: "25gbe",
+        nominal: 25e9,
+        efficiency: 1.0,
+    },
+    Standard {
+        name: "40gbe",
+        nominal: 40e9,
+        efficiency: 1.0,
+    },
+    Standard {
+        name: "100gbe",
+        nominal: 100e9,
+        efficiency: 1.0,
+    },
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let size: Option<f64> = pargs.opt_value_from_fn("--size", parse_size)?;
+    let mut out = output(&mut pargs)?;
+    let name = remaining_args(pargs)?.to_ascii_lowercase();
+    if name == "list" {
+        for s in STANDARDS {
+            out.line(
+                &format!("{:<14} {}", s.name, format_bitrate(s.nominal / 8.0)),
+                &[
+                    ("name", Value::Text(s.name)),
+                    (
+                        "nominal",
+                        Value::Bitrate(Rate::from_decimal(s.nominal / 8.0)),
+                    ),
+                ],
+            )?;
+        }
+        return Ok(());
+    }
+    let key = name.replace([' ', '_'], "-");
+    let Some(standard) = STANDARDS.iter().find(|s| s.name == key) else {
+        return Err(Error::Usage(messages::text(
+            Message::UnknownInterface,
+            &[&format!("{:?}", name)],
+        )));
+    };
+
+    let nominal = standard.nominal / 8.0;
+    let data = nominal * standard.efficiency;
+    let text = format!(
+        "nominal : {}\ndata    : {}",
+        format_bitrate(nominal),
+        format_rate(data)
+    );
+    let rate = Rate::from_decimal(data);
+    out.line(
+        &text,
+        &[
+            ("nominal", Value::Bitrate(Rate::from_decimal(nominal))),
+            ("data", Value::Rate(rate)),
+        ],
+    )?;
+    out.table(rate)?;
+    if let Some(size) = size {
+        let time = size / data;
+        out.line(
+            &format!("time    : {}", format_duration(time)),
+            &[("time", Value::Duration(time))],
+        )?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,109 @@
+//! `rate stats`: summary statistics over a list of rates.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_rate, input_lines, parse, period_to_seconds, Error, ParseError, ParseErrorKind, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " stats [--period <period>] [<rate>...]\n",
+    "       Reads rates from the arguments, or one per line from stdin.\n",
+    "       With --period, the inputs are plain byte counts per <period>."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let period: Option<String> = pargs.opt_value_from_str("--period")?;
+    let mut out = output(&mut pargs)?;
+    let seconds = match period {
+        Some(p) => Some(period_to_seconds(&p.to_ascii_lowercase())? as f64),
+        None => None,
+    };
+    let mut rates: Vec<f64> = Vec::new();
+    for (n, line) in input_lines(pargs)? {
+        let rate = match seconds {
+            Some(secs) => parse_count(&line).map(|bytes| bytes / secs),
+            None => parse(&line),
+        };
+        rates.push(rate.map_err(|e| Error::Line(n, line, e))?);
+    }
+    if rates.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::NoRatesToSummarize,
+            &[],
+        )));
+    }
+    rates.sort_by(f64::total_cmp);
+
+    let n = rates.len() as f64;
+    let mean = rates.iter().sum::<f64>() / n;
+    let variance = rates.iter().map(|r| (r - mean) * (r - mean)).sum::<f64>() / n;
+    let stats = [
+        ("min", rates[0]),
+        ("max", rates[rates.len() - 1]),
+        ("mean", mean),
+        ("median", median(&rates)),
+        ("p95", percentile(&rates, 95.0)),
+        ("stddev", variance.sqrt()),
+    ];
+    let mut text = vec![format!("count  : {}", rates.len())];
+    let mut fields = vec![("count", Value::Number(n))];
+    for (name, rate) in stats {
+        text.push(format!("{:<6} : {}", name, format_rate(rate)));
+        fields.push((name, Value::Rate(Rate::from_decimal(rate))));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// A byte count: a finite number, not negative.
+fn parse_count(s: &str) -> Result<f64, ParseError> {
+    match s.trim().parse::<f64>() {
+        Ok(n) if n.is_finite() && n >= 0.0 => return Ok(n),
+        _ => return Err(ParseErrorKind::InvalidNumber.into()),
+    }
+}
+
+/// Median of a sorted, non-empty slice.
+pub fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        return (sorted[mid - 1] + sorted[mid]) / 2.0;
+    }
+    return sorted[mid];
+}
+
+/// Nearest-rank percentile of a sorted, non-empty slice.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    return sorted[rank.clamp(1, sorted.len()) - 1];
+}
+
+#[test]
+fn test_median() {
+    assert_eq!(median(&[1.0]), 1.0);
+    assert_eq!(median(&[1.0, 2.0]), 1.5);
+    assert_eq!(median(&[1.0, 2.0, 10.0]), 2.0);
+}
+
+#[test]
+fn test_percentile() {
+    let xs: Vec<f64> = (1..=100).map(|x| x as f64).collect();
+    assert_eq!(percentile(&xs, 95.0), 95.0);
+    assert_eq!(percentile(&xs, 100.0), 100.0);
+    assert_eq!(percentile(&xs, 0.0), 1.0);
+    assert_eq!(percentile(&[7.0], 95.0), 7.0);
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count(" 1500 "), Ok(1500.0));
+    assert_eq!(parse_count("0"), Ok(0.0));
+    for s in ["nan", "inf", "-inf", "-1", "1e400", "12 MB"] {
+        assert_eq!(parse_count(s), Err(ParseErrorKind::InvalidNumber.into()));
+    }
+}
@@ -0,0 +1,118 @@
+//! `rate tape`: how long a tape-out takes, and how many cartridges.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_duration, format_rate, format_size, parse_size, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " tape <size> [--drive <lto5..lto10>] [--drives <n>] [--compressed]\n",
+    "       Uses the native speed and capacity of the LTO generation (lto9\n",
+    "       by default), or the vendors' compressed figures with --compressed.\n",
+    "       Cartridge changes are not accounted for."
+);
+
+struct Drive {
+    name: &'static str,
+    capacity: f64,
+    speed: f64,
+    compression: f64,
+}
+
+/// Native capacity and speed of each LTO generation, in bytes and
+/// bytes per second, and the compression ratio the vendors assume for
+/// their "compressed" figures.
+const DRIVES: &[Drive] = &[
+    Drive {
+        name: "lto5",
+        capacity: 1.5e12,
+        speed: 140e6,
+        compression: 2.0,
+    },
+    Drive {
+        name: "lto6",
+        capacity: 2.5e12,
+        speed: 160e6,
+        compression: 2.5,
+    },
+    Drive {
+        name: "lto7",
+        capacity: 6e12,
+        speed: 300e6,
+        compression: 2.5,
+    },
+    Drive {
+        name: "lto8",
+        capacity: 12e12,
+        speed: 360e6,
+        compression: 2.5,
+    },
+    Drive {
+        name: "lto9",
+        capacity: 18e12,
+        speed: 400e6,
+        compression: 2.5,
+    },
+    Drive {
+        name: "lto10",
+        capacity: 30e12,
+        speed: 400e6,
+        compression: 2.5,
+    },
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let name: String = pargs
+        .opt_value_from_str("--drive")?
+        .unwrap_or_else(|| String::from("lto9"));
+    let drives: usize = pargs.opt_value_from_str("--drives")?.unwrap_or(1);
+    let compressed = pargs.contains("--compressed");
+    let mut out = output(&mut pargs)?;
+    let size = parse_size(&remaining_args(pargs)?)?;
+    let key = name.to_ascii_lowercase().replace(['-', ' '], "");
+    let Some(drive) = DRIVES.iter().find(|d| d.name == key) else {
+        let names: Vec<&str> = DRIVES.iter().map(|d| d.name).collect();
+        return Err(Error::Usage(messages::text(
+            Message::UnknownDrive,
+            &[&format!("{:?}", name), &names.join(" ")],
+        )));
+    };
+    if drives == 0 {
+        return Err(Error::Usage(messages::text(
+            Message::OptionPositive,
+            &[&"--drives"],
+        )));
+    }
+
+    let factor = if compressed { drive.compression } else { 1.0 };
+    let capacity = drive.capacity * factor;
+    let speed = drive.speed * factor * drives as f64;
+    let cartridges = (size / capacity).ceil();
+    let text = [
+        format!(
+            "drive      : {} x {} ({}, {})",
+            drives,
+            drive.name.to_ascii_uppercase(),
+            format_size(capacity),
+            format_rate(drive.speed * factor)
+        ),
+        format!("cartridges : {}", cartridges),
+        format!("throughput : {}", format_rate(speed)),
+        format!("duration   : {}", format_duration(size / speed)),
+    ];
+    out.line(
+        &text.join("\n"),
+        &[
+            ("drive", Value::Text(drive.name)),
+            ("drives", Value::Number(drives as f64)),
+            ("capacity", Value::Size(capacity)),
+            ("cartridges", Value::Number(cartridges)),
+            ("throughput", Value::Rate(Rate::from_decimal(speed))),
+            ("duration", Value::Duration(size / speed)),
+        ],
+    )?;
+    return Ok(());
+}
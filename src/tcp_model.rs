@@ -0,0 +1,92 @@
+//! `rate tcp-model`: the throughput of a TCP stream from its RTT and loss.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_rate, parse, parse_duration, parse_percent, parse_size, remaining_args, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " tcp-model --rtt <duration> --loss <ratio> [--mss <bytes>] [--window <size>]\n",
+    "       [--target <rate>]\n",
+    "       Estimates the throughput of a single TCP stream with the Mathis\n",
+    "       and the Padhye models, for a loss rate such as 0.3%. The segment\n",
+    "       size is 1460 bytes by default. --window caps a stream at one\n",
+    "       receive window per round trip. With --target, also prints how\n",
+    "       many parallel streams reach that rate."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let rtt: f64 = pargs.value_from_fn("--rtt", parse_duration)?;
+    let loss: f64 = pargs.value_from_fn("--loss", parse_percent)?;
+    let mss: f64 = pargs.opt_value_from_str("--mss")?.unwrap_or(1460.0);
+    let window: Option<f64> = pargs.opt_value_from_fn("--window", parse_size)?;
+    let target: Option<f64> = pargs.opt_value_from_fn("--target", parse)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    if !args.is_empty() {
+        return Err(Error::Usage(messages::text(
+            Message::TooManyArguments,
+            &[&args],
+        )));
+    }
+    if rtt <= 0.0 || mss <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::TcpPositive, &[])));
+    }
+    if loss <= 0.0 || loss >= 1.0 {
+        return Err(Error::Usage(messages::text(Message::LossRange, &[])));
+    }
+
+    let cap = |rate: f64| window.map_or(rate, |w| rate.min(w / rtt));
+    let mathis = cap(mathis(mss, rtt, loss));
+    let padhye = cap(padhye(mss, rtt, loss));
+    let mut text = vec![
+        format!("mathis  : {}", format_rate(mathis)),
+        format!("padhye  : {}", format_rate(padhye)),
+    ];
+    let mut fields = vec![
+        ("mathis", Value::Rate(Rate::from_decimal(mathis))),
+        ("padhye", Value::Rate(Rate::from_decimal(padhye))),
+    ];
+    if let Some(window) = window {
+        text.push(format!("window  : {}", format_rate(window / rtt)));
+        fields.push(("window", Value::Rate(Rate::from_decimal(window / rtt))));
+    }
+    if let Some(target) = target {
+        let streams = (target / padhye).ceil().max(1.0);
+        text.push(format!("streams : {} for {}", streams, format_rate(target)));
+        fields.push(("streams", Value::Number(streams)));
+        fields.push(("target", Value::Rate(Rate::from_decimal(target))));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// The Mathis et al. estimate: MSS / RTT × √(3/2) / √p.
+fn mathis(mss: f64, rtt: f64, loss: f64) -> f64 {
+    return mss / rtt * (1.5f64).sqrt() / loss.sqrt();
+}
+
+/// The Padhye et al. estimate, which also counts the timeouts, with one
+/// segment acknowledged at a time and a timeout of four round trips as
+/// in RFC 5348.
+fn padhye(mss: f64, rtt: f64, loss: f64) -> f64 {
+    let timeout = 4.0 * rtt;
+    let denominator = rtt * (2.0 * loss / 3.0).sqrt()
+        + timeout * (3.0 * (3.0 * loss / 8.0).sqrt()).min(1.0) * loss * (1.0 + 32.0 * loss * loss);
+    return mss / denominator;
+}
+
+#[test]
+fn test_models() {
+    // 1460 bytes per 100ms at 1% loss: 14600 × 1.2247 / 0.1 B/s.
+    assert_eq!(mathis(1460.0, 0.1, 0.01).round(), 178813.0);
+    // The timeouts make Padhye the more pessimistic, more so with loss.
+    assert_eq!(padhye(1460.0, 0.1, 0.01).round(), 164005.0);
+    let ratio = |loss| padhye(1460.0, 0.1, loss) / mathis(1460.0, 0.1, loss);
+    assert!(ratio(0.1) < ratio(0.01));
+}
@@ -0,0 +1,55 @@
+//! `rate telemetry`: data used by a payload sent at a fixed interval.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_size, month, parse_duration, parse_quantity, parse_size, remaining_args, split_keyword,
+    Error, DAY, HOUR,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " telemetry <size> every <duration> [--devices <n>] [--price <per GB>]\n",
+    "       e.g. telemetry 2KB every 30s --devices 500 --price 0.10\n",
+    "       Prints the data used per hour, day and month, for one device or\n",
+    "       a fleet, and the monthly cost at a price per gigabyte."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let devices: f64 = pargs
+        .opt_value_from_fn("--devices", parse_quantity)?
+        .unwrap_or(1.0);
+    let price: Option<f64> = pargs.opt_value_from_str("--price")?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((size, interval)) = split_keyword(&args, "every") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<size> every <duration>"],
+        )));
+    };
+    let size = parse_size(size)?;
+    let interval = parse_duration(interval)?;
+    if interval <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::IntervalPositive, &[])));
+    }
+
+    let rate = size * devices / interval;
+    let mut text = Vec::new();
+    let mut fields = Vec::new();
+    for (name, seconds) in [("per_hour", HOUR), ("per_day", DAY), ("per_month", month())] {
+        let size = rate * seconds as f64;
+        text.push(format!("per {:<5} : {}", &name[4..], format_size(size)));
+        fields.push((name, Value::Size(size)));
+    }
+    if let Some(price) = price {
+        let cost = rate * month() as f64 / 1e9 * price;
+        text.push(format!("cost      : {:.2} / month", cost));
+        fields.push(("cost", Value::Number(cost)));
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
@@ -0,0 +1,104 @@
+//! Just enough calendar math to read and print UTC timestamps without
+//! pulling in a date library.
+
+/// Parses either Unix seconds ("1700000000.5") or an ISO 8601 UTC
+/// timestamp ("2024-01-02T03:04:05Z", fractional seconds allowed).
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Ok(t) = s.parse::<f64>() {
+        return t.is_finite().then_some(t);
+    }
+    let s = s.strip_suffix('Z').unwrap_or(s);
+    let (date, time) = s.split_once(['T', ' '])?;
+    let mut date = date.splitn(3, '-').map(|x| x.parse::<i64>());
+    let (y, m, d) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':');
+    let h = time.next()?.parse::<i64>().ok()?;
+    let min = time.next()?.parse::<i64>().ok()?;
+    let sec = time.next().unwrap_or("0").parse::<f64>().ok()?;
+    // A second of 60 is a leap second.
+    if !(0..=9999).contains(&y)
+        || !(1..=12).contains(&m)
+        || !(1..=31).contains(&d)
+        || !(0..24).contains(&h)
+        || !(0..60).contains(&min)
+        || !(0.0..61.0).contains(&sec)
+    {
+        return None;
+    }
+    let days = days_from_civil(y, m, d);
+    return Some((days * 86400 + h * 3600 + min * 60) as f64 + sec);
+}
+
+/// Formats Unix seconds as "YYYY-MM-DD HH:MM:SS" (UTC).
+pub fn format_timestamp(t: f64) -> String {
+    let t = t.floor() as i64;
+    let (y, m, d) = civil_from_days(t.div_euclid(86400));
+    let secs = t.rem_euclid(86400);
+    return format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        y,
+        m,
+        d,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    );
+}
+
+/// Current time in Unix seconds.
+pub fn now() -> f64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+}
+
+// Both conversions are Howard Hinnant's algorithms for the proleptic
+// Gregorian calendar, with years starting in March.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    return era * 146097 + doe - 719468;
+}
+
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = if m <= 2 {
+        yoe + era * 400 + 1
+    } else {
+        yoe + era * 400
+    };
+    return (y, m, d);
+}
+
+#[test]
+fn test_timestamps() {
+    assert_eq!(parse_timestamp("1700000000"), Some(1700000000.0));
+    assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0.0));
+    assert_eq!(parse_timestamp("2000-03-01T12:30:15.5"), Some(951913815.5));
+    assert_eq!(parse_timestamp("2024-13-01T00:00:00"), None);
+    assert_eq!(parse_timestamp("yesterday"), None);
+    for s in [
+        "inf",
+        "nan",
+        "1e400",
+        "2024-01-01T00:00:inf",
+        "99999999999999-01-01T00:00",
+    ] {
+        assert_eq!(parse_timestamp(s), None);
+    }
+    assert_eq!(format_timestamp(951913815.5), "2000-03-01 12:30:15");
+    assert_eq!(format_timestamp(0.0), "1970-01-01 00:00:00");
+}
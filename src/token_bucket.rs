@@ -0,0 +1,103 @@
+//! A token bucket, to hold a stream of data to a rate.
+
+use std::time::{Duration, Instant};
+
+use crate::Rate;
+
+/// Lets data through at a sustained rate, with bursts of up to
+/// `capacity` bytes. Each byte takes a token; the bucket refills at the
+/// rate, and starts full.
+///
+/// ```
+/// let mut bucket = rate::TokenBucket::new(1000, rate::parse("1 MB/s").unwrap());
+/// assert!(bucket.try_acquire(600));
+/// assert!(!bucket.try_acquire(600));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    capacity: u64,
+    rate: Rate,
+    /// Negative after `acquire` takes more than was available.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u64, rate: Rate) -> TokenBucket {
+        return TokenBucket {
+            capacity,
+            rate,
+            tokens: capacity as f64,
+            updated: Instant::now(),
+        };
+    }
+
+    pub fn capacity(&self) -> u64 {
+        return self.capacity;
+    }
+
+    pub fn rate(&self) -> Rate {
+        return self.rate;
+    }
+
+    /// The tokens that can be taken right now.
+    pub fn available(&mut self) -> u64 {
+        self.refill(Instant::now());
+        return self.tokens.max(0.0) as u64;
+    }
+
+    /// Takes `n` tokens if they are available, without waiting.
+    pub fn try_acquire(&mut self, n: u64) -> bool {
+        self.refill(Instant::now());
+        if self.tokens < n as f64 {
+            return false;
+        }
+        self.tokens -= n as f64;
+        return true;
+    }
+
+    /// Takes `n` tokens, sleeping as long as it takes to pay for them.
+    /// `n` may exceed the capacity: the bucket then goes into debt, and
+    /// the wait covers it.
+    pub fn acquire(&mut self, n: u64) {
+        let wait = self.take(n, Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Takes `n` tokens at `now`, and returns how long until the bucket
+    /// is out of debt.
+    fn take(&mut self, n: u64, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= n as f64;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        let seconds = -self.tokens / self.rate.bytes_per_second();
+        return Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.rate.bytes_per_second()).min(self.capacity as f64);
+        self.updated = self.updated.max(now);
+    }
+}
+
+#[test]
+fn test_token_bucket() {
+    let mut bucket = TokenBucket::new(1000, Rate::new(100, 1));
+    let start = bucket.updated;
+    assert_eq!(bucket.take(400, start), Duration::ZERO);
+    assert_eq!(bucket.take(800, start), Duration::from_secs(2));
+    // Two seconds later, the debt is paid, and the bucket refills up to
+    // its capacity only.
+    bucket.refill(start + Duration::from_secs(2));
+    assert_eq!(bucket.tokens, 0.0);
+    bucket.refill(start + Duration::from_secs(60));
+    assert_eq!(bucket.tokens, 1000.0);
+    assert!(bucket.try_acquire(1000));
+    assert!(!bucket.try_acquire(1000));
+}
@@ -0,0 +1,98 @@
+//! `rate topic`: throughput planning for a message-queue topic.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, format_count, format_rate, parse, parse_frequency, parse_size, remaining_args,
+    split_keyword, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " topic <messages>/<period> @ <size> [--replication <n>] [--partitions <n>]\n",
+    "       [--brokers <n>] [--consumers <groups>] [--broker-capacity <rate>]\n",
+    "       e.g. topic 200k/s @ 1KB --replication 3 --partitions 24 --brokers 3\n",
+    "       Splits the load evenly over the brokers: each one receives its\n",
+    "       share of the produced data times the replication factor, and\n",
+    "       sends out the replicas plus what every consumer group reads.\n",
+    "       Defaults: 1 replica, 1 partition, 1 broker, 1 consumer group."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let replication: f64 = pargs.opt_value_from_str("--replication")?.unwrap_or(1.0);
+    let partitions: f64 = pargs.opt_value_from_str("--partitions")?.unwrap_or(1.0);
+    let brokers: f64 = pargs.opt_value_from_str("--brokers")?.unwrap_or(1.0);
+    let consumers: f64 = pargs.opt_value_from_str("--consumers")?.unwrap_or(1.0);
+    let capacity: Option<f64> = pargs.opt_value_from_fn("--broker-capacity", parse)?;
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((messages, size)) = split_keyword(&args, "@").or(split_keyword(&args, "at")) else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<messages>/<period> @ <size>"],
+        )));
+    };
+    let messages = parse_frequency(messages)?;
+    let size = parse_size(size)?;
+    if replication < 1.0 || partitions < 1.0 || brokers < 1.0 {
+        return Err(Error::Usage(messages::text(Message::TopicAtLeastOne, &[])));
+    }
+    if replication > brokers {
+        return Err(Error::Usage(messages::text(
+            Message::ReplicationBrokers,
+            &[],
+        )));
+    }
+
+    let produced = messages * size;
+    let load = broker_load(produced, replication, brokers, consumers);
+    let mut text = vec![
+        format!("messages      : {}/sec", format_count(messages)),
+        format!("produced      : {}", format_rate(produced)),
+        format!("per partition : {}", format_rate(produced / partitions)),
+        format!("broker in     : {}", format_rate(load.0)),
+        format!("broker out    : {}", format_rate(load.1)),
+    ];
+    let mut fields = vec![
+        ("messages", Value::Number(messages)),
+        ("produced", Value::Rate(Rate::from_decimal(produced))),
+        (
+            "per_partition",
+            Value::Rate(Rate::from_decimal(produced / partitions)),
+        ),
+        ("broker_in", Value::Rate(Rate::from_decimal(load.0))),
+        ("broker_out", Value::Rate(Rate::from_decimal(load.1))),
+    ];
+    if let Some(capacity) = capacity {
+        let busiest = load.0.max(load.1);
+        text.push(format!(
+            "utilization   : {:.1}% of {}",
+            100.0 * busiest / capacity,
+            format_bitrate(capacity)
+        ));
+        fields.push(("utilization", Value::Number(busiest / capacity)));
+        if busiest > capacity {
+            text.push(String::from(
+                "the brokers cannot keep up; add brokers or capacity",
+            ));
+        }
+    }
+    out.line(&text.join("\n"), &fields)?;
+    return Ok(());
+}
+
+/// Returns the bytes per second each broker receives and sends.
+fn broker_load(produced: f64, replication: f64, brokers: f64, consumers: f64) -> (f64, f64) {
+    let ingress = produced * replication / brokers;
+    let egress = produced * (replication - 1.0 + consumers) / brokers;
+    return (ingress, egress);
+}
+
+#[test]
+fn test_broker_load() {
+    assert_eq!(broker_load(300.0, 3.0, 3.0, 1.0), (300.0, 300.0));
+    assert_eq!(broker_load(300.0, 1.0, 3.0, 2.0), (100.0, 200.0));
+}
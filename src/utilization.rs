@@ -0,0 +1,92 @@
+//! `rate utilization`: how much of a link a rate uses.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{
+    format_bitrate, parse, parse_input, parse_percent, remaining_args, split_keyword, Error, Rate,
+};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " utilization <rate> of <rate> [--warn <ratio>]\n",
+    "       Prints the share of a link that a rate uses, the headroom left,\n",
+    "       and a bar with the warning threshold (80% by default) marked\n",
+    "       with |. Above it, prints a warning."
+);
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let warn: f64 = pargs
+        .opt_value_from_fn("--warn", parse_percent)?
+        .unwrap_or(0.8);
+    let mut out = output(&mut pargs)?;
+    let args = remaining_args(pargs)?;
+    let Some((used, link)) = split_keyword(&args, "of") else {
+        return Err(Error::Usage(messages::text(
+            Message::Expected,
+            &[&"<rate> of <rate>"],
+        )));
+    };
+    let used = parse_input(&args, used, parse)?;
+    let link = parse_input(&args, link, parse)?;
+    if link <= 0.0 {
+        return Err(Error::Usage(messages::text(Message::LinkRatePositive, &[])));
+    }
+    if warn > 1.0 {
+        return Err(Error::Usage(messages::text(Message::WarningThreshold, &[])));
+    }
+
+    let ratio = used / link;
+    let mut text = vec![format!(
+        "utilization : {:.1}% [{}]",
+        ratio * 100.0,
+        bar(ratio, warn)
+    )];
+    match ratio <= 1.0 {
+        true => text.push(format!(
+            "headroom    : {} ({:.1}%)",
+            format_bitrate(link - used),
+            (1.0 - ratio) * 100.0
+        )),
+        false => text.push(format!(
+            "headroom    : none, {} over",
+            format_bitrate(used - link)
+        )),
+    }
+    if ratio > warn {
+        text.push(format!("warning     : above {:.0}%", warn * 100.0));
+    }
+    out.line(
+        &text.join("\n"),
+        &[
+            ("utilization", Value::Number(ratio)),
+            ("headroom", Value::Bitrate(Rate::from_decimal(link - used))),
+            ("warn", Value::Number(warn)),
+        ],
+    )?;
+    return Ok(());
+}
+
+/// A bar of the share `ratio` of the link, with the warning threshold
+/// `warn` marked with '|', e.g. `=======          |   `.
+fn bar(ratio: f64, warn: f64) -> String {
+    const WIDTH: usize = 20;
+    let filled = (ratio.clamp(0.0, 1.0) * WIDTH as f64).round() as usize;
+    let mark = ((warn * WIDTH as f64).round() as usize).min(WIDTH - 1);
+    return (0..WIDTH)
+        .map(|i| match i {
+            _ if i == mark => '|',
+            _ if i < filled => '=',
+            _ => ' ',
+        })
+        .collect();
+}
+
+#[test]
+fn test_bar() {
+    assert_eq!(bar(0.34, 0.8), "=======         |   ");
+    assert_eq!(bar(0.9, 0.8), "================|=  ");
+    assert_eq!(bar(1.5, 1.0), "===================|");
+}
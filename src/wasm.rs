@@ -0,0 +1,25 @@
+//! JavaScript bindings, with the `wasm-bindgen` feature.
+//!
+//! Build the module with
+//! `cargo rustc --lib --release --no-default-features --features wasm-bindgen
+//! --target wasm32-unknown-unknown --crate-type cdylib`, then run
+//! `wasm-bindgen` on the output.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{format_table, Rate};
+
+/// Parses a rate expression and returns it in bytes per second.
+#[wasm_bindgen(js_name = parse)]
+pub fn parse_js(s: &str) -> Result<f64, JsError> {
+    match crate::parse(s) {
+        Ok(rate) => return Ok(rate.bytes_per_second()),
+        Err(e) => return Err(JsError::new(&format!("{:?}: {}", s, e))),
+    }
+}
+
+/// Returns the conversion table of a rate given in bytes per second.
+#[wasm_bindgen(js_name = formatTable)]
+pub fn format_table_js(bytes_per_second: f64) -> String {
+    return format_table(Rate::from_bytes_per_second(bytes_per_second));
+}
@@ -0,0 +1,121 @@
+//! `rate wifi`: theoretical and realistic Wi-Fi throughput.
+
+use rate::sink::Value;
+
+use crate::messages::{self, Message};
+use crate::report::output;
+use crate::{format_bitrate, parse_percent, remaining_args, Error, Rate};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_BIN_NAME"),
+    " wifi <wifi4|wifi5|wifi6|wifi7> [--width <MHz>] [--streams <n>]\n",
+    "       [--efficiency <ratio>]\n",
+    "       Prints the PHY rate at the best modulation (short guard interval)\n",
+    "       and the goodput that can realistically be expected, which is\n",
+    "       60% of the PHY rate by default. Defaults: 80MHz (40MHz for\n",
+    "       wifi4), 2 spatial streams."
+);
+
+struct Standard {
+    names: &'static [&'static str],
+    /// PHY rate of one spatial stream in Mb/s for 20, 40, 80, 160 and
+    /// 320MHz channels, zero when the width is not supported.
+    per_stream: [f64; 5],
+    max_streams: u32,
+}
+
+const WIDTHS: [u32; 5] = [20, 40, 80, 160, 320];
+
+const STANDARDS: &[Standard] = &[
+    Standard {
+        names: &["wifi4", "802.11n", "n"],
+        per_stream: [72.2, 150.0, 0.0, 0.0, 0.0],
+        max_streams: 4,
+    },
+    Standard {
+        names: &["wifi5", "802.11ac", "ac"],
+        per_stream: [86.7, 200.0, 433.3, 866.7, 0.0],
+        max_streams: 8,
+    },
+    Standard {
+        names: &["wifi6", "wifi6e", "802.11ax", "ax"],
+        per_stream: [143.4, 286.8, 600.5, 1201.0, 0.0],
+        max_streams: 8,
+    },
+    Standard {
+        names: &["wifi7", "802.11be", "be"],
+        per_stream: [172.1, 344.1, 720.6, 1441.2, 2882.4],
+        max_streams: 16,
+    },
+];
+
+pub fn run(mut pargs: pico_args::Arguments) -> Result<(), Error> {
+    let width: Option<u32> = pargs.opt_value_from_str("--width")?;
+    let streams: u32 = pargs.opt_value_from_str("--streams")?.unwrap_or(2);
+    let mut out = output(&mut pargs)?;
+    let efficiency: f64 = pargs
+        .opt_value_from_fn("--efficiency", parse_percent)?
+        .unwrap_or(0.6);
+    if !(efficiency > 0.0 && efficiency <= 1.0) {
+        return Err(Error::Usage(messages::text(
+            Message::Between,
+            &[&"--efficiency", &0, &1],
+        )));
+    }
+    let name = remaining_args(pargs)?
+        .to_ascii_lowercase()
+        .replace(['-', ' '], "");
+    let Some(standard) = STANDARDS.iter().find(|s| s.names.contains(&name.as_str())) else {
+        let name = format!("{:?}", name);
+        return Err(Error::Usage(messages::text(
+            Message::UnknownWifi,
+            &[&name, &"wifi4 wifi5 wifi6 wifi7"],
+        )));
+    };
+    let width = width.unwrap_or(if standard.per_stream[2] > 0.0 { 80 } else { 40 });
+    let per_stream = match WIDTHS.iter().position(|w| *w == width) {
+        Some(i) if standard.per_stream[i] > 0.0 => standard.per_stream[i],
+        _ => {
+            return Err(Error::Usage(messages::text(
+                Message::ChannelWidth,
+                &[&standard.names[0], &width],
+            )))
+        }
+    };
+    if streams == 0 || streams > standard.max_streams {
+        return Err(Error::Usage(messages::text(
+            Message::Streams,
+            &[&standard.names[0], &standard.max_streams],
+        )));
+    }
+
+    let phy = per_stream * 1e6 / 8.0 * streams as f64;
+    let goodput = phy * efficiency;
+    let text = [
+        format!(
+            "link    : {} {}MHz x {} streams",
+            standard.names[0], width, streams
+        ),
+        format!("phy     : {}", format_bitrate(phy)),
+        format!(
+            "goodput : {} ({:.0}% of the PHY rate)",
+            format_bitrate(goodput),
+            efficiency * 100.0
+        ),
+    ];
+    let goodput = Rate::from_decimal(goodput);
+    out.line(
+        &text.join("\n"),
+        &[
+            ("standard", Value::Text(standard.names[0])),
+            ("width", Value::Number(width as f64)),
+            ("streams", Value::Number(streams as f64)),
+            ("phy", Value::Bitrate(Rate::from_decimal(phy))),
+            ("goodput", Value::Bitrate(goodput)),
+            ("efficiency", Value::Number(efficiency)),
+        ],
+    )?;
+    out.table(goodput)?;
+    return Ok(());
+}
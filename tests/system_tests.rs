@@ -7,3 +7,541 @@ fn test000() {
         .unwrap();
     assert_eq!(x.stdout, include_bytes!("test000.out"));
 }
+
+#[test]
+fn test_assert() {
+    use std::process::Command;
+    let ok = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["assert", "60MB/s", "--at-least", "50MB/s"])
+        .status()
+        .unwrap();
+    assert!(ok.success());
+    let fail = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args([
+            "assert",
+            "40 MB/s",
+            "--at-least",
+            "50MB/s",
+            "--at-most",
+            "1GB/s",
+        ])
+        .status()
+        .unwrap();
+    assert_eq!(fail.code(), Some(1));
+}
+
+#[test]
+fn test_pps_line_rate() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pps", "10Gb/s", "--frame", "64B"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(x.stdout).unwrap();
+    assert!(stdout.starts_with("packets   : 14.881 Mpps\n"));
+}
+
+#[test]
+fn test_pcie() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pcie", "gen3", "x16"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(x.stdout).unwrap();
+    assert!(stdout.contains("effective : 15.754 GB / sec"));
+}
+
+#[test]
+fn test_pipe() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let input: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .arg("pipe")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let expected = input.clone();
+    let writer = std::thread::spawn(move || stdin.write_all(&input).unwrap());
+    let x = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    assert!(x.status.success());
+    assert_eq!(x.stdout, expected);
+    assert!(String::from_utf8(x.stderr)
+        .unwrap()
+        .contains("200.000 KB copied"));
+}
+
+#[test]
+fn test_parse_error_caret() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["12", "MBs/s"])
+        .output()
+        .unwrap();
+    assert_eq!(x.status.code(), Some(4));
+    let stderr = String::from_utf8(x.stderr).unwrap();
+    assert!(stderr.contains("did you mean \"MB\"?\n"));
+    assert!(stderr.ends_with("    12 MBs/s\n       ^^^\n"));
+}
+
+#[test]
+fn test_pipe_log() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let log = std::env::temp_dir().join(format!("rate-test-{}.jsonl", std::process::id()));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pipe", "--log"])
+        .arg(&log)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&[0; 5000]).unwrap();
+    assert!(child.wait().unwrap().success());
+    let logged = std::fs::read_to_string(&log).unwrap();
+    assert!(logged.contains(r#""bytes":5000,"total":5000,"#));
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .arg("log")
+        .arg(&log)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&log).unwrap();
+    assert!(String::from_utf8(x.stdout)
+        .unwrap()
+        .contains("total   : 5.000 KB"));
+}
+
+#[test]
+fn test_config_file() {
+    use std::process::Command;
+    let dir = std::env::temp_dir().join(format!("rate-test-config-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("rate")).unwrap();
+    std::fs::write(
+        dir.join("rate").join("config.toml"),
+        "precision = 1\nperiods = [\"sec\", \"min\"]\n",
+    )
+    .unwrap();
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .arg("1MB/s")
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(x.stdout).unwrap(),
+        "  1.0 MB / sec\n 60.0 MB / min\n"
+    );
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .env("XDG_CONFIG_HOME", &dir)
+        .args(["1MB/s", "--precision", "0"])
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        String::from_utf8(x.stdout).unwrap(),
+        "  1 MB / sec\n 60 MB / min\n"
+    );
+}
+
+#[test]
+fn test_subcommand_error_caret() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["countdown", "2TB", "at", "1", "GBs/s"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(x.stderr).unwrap();
+    assert!(stderr.ends_with("    2TB at 1 GBs/s\n             ^^^\n"));
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["stats", "1MB/s", "2 MB/mnth"])
+        .output()
+        .unwrap();
+    assert_eq!(x.status.code(), Some(5));
+    let stderr = String::from_utf8(x.stderr).unwrap();
+    assert!(stderr.starts_with("rate: line 2: not a recognized time period"));
+    assert!(stderr.ends_with("    2 MB/mnth\n         ^^^^\n"));
+}
+
+#[test]
+fn test_exit_codes() {
+    use std::process::Command;
+    let code = |args: &[&str]| {
+        let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+            .args(args)
+            .output()
+            .unwrap();
+        x.status.code()
+    };
+    assert_eq!(code(&["1MB/s"]), Some(0));
+    assert_eq!(code(&["x", "MB/s"]), Some(3));
+    assert_eq!(code(&["1", "MB", "s"]), Some(6));
+    assert_eq!(code(&["1MB/s", "--bogus"]), Some(2));
+    assert_eq!(code(&["log", "/nonexistent/rate.log"]), Some(8));
+    assert_eq!(code(&["1000000YB/s"]), Some(0));
+    assert_eq!(code(&["340000000QB/s"]), Some(2));
+    let slot = ["--slot", "00:00-01:00=1 KB/s"];
+    assert_eq!(code(&[&["simulate", "1 PB"][..], &slot].concat()), Some(2));
+    assert_eq!(code(&[&["simulate", "0 B"][..], &slot].concat()), Some(2));
+    assert_eq!(code(&["bench-disk", "--block", "0 B"]), Some(2));
+    assert_eq!(code(&["bench-disk", "/tmp", "extra"]), Some(2));
+    assert_eq!(code(&["bench-disk", "--bogus"]), Some(2));
+    assert_eq!(code(&["burstable", "8", "--percentile", "101"]), Some(2));
+    assert_eq!(code(&["burstable", "8", "--price", "nan"]), Some(2));
+    assert_eq!(code(&["burstable", "8", "--price", "-1"]), Some(2));
+    assert_eq!(code(&["burstable", "8", "inf"]), Some(3));
+    assert_eq!(
+        code(&[
+            "countdown",
+            "1 GB at 1 MB/s",
+            "--refresh",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(code(&["graph", "1 MB/s", "--days", "inf"]), Some(2));
+    assert_eq!(code(&["graph", "1 MB/s", "--days", "nan"]), Some(2));
+    assert_eq!(
+        code(&["pipe", "--interval", "100000000000000000000000000 sec"]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "fetch",
+            "--duration",
+            "100000000000000000000000000 sec",
+            "http://127.0.0.1:9/"
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "bench-net",
+            "--client",
+            "127.0.0.1",
+            "--duration",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&["bench-net", "--client", "127.0.0.1", "extra"]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "daemon",
+            "lo",
+            "--interval",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "diff",
+            "--live",
+            "--interval",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "cgroup",
+            "/",
+            "--interval",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(code(&["cgroup", "/", "extra"]), Some(2));
+    assert_eq!(
+        code(&[
+            "progress",
+            "1 GB at 1 MB/s",
+            "--refresh",
+            "100000000000000000000000000 sec"
+        ]),
+        Some(2)
+    );
+    assert_eq!(code(&["log", "a.log", "b.log"]), Some(2));
+    assert_eq!(code(&["log", "a.log", "--windw", "1m"]), Some(2));
+    assert_eq!(
+        code(&["reparse", "--from", "curl", "a.log", "b.log"]),
+        Some(2)
+    );
+    assert_eq!(code(&["fetch", "http://127.0.0.1:9/", "extra"]), Some(2));
+    assert_eq!(
+        code(&["fetch", "http://127.0.0.1:9/", "--duraton", "1s"]),
+        Some(2)
+    );
+    assert_eq!(code(&["prom", "bytes_total", "extra"]), Some(2));
+    assert_eq!(code(&["prom", "bytes_total", "--windw", "5m"]), Some(2));
+    assert_eq!(code(&["wifi", "wifi6", "--efficiency", "0"]), Some(2));
+    assert_eq!(code(&["wifi", "wifi6", "--efficiency", "150%"]), Some(2));
+    assert_eq!(
+        code(&[
+            "bucket",
+            "--sustained",
+            "1MB/s",
+            "--burst",
+            "2MB/s",
+            "--for",
+            "1 min",
+            "extra"
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&["catchup", "--lag", "1GB", "--write", "1MB/s", "--apply", "2MB/s", "extra"]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&["tcp-model", "--rtt", "120ms", "--loss", "0.3%", "extra"]),
+        Some(2)
+    );
+    assert_eq!(code(&["config", "path", "extra"]), Some(2));
+}
+
+#[test]
+fn test_lang() {
+    use std::process::Command;
+    let stderr = |lang: &str, args: &[&str]| {
+        let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+            .env_remove("LC_ALL")
+            .env_remove("LC_MESSAGES")
+            .env("LANG", lang)
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8(x.stderr).unwrap()
+    };
+    assert!(stderr("fr_FR.UTF-8", &["x", "MB/s"]).starts_with("rate: nombre invalide\n"));
+    assert!(stderr("C", &["--lang", "es", "x", "MB/s"]).starts_with("rate: número no válido\n"));
+    assert!(stderr("es_ES.UTF-8", &["--lang", "en", "x", "MB/s"])
+        .starts_with("rate: not a valid number\n"));
+    assert!(stderr("C", &["--lang", "de", "1MB/s"]).starts_with("rate: unknown language \"de\""));
+    assert_eq!(
+        stderr("C", &["--lang", "fr", "pipe", "--interval", "0 s"]),
+        "rate: l'intervalle doit être positif\n"
+    );
+    assert_eq!(
+        stderr("es_ES.UTF-8", &["wifi", "wifi6", "--streams", "9"]),
+        "rate: wifi6 admite de 1 a 8 flujos espaciales\n"
+    );
+}
+
+#[test]
+fn test_config_command() {
+    use std::process::Command;
+    let dir = std::env::temp_dir().join(format!("rate-test-config-cmd-{}", std::process::id()));
+    let rate = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_rate"))
+            .env("XDG_CONFIG_HOME", &dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert!(rate(&["config", "set", "precision", "0"]).status.success());
+    assert!(rate(&["config", "set", "aliases.nas", "2 MB/s"])
+        .status
+        .success());
+    assert_eq!(
+        rate(&["config", "set", "units", "metric"]).status.code(),
+        Some(2)
+    );
+    let x = rate(&["config", "get", "aliases.nas"]);
+    assert_eq!(String::from_utf8(x.stdout).unwrap(), "2 MB/s\n");
+    let x = rate(&["nas"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(String::from_utf8(x.stdout)
+        .unwrap()
+        .starts_with("  2 MB / sec\n"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_diff_live() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["diff", "--live", "--interval", "0.1s"])
+        .output()
+        .unwrap();
+    assert!(x.status.success());
+    assert!(String::from_utf8(x.stdout).unwrap().contains("lo rx"));
+}
+
+#[test]
+fn test_pipe_summary_only() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pipe", "--summary-only", "--output", "csv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&[0; 1000]).unwrap();
+    let x = child.wait_with_output().unwrap();
+    assert!(x.status.success());
+    let stderr = String::from_utf8(x.stderr).unwrap();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "elapsed,bytes,rate");
+    assert!(lines[1].contains(",1000,"));
+}
+
+#[test]
+fn test_normalize() {
+    use std::process::Command;
+    let normalize = |input: &str| {
+        let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+            .args([input, "--normalize"])
+            .output()
+            .unwrap();
+        assert!(x.status.success());
+        String::from_utf8(x.stdout).unwrap()
+    };
+    assert_eq!(normalize("12000 KB / sec"), "12.000 MB/s\n");
+    assert_eq!(normalize("1440MB/min"), "1.440 GB/min\n");
+    assert_eq!(normalize("8 Mb/s"), "1.000 MB/s\n");
+}
+
+#[test]
+fn test_output_periods() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["1MB/s", "--output", "csv", "--periods", "hour,day"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(x.stdout).unwrap(),
+        "period,seconds,scaled_value,unit,raw_bytes\n\
+         hour,3600,3.6,GB,3600000000\n\
+         day,86400,86.4,GB,86400000000\n"
+    );
+}
+
+#[test]
+fn test_output_month_days() {
+    use std::process::Command;
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["--month-days", "31", "1KB/s", "--output", "json"])
+        .args(["--periods", "month"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(x.stdout).unwrap(),
+        "[{\"period\":\"month\",\"seconds\":2678400,\"scaled_value\":2.6784,\
+         \"unit\":\"GB\",\"raw_bytes\":2678400000}]\n"
+    );
+}
+
+#[test]
+fn test_subcommand_output() {
+    use std::process::Command;
+    let run = |args: &[&str]| {
+        let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(x.status.success());
+        String::from_utf8(x.stdout).unwrap()
+    };
+    let args = ["pps", "100Kpps", "--frame", "1000B"];
+    assert_eq!(
+        run(&args),
+        "packets   : 100.000 Kpps\n\
+         frame     : 1000 B (+20 B on the wire)\n\
+         frames    : 800.000 Mb / sec\n\
+         line rate : 816.000 Mb / sec\n"
+    );
+    assert_eq!(
+        run(&[&args[..], &["--output", "json"]].concat()),
+        "{\"packets\":100000,\"frame\":1000,\"overhead\":20,\
+         \"frames\":100000000,\"line_rate\":102000000}\n"
+    );
+    let csv = run(&["cdn", "10 @ 1MB/s", "--output", "csv"]);
+    assert!(csv.starts_with("egress\n10000000\nperiod,seconds,scaled_value,unit,raw_bytes\n"));
+    assert_eq!(
+        run(&["catchup", "--lag", "50GB", "--write", "20MB/s", "--apply", "20MB/s"]),
+        "behind    : 50.000 GB\n\
+         caught up : never, the lag stays constant\n"
+    );
+    // Charts are for people only.
+    assert_eq!(
+        run(&["graph", "1MB/s", "--cap", "1TB", "--output", "json"]),
+        "{\"cap\":1000000000000,\"day\":11.574074074074074}\n"
+    );
+}
+
+#[test]
+fn test_log_pattern() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let log = "[1700000000] a sent 1MB\n\
+               [1700000030] b received 5MB\n\
+               [1700000059] c sent 2MB\n";
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["log", "--pattern", "[{time}] * sent {bytes}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(log.as_bytes())
+        .unwrap();
+    let x = child.wait_with_output().unwrap();
+    assert!(x.status.success());
+    assert!(String::from_utf8(x.stdout)
+        .unwrap()
+        .contains("total   : 3.000 MB"));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["log", "--window", "1s"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let log = "0 1\n1700000000 1\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(log.as_bytes())
+        .unwrap();
+    let x = child.wait_with_output().unwrap();
+    assert_eq!(x.status.code(), Some(2));
+}
+
+#[test]
+fn test_pipe_limit() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let x = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pipe", "--limit", "0 MB/s"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert_eq!(x.status.code(), Some(2));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rate"))
+        .args(["pipe", "--summary-only", "--limit", "1 Gb/s"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&[0; 1000]).unwrap();
+    let x = child.wait_with_output().unwrap();
+    assert!(x.status.success());
+    assert_eq!(x.stdout.len(), 1000);
+}